@@ -0,0 +1,86 @@
+use std::time::Duration;
+
+use reqwest::Method;
+use serde::Serialize;
+use serde_json::Value;
+use tauri::State;
+
+use crate::auth::Auth;
+use crate::client::AppClient;
+use crate::error::MigrationError;
+use crate::state::AppState;
+
+pub const DEFAULT_POLL_INTERVAL_SECS: u64 = 2;
+pub const DEFAULT_WAIT_TIMEOUT_SECS: u64 = 300;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ActivityResult {
+    pub activity_id: String,
+    // Terminal status as reported by R1, e.g. SUCCESS or FAIL
+    pub status: String,
+    pub success: bool,
+    pub activity: Value,
+}
+
+// Polls {api_url}/activities/{activity_id} every poll_interval_secs (default 2) until
+// the activity reaches a terminal status or timeout_secs (default 300) runs out, which
+// fails with MigrationError::Timeout. A failed activity is still Ok - check `success`.
+#[tauri::command]
+pub async fn wait_for_activity(
+    state: State<'_, AppState>,
+    api_url: Option<String>,
+    activity_id: String,
+    auth: Auth,
+    poll_interval_secs: Option<u64>,
+    timeout_secs: Option<u64>,
+) -> Result<ActivityResult, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let interval = poll_interval_secs.unwrap_or(DEFAULT_POLL_INTERVAL_SECS);
+    let timeout = timeout_secs.unwrap_or(DEFAULT_WAIT_TIMEOUT_SECS);
+    if interval == 0 || timeout == 0 {
+        return Err(MigrationError::invalid_input("poll_interval_secs and timeout_secs must be at least 1"));
+    }
+
+    poll(client, &config.api_url_or(api_url.as_deref())?, &auth, activity_id.trim(), Duration::from_secs(interval), Duration::from_secs(timeout)).await
+}
+
+pub async fn poll(client: &AppClient, api_url: &str, auth: &Auth, activity_id: &str, interval: Duration, timeout: Duration) -> Result<ActivityResult, MigrationError> {
+    if activity_id.is_empty() {
+        return Err(MigrationError::invalid_input("activity_id must not be empty"));
+    }
+    let url = format!("{}/activities/{}", api_url, activity_id);
+
+    let wait = async {
+        loop {
+            let activity = client.send_json(Method::GET, &url, auth, None, None).await?;
+            let status = activity.get("status").and_then(Value::as_str).unwrap_or_default().to_uppercase();
+            if let Some(success) = terminal(&status) {
+                tracing::info!("Activity {} finished with {}", activity_id, status);
+                return Ok(ActivityResult { activity_id: activity_id.to_string(), status, success, activity });
+            }
+            tracing::debug!("Activity {} is {}, checking again in {:?}", activity_id, status, interval);
+            tokio::time::sleep(interval).await;
+        }
+    };
+
+    tokio::time::timeout(timeout, wait)
+        .await
+        .unwrap_or(Err(MigrationError::Timeout { secs: timeout.as_secs(), connect: false, request_id: None }))
+}
+
+// Some(success) once the activity is done, None while it's still running
+fn terminal(status: &str) -> Option<bool> {
+    match status {
+        "SUCCESS" | "SUCCEEDED" | "COMPLETED" => Some(true),
+        "FAIL" | "FAILED" | "FAILURE" | "ERROR" | "CANCELLED" | "CANCELED" => Some(false),
+        _ => None,
+    }
+}
+
+// 202 responses name the activity to poll by its requestId
+pub fn activity_id(response: &Value) -> Option<String> {
+    ["requestId", "activityId", "id"]
+        .iter()
+        .find_map(|key| response.get(*key).and_then(Value::as_str))
+        .map(str::to_string)
+}
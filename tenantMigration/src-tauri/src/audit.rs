@@ -0,0 +1,124 @@
+use std::fs::{File, OpenOptions};
+use std::future::Future;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use reqwest::Method;
+use serde::Serialize;
+use serde_json::Value;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::auth::Auth;
+use crate::client::RawResponse;
+use crate::error::MigrationError;
+use crate::files;
+use crate::redact;
+
+tokio::task_local! {
+    // Set for the duration of a migration so AppClient::send_json can log every
+    // call without the log being threaded through each fetch/push helper
+    static CURRENT: Arc<AuditLog>;
+}
+
+// Append-only JSON Lines file, one entry per API call
+pub struct AuditLog {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+#[derive(Serialize)]
+struct AuditEntry<'a> {
+    timestamp: String,
+    method: &'a str,
+    url: &'a str,
+    // None when no response came back (network error, timeout)
+    status: Option<u16>,
+    resource_id: Option<String>,
+    token: String,
+    error: Option<String>,
+    // x-request-id sent with the call, to join this log with R1's server logs
+    request_id: Option<&'a str>,
+}
+
+impl AuditLog {
+    // New file under <app data>/audit, named after the run and its start time (see
+    // files::run_file_name)
+    pub fn create<R: Runtime>(app: &AppHandle<R>, label: &str) -> Result<Self, MigrationError> {
+        let dir = app
+            .path()
+            .app_data_dir()
+            .map_err(|e| MigrationError::Io { path: "audit".to_string(), message: format!("no app data directory: {}", e) })?
+            .join("audit");
+        std::fs::create_dir_all(&dir).map_err(|e| MigrationError::io(&dir, e))?;
+
+        let path = dir.join(files::run_file_name(label, "jsonl"));
+        let file = OpenOptions::new()
+            .create_new(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| MigrationError::io(&path, e))?;
+
+        tracing::info!("Writing audit log to {}", path.display());
+        Ok(Self { path, file: Mutex::new(file) })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn write(&self, entry: &AuditEntry) {
+        let line = match serde_json::to_string(entry) {
+            Ok(line) => line,
+            Err(e) => return tracing::warn!("Could not serialize audit entry: {}", e),
+        };
+        let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Err(e) = writeln!(file, "{}", line) {
+            tracing::warn!("Could not write to audit log {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+// Runs `f` with `log` as the audit log for every send_json call it makes
+pub async fn scope<F: Future>(log: Arc<AuditLog>, f: F) -> F::Output {
+    CURRENT.scope(log, f).await
+}
+
+// No-op outside scope()
+pub fn record(method: &Method, url: &str, auth: &Auth, result: &Result<RawResponse, MigrationError>) {
+    let _ = CURRENT.try_with(|log| {
+        let (status, resource_id, error) = match result {
+            Ok(response) => (Some(response.status), resource_id(&response.body), None),
+            Err(MigrationError::Http { status, .. }) | Err(MigrationError::Auth { status, .. }) => (Some(*status), None, Some(result_error(result))),
+            Err(MigrationError::Conflict { .. }) => (Some(412), None, Some(result_error(result))),
+            Err(_) => (None, None, Some(result_error(result))),
+        };
+
+        log.write(&AuditEntry {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            method: method.as_str(),
+            url,
+            status,
+            resource_id,
+            token: redact::mask_token(auth.secret()),
+            error,
+            request_id: match result {
+                Ok(response) => Some(response.request_id.as_str()),
+                Err(e) => e.request_id(),
+            },
+        });
+    });
+}
+
+fn result_error(result: &Result<RawResponse, MigrationError>) -> String {
+    result.as_ref().err().map(ToString::to_string).unwrap_or_default()
+}
+
+fn resource_id(body: &str) -> Option<String> {
+    let value: Value = serde_json::from_str(body).ok()?;
+    value
+        .get("id")
+        .or_else(|| value.get("tenant_id"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
@@ -0,0 +1,63 @@
+use reqwest::RequestBuilder;
+use serde::Deserialize;
+
+// Header R1 reads API keys from
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+// How commands authenticate, sent by the UI as `auth`: either
+// { "scheme": "bearer" | "api_key", "value": "..." } or, as the `token` commands
+// took before, a bare token string, which is a bearer token.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize)]
+#[serde(from = "AuthInput")]
+pub enum Auth {
+    Bearer(String),
+    ApiKey(String),
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum AuthInput {
+    Token(String),
+    Tagged(TaggedAuth),
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "scheme", content = "value", rename_all = "snake_case")]
+enum TaggedAuth {
+    Bearer(String),
+    ApiKey(String),
+}
+
+impl From<AuthInput> for Auth {
+    fn from(input: AuthInput) -> Self {
+        match input {
+            AuthInput::Token(token) | AuthInput::Tagged(TaggedAuth::Bearer(token)) => Auth::Bearer(token.trim().to_string()),
+            AuthInput::Tagged(TaggedAuth::ApiKey(key)) => Auth::ApiKey(key.trim().to_string()),
+        }
+    }
+}
+
+impl Auth {
+    // Adds the credential to a request; every request to R1 is built through this.
+    // An empty bearer token is left for AppClient to fill from the keychain.
+    pub fn apply(&self, request: RequestBuilder) -> RequestBuilder {
+        let (name, value) = self.header(str::to_string);
+        request.header(name, value)
+    }
+
+    // Header name and value, with the secret passed through `secret` first (e.g.
+    // redact::mask_token for previews)
+    pub fn header(&self, secret: impl FnOnce(&str) -> String) -> (&'static str, String) {
+        match self {
+            Auth::Bearer(token) => ("Authorization", format!("Bearer {}", secret(token))),
+            Auth::ApiKey(key) => (API_KEY_HEADER, secret(key)),
+        }
+    }
+
+    // The token or key itself, for masking in logs
+    pub fn secret(&self) -> &str {
+        match self {
+            Auth::Bearer(secret) | Auth::ApiKey(secret) => secret,
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use futures::future::BoxFuture;
+
+// The one place a request actually goes out. AppClient builds requests with
+// reqwest and hands them to a backend, so retries, limits and error mapping can be
+// exercised against a fake in tests.
+pub trait HttpBackend: Send + Sync {
+    fn send(&self, request: reqwest::Request) -> BoxFuture<'_, reqwest::Result<reqwest::Response>>;
+}
+
+impl HttpBackend for reqwest::Client {
+    fn send(&self, request: reqwest::Request) -> BoxFuture<'_, reqwest::Result<reqwest::Response>> {
+        Box::pin(self.execute(request))
+    }
+}
+
+#[cfg(test)]
+pub mod mock {
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    use futures::future::BoxFuture;
+    use reqwest::header::HeaderMap;
+    use reqwest::Method;
+    use serde_json::Value;
+
+    use super::HttpBackend;
+
+    // Non-JSON bodies (e.g. form posts) are kept as a JSON string
+    #[derive(Clone, Debug)]
+    pub struct RecordedRequest {
+        pub method: Method,
+        pub url: String,
+        pub headers: HeaderMap,
+        pub body: Option<Value>,
+    }
+
+    // Records every request and answers with the queued responses in order, then
+    // with empty 200s once the queue runs out
+    #[derive(Default)]
+    pub struct MockBackend {
+        requests: Mutex<Vec<RecordedRequest>>,
+        responses: Mutex<VecDeque<QueuedResponse>>,
+    }
+
+    struct QueuedResponse {
+        status: u16,
+        headers: Vec<(String, String)>,
+        body: String,
+    }
+
+    impl MockBackend {
+        pub fn respond(&self, status: u16, body: &str) -> &Self {
+            self.respond_with_headers(status, &[], body)
+        }
+
+        pub fn respond_with_headers(&self, status: u16, headers: &[(&str, &str)], body: &str) -> &Self {
+            let headers = headers.iter().map(|(name, value)| (name.to_string(), value.to_string())).collect();
+            self.responses.lock().unwrap().push_back(QueuedResponse { status, headers, body: body.to_string() });
+            self
+        }
+
+        pub fn requests(&self) -> Vec<RecordedRequest> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    impl HttpBackend for MockBackend {
+        fn send(&self, request: reqwest::Request) -> BoxFuture<'_, reqwest::Result<reqwest::Response>> {
+            let body = request
+                .body()
+                .and_then(|body| body.as_bytes())
+                .map(|bytes| serde_json::from_slice(bytes).unwrap_or_else(|_| Value::String(String::from_utf8_lossy(bytes).into_owned())));
+            self.requests.lock().unwrap().push(RecordedRequest {
+                method: request.method().clone(),
+                url: request.url().to_string(),
+                headers: request.headers().clone(),
+                body,
+            });
+
+            let queued = self.responses.lock().unwrap().pop_front();
+            let queued = queued.unwrap_or(QueuedResponse { status: 200, headers: Vec::new(), body: String::new() });
+            let mut response = http::Response::builder().status(queued.status);
+            for (name, value) in queued.headers {
+                response = response.header(name, value);
+            }
+            let response = response.body(queued.body).unwrap();
+            Box::pin(async move { Ok(reqwest::Response::from(response)) })
+        }
+    }
+}
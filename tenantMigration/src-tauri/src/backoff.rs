@@ -0,0 +1,78 @@
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+// Retry delays of `base * 2^attempt` plus a random 0..base of jitter, capped at
+// `max`. The jitter keeps parallel requests that failed together from all retrying
+// at the same instant.
+pub struct Backoff {
+    base: Duration,
+    max: Duration,
+    rng: StdRng,
+}
+
+impl Backoff {
+    pub fn new(base: Duration, max: Duration) -> Self {
+        Self { base, max, rng: StdRng::from_entropy() }
+    }
+
+    // Same seed, same delays - for tests
+    #[cfg(test)]
+    pub fn with_seed(base: Duration, max: Duration, seed: u64) -> Self {
+        Self { base, max, rng: StdRng::seed_from_u64(seed) }
+    }
+
+    // Delay before retry number `attempt`, counting from 0
+    pub fn delay(&mut self, attempt: u32) -> Duration {
+        let exponential = self.base.saturating_mul(2u32.saturating_pow(attempt));
+        let jitter = if self.base.is_zero() { Duration::ZERO } else { self.rng.gen_range(Duration::ZERO..self.base) };
+        exponential.saturating_add(jitter).min(self.max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: Duration = Duration::from_millis(250);
+    const MAX: Duration = Duration::from_secs(10);
+
+    #[test]
+    fn same_seed_gives_same_delays() {
+        let mut a = Backoff::with_seed(BASE, MAX, 42);
+        let mut b = Backoff::with_seed(BASE, MAX, 42);
+        for attempt in 0..5 {
+            assert_eq!(a.delay(attempt), b.delay(attempt));
+        }
+    }
+
+    #[test]
+    fn delay_is_exponential_plus_jitter_below_base() {
+        let mut backoff = Backoff::with_seed(BASE, MAX, 7);
+        for attempt in 0..5 {
+            let floor = BASE * 2u32.pow(attempt);
+            let delay = backoff.delay(attempt);
+            assert!(delay >= floor && delay < floor + BASE, "attempt {}: {:?}", attempt, delay);
+        }
+    }
+
+    #[test]
+    fn different_seeds_spread_retries() {
+        let delays: Vec<Duration> = (0..10).map(|seed| Backoff::with_seed(BASE, MAX, seed).delay(1)).collect();
+        assert!(delays.iter().any(|d| *d != delays[0]), "no jitter: {:?}", delays);
+    }
+
+    #[test]
+    fn delay_is_capped_and_does_not_overflow() {
+        let mut backoff = Backoff::with_seed(BASE, MAX, 1);
+        assert_eq!(backoff.delay(10), MAX);
+        assert_eq!(backoff.delay(u32::MAX), MAX);
+    }
+
+    #[test]
+    fn zero_base_means_no_delay() {
+        let mut backoff = Backoff::with_seed(Duration::ZERO, MAX, 1);
+        assert_eq!(backoff.delay(3), Duration::ZERO);
+    }
+}
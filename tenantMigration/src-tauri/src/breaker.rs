@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::error::MigrationError;
+
+// Consecutive failures to one host before calls to it fail fast
+pub const DEFAULT_THRESHOLD: u32 = 5;
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+// Per-host circuit breaker owned by AppClient. Connection errors, timeouts and
+// 502/503/504 count as failures; any other response means the host is up. After
+// `threshold` failures in a row the circuit opens and calls fail with CircuitOpen
+// without being sent. Once `cooldown` has passed a single probe call is let
+// through: success closes the circuit, failure opens it for another cooldown.
+pub struct CircuitBreaker {
+    settings: Mutex<(u32, Duration)>,
+    hosts: Mutex<HashMap<String, HostState>>,
+}
+
+#[derive(Default)]
+struct HostState {
+    failures: u32,
+    opened_at: Option<Instant>,
+    // When the half-open probe was let through; a probe that never reports back
+    // (its caller was cancelled) expires after another cooldown
+    probe_started: Option<Instant>,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self { settings: Mutex::new((DEFAULT_THRESHOLD, DEFAULT_COOLDOWN)), hosts: Mutex::new(HashMap::new()) }
+    }
+}
+
+impl CircuitBreaker {
+    pub fn configure(&self, threshold: u32, cooldown: Duration) {
+        *self.settings.lock().unwrap_or_else(PoisonError::into_inner) = (threshold, cooldown);
+    }
+
+    // Err(CircuitOpen) when a call to `host` must not be sent right now
+    pub fn allow(&self, host: &str) -> Result<(), MigrationError> {
+        self.allow_at(host, Instant::now())
+    }
+
+    pub fn record(&self, host: &str, failed: bool) {
+        self.record_at(host, failed, Instant::now())
+    }
+
+    fn allow_at(&self, host: &str, now: Instant) -> Result<(), MigrationError> {
+        let (_, cooldown) = *self.settings.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut hosts = self.hosts.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(state) = hosts.get_mut(host) else {
+            return Ok(());
+        };
+        let Some(opened_at) = state.opened_at else {
+            return Ok(());
+        };
+
+        let open_for = now.saturating_duration_since(opened_at);
+        let probe_running = state.probe_started.is_some_and(|started| now.saturating_duration_since(started) < cooldown);
+        if open_for >= cooldown && !probe_running {
+            tracing::info!("Circuit for {} half-open, letting a probe through", host);
+            state.probe_started = Some(now);
+            return Ok(());
+        }
+        Err(MigrationError::CircuitOpen { host: host.to_string(), retry_in_secs: cooldown.saturating_sub(open_for).as_secs().max(1) })
+    }
+
+    fn record_at(&self, host: &str, failed: bool, now: Instant) {
+        let (threshold, _) = *self.settings.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut hosts = self.hosts.lock().unwrap_or_else(PoisonError::into_inner);
+        if !failed {
+            if hosts.remove(host).is_some_and(|state| state.opened_at.is_some()) {
+                tracing::info!("Circuit for {} closed", host);
+            }
+            return;
+        }
+
+        let state = hosts.entry(host.to_string()).or_default();
+        state.failures += 1;
+        let probe_failed = state.probe_started.take().is_some();
+        if probe_failed || (state.opened_at.is_none() && state.failures >= threshold) {
+            tracing::warn!("Circuit for {} open after {} consecutive failures", host, state.failures);
+            state.opened_at = Some(now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_after_threshold_and_probes_after_cooldown() {
+        let breaker = CircuitBreaker::default();
+        breaker.configure(2, Duration::from_secs(10));
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        breaker.record_at("r1.example.test", true, at(0));
+        assert!(breaker.allow_at("r1.example.test", at(0)).is_ok());
+        breaker.record_at("r1.example.test", true, at(1));
+        assert!(matches!(breaker.allow_at("r1.example.test", at(2)), Err(MigrationError::CircuitOpen { retry_in_secs: 9, .. })));
+        // Other hosts are unaffected
+        assert!(breaker.allow_at("other.example.test", at(2)).is_ok());
+
+        // One probe after the cooldown; a second caller still fails fast
+        assert!(breaker.allow_at("r1.example.test", at(11)).is_ok());
+        assert!(breaker.allow_at("r1.example.test", at(11)).is_err());
+        // Failed probe reopens, a successful one closes
+        breaker.record_at("r1.example.test", true, at(12));
+        assert!(breaker.allow_at("r1.example.test", at(13)).is_err());
+        assert!(breaker.allow_at("r1.example.test", at(22)).is_ok());
+        breaker.record_at("r1.example.test", false, at(22));
+        assert!(breaker.allow_at("r1.example.test", at(22)).is_ok());
+    }
+}
@@ -0,0 +1,88 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use crate::error::MigrationError;
+
+tokio::task_local! {
+    // Set around one record's create so AppClient's retries can be counted
+    static RETRIES: Arc<AtomicU32>;
+}
+
+// Post-mortem of a bulk create (put_venues, put_aps). Skipped records aren't
+// attempted. With put_venues the "retry failed" action is the same call again with
+// the same run_id, which only re-sends what didn't go through.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct BulkSummary {
+    pub attempted: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    // Records that needed more than one attempt, whether they went through or not
+    pub retried: usize,
+    pub failures: Vec<BulkFailure>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct BulkFailure {
+    pub name: String,
+    pub attempts: u32,
+    // See MigrationError::is_retryable; false means sending it again as-is will fail
+    // the same way
+    pub retryable: bool,
+    pub error: MigrationError,
+}
+
+impl BulkSummary {
+    pub fn add(&mut self, name: &str, retries: u32, result: Result<(), &MigrationError>) {
+        self.attempted += 1;
+        if retries > 0 {
+            self.retried += 1;
+        }
+        match result {
+            Ok(()) => self.succeeded += 1,
+            Err(error) => {
+                self.failed += 1;
+                self.failures.push(BulkFailure { name: name.to_string(), attempts: retries + 1, retryable: error.is_retryable(), error: error.clone() });
+            }
+        }
+    }
+}
+
+// Runs `f` and returns how many times AppClient retried a request inside it
+pub async fn count_retries<F: Future>(f: F) -> (F::Output, u32) {
+    let counter = Arc::new(AtomicU32::new(0));
+    let output = RETRIES.scope(counter.clone(), f).await;
+    (output, counter.load(Ordering::Relaxed))
+}
+
+// Called by AppClient before every retry
+pub fn note_retry() {
+    let _ = RETRIES.try_with(|retries| retries.fetch_add(1, Ordering::Relaxed));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn counts_retries_and_classifies_failures() {
+        let ((), retries) = count_retries(async {
+            note_retry();
+            note_retry();
+        })
+        .await;
+        assert_eq!(retries, 2);
+
+        let mut summary = BulkSummary::default();
+        let unavailable = MigrationError::Http { status: 503, body: String::new(), attempts: 3, api_error: None, request_id: None };
+        let invalid = MigrationError::Http { status: 400, body: String::new(), attempts: 1, api_error: None, request_id: None };
+        summary.add("HQ", 1, Ok(()));
+        summary.add("Depot", 2, Err(&unavailable));
+        summary.add("Store", 0, Err(&invalid));
+
+        assert_eq!((summary.attempted, summary.succeeded, summary.failed, summary.retried), (3, 1, 2, 2));
+        assert_eq!(summary.failures.iter().map(|f| (f.attempts, f.retryable)).collect::<Vec<_>>(), [(3, true), (1, false)]);
+    }
+}
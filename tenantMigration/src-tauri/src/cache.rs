@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use reqwest::Method;
+use serde_json::Value;
+
+// Successful read responses of AppClient::send_json, kept for `ttl` so diff, plan,
+// migrate and verify against the same source don't fetch it four times. Off until
+// set_cache_ttl is called. Keys are coalesce::key, so different tokens or bodies
+// (e.g. query pages) never share an entry. A successful write drops the entries of
+// the tenant it was sent for, so reads after a create see it.
+#[derive(Default)]
+pub struct ResponseCache(Mutex<CacheInner>);
+
+#[derive(Default)]
+struct CacheInner {
+    // None = caching off
+    ttl: Option<Duration>,
+    entries: HashMap<String, CacheEntry>,
+}
+
+struct CacheEntry {
+    stored: Instant,
+    // x-rks-tenantid the response was fetched with
+    tenant_id: Option<String>,
+    value: Value,
+}
+
+impl ResponseCache {
+    // Turning the cache off also empties it
+    pub fn set_ttl(&self, ttl: Option<Duration>) {
+        let mut inner = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        inner.ttl = ttl;
+        if ttl.is_none() {
+            inner.entries.clear();
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<Value> {
+        self.get_at(key, Instant::now())
+    }
+
+    fn get_at(&self, key: &str, now: Instant) -> Option<Value> {
+        let mut inner = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let ttl = inner.ttl?;
+        let entry = inner.entries.get(key)?;
+        if now.saturating_duration_since(entry.stored) < ttl {
+            return Some(entry.value.clone());
+        }
+        inner.entries.remove(key);
+        None
+    }
+
+    pub fn insert(&self, key: String, tenant_id: Option<&str>, value: &Value) {
+        let mut inner = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if inner.ttl.is_some() {
+            let entry = CacheEntry { stored: Instant::now(), tenant_id: tenant_id.map(str::to_string), value: value.clone() };
+            inner.entries.insert(key, entry);
+        }
+    }
+
+    pub fn invalidate_tenant(&self, tenant_id: Option<&str>) {
+        let mut inner = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        inner.entries.retain(|_, entry| entry.tenant_id.as_deref() != tenant_id);
+    }
+
+    // Returns how many entries were dropped
+    pub fn clear(&self) -> usize {
+        let mut inner = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let cleared = inner.entries.len();
+        inner.entries.clear();
+        cleared
+    }
+}
+
+// GETs and R1's POST .../query searches only read; every other call is a write
+pub fn is_read(method: &Method, url: &str) -> bool {
+    let path = url.split(['?', '#']).next().unwrap_or(url).trim_end_matches('/');
+    *method == Method::GET || (*method == Method::POST && path.ends_with("/query"))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn entries_expire_and_writes_invalidate_their_tenant() {
+        let cache = ResponseCache::default();
+        cache.insert("a".to_string(), Some("t1"), &json!(1));
+        assert_eq!(cache.get("a"), None, "nothing is kept while caching is off");
+
+        cache.set_ttl(Some(Duration::from_secs(60)));
+        cache.insert("a".to_string(), Some("t1"), &json!(1));
+        cache.insert("b".to_string(), Some("t2"), &json!(2));
+        assert_eq!(cache.get("a"), Some(json!(1)));
+        assert_eq!(cache.get_at("a", Instant::now() + Duration::from_secs(61)), None);
+
+        cache.invalidate_tenant(Some("t2"));
+        assert_eq!(cache.get("b"), None);
+
+        assert!(is_read(&Method::POST, "https://api.example.test/venues/query"));
+        assert!(!is_read(&Method::POST, "https://api.example.test/venues"));
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::sync::{PoisonError, RwLock};
+
+use reqwest::Method;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::State;
+
+use crate::apiurl;
+use crate::auth::Auth;
+use crate::client::AppClient;
+use crate::error::MigrationError;
+use crate::state::AppState;
+
+// Collection paths that were renamed between R1 API versions, current name first
+const TENANT_PATHS: &[&str] = &["mspCustomers", "tenants"];
+// Where a single tenant is read from ({path}/{id} and {path}/self), which isn't always
+// the collection tenants are listed and created on
+const TENANT_RECORD_PATHS: &[&str] = &["tenants", "mspCustomers"];
+const WIFI_NETWORK_PATHS: &[&str] = &["wifiNetworks", "wlans"];
+
+// Which path conventions a deployment uses. Commands build URLs from
+// AppClient::paths(api_url), which is the current names until detect_capabilities
+// has probed that api_url.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub struct ApiPaths {
+    pub tenants: &'static str,
+    pub tenant_records: &'static str,
+    pub wifi_networks: &'static str,
+}
+
+impl Default for ApiPaths {
+    fn default() -> Self {
+        Self { tenants: TENANT_PATHS[0], tenant_records: TENANT_RECORD_PATHS[0], wifi_networks: WIFI_NETWORK_PATHS[0] }
+    }
+}
+
+// Detected ApiPaths per API base URL, held by AppClient
+#[derive(Default)]
+pub struct PathStore(RwLock<HashMap<String, ApiPaths>>);
+
+impl PathStore {
+    pub fn get(&self, api_url: &str) -> ApiPaths {
+        let paths = self.0.read().unwrap_or_else(PoisonError::into_inner);
+        paths.get(&store_key(api_url)).copied().unwrap_or_default()
+    }
+
+    pub fn set(&self, api_url: &str, paths: ApiPaths) {
+        self.0.write().unwrap_or_else(PoisonError::into_inner).insert(store_key(api_url), paths);
+    }
+}
+
+// Callers pass api_url both before and after apiurl::normalize
+fn store_key(api_url: &str) -> String {
+    apiurl::normalize(api_url).unwrap_or_else(|_| api_url.to_string())
+}
+
+// Probes both names of each renamed collection once and records the ones this
+// deployment answers on, for every later command sent to `api_url`. A name that
+// 404s is skipped; when neither answers the current name is kept. A rejected token
+// fails the detection rather than being mistaken for a missing path.
+#[tauri::command]
+pub async fn detect_capabilities(state: State<'_, AppState>, api_url: Option<String>, auth: Auth) -> Result<ApiPaths, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let api_url = config.api_url_or(api_url.as_deref())?;
+    detect(client, &api_url, &auth).await
+}
+
+pub async fn detect(client: &AppClient, api_url: &str, auth: &Auth) -> Result<ApiPaths, MigrationError> {
+    let tenants = first_answering(client, auth, TENANT_PATHS, Method::GET, |path| format!("{}/{}?page=1&pageSize=1", api_url, path), None).await?;
+    let query = json!({ "fields": ["id"], "page": 1, "pageSize": 1 });
+    let wifi_networks = first_answering(client, auth, WIFI_NETWORK_PATHS, Method::POST, |path| format!("{}/{}/query", api_url, path), Some(&query)).await?;
+    let tenant_records = first_answering(client, auth, TENANT_RECORD_PATHS, Method::GET, |path| format!("{}/{}/self", api_url, path), None).await?;
+
+    let paths = ApiPaths { tenants, tenant_records, wifi_networks };
+    tracing::info!("{} uses /{}, /{} and /{}", api_url, paths.tenants, paths.tenant_records, paths.wifi_networks);
+    client.set_paths(api_url, paths);
+    Ok(paths)
+}
+
+async fn first_answering(
+    client: &AppClient,
+    auth: &Auth,
+    candidates: &'static [&'static str],
+    method: Method,
+    url_for: impl Fn(&str) -> String,
+    body: Option<&Value>,
+) -> Result<&'static str, MigrationError> {
+    for path in candidates {
+        match client.probe_with(method.clone(), &url_for(path), auth, body).await {
+            Err(MigrationError::Http { status: 404, .. }) => tracing::debug!("/{} not found", path),
+            Err(e @ MigrationError::Auth { status: 401, .. }) => return Err(e),
+            // Anything else (including 400 or 403) came from a route that exists
+            _ => return Ok(path),
+        }
+    }
+    tracing::warn!("None of {:?} answered, keeping /{}", candidates, candidates[0]);
+    Ok(candidates[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::backend::mock::MockBackend;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn detects_renamed_paths_and_uses_them_afterwards() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(404, "").respond(200, r#"{"data":[]}"#).respond(404, "").respond(200, r#"{"data":[]}"#).respond(404, "").respond(200, "{}");
+        let client = AppClient::with_backend(mock.clone()).unwrap();
+        let api_url = "https://api.example.test";
+        assert_eq!(client.paths(api_url), ApiPaths::default());
+
+        let paths = detect(&client, api_url, &Auth::Bearer("token".to_string())).await.unwrap();
+        assert_eq!(paths, ApiPaths { tenants: "tenants", tenant_records: "mspCustomers", wifi_networks: "wlans" });
+        assert_eq!(client.paths("api.example.test/"), paths);
+
+        let urls: Vec<String> = mock.requests().into_iter().map(|r| r.url).collect();
+        assert_eq!(urls[1], format!("{}/tenants?page=1&pageSize=1", api_url));
+        assert_eq!(urls[3], format!("{}/wlans/query", api_url));
+        assert_eq!(urls[5], format!("{}/mspCustomers/self", api_url));
+    }
+}
@@ -0,0 +1,201 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Manager, Runtime};
+
+use crate::error::MigrationError;
+use crate::files;
+use crate::migration::{ResourceKind, ResourceRef};
+use crate::transform::TransformRule;
+
+// Bumped whenever the line format changes; older or newer files are refused
+// rather than half-understood
+pub const CHECKPOINT_VERSION: u32 = 1;
+
+// First line of a checkpoint file: everything needed to rerun the push without the
+// source. Tokens are never written, resume_migration takes the target token again.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CheckpointHeader {
+    pub version: u32,
+    pub tenant_id: String,
+    pub target_api_url: String,
+    // The fetched source data, written once next to the checkpoint in export_tenant format
+    pub snapshot_file: String,
+    pub tenant_payload: Value,
+    pub transforms: Vec<TransformRule>,
+    // Existing tenant the run writes into instead of creating one
+    #[serde(default)]
+    pub target_tenant_id: Option<String>,
+}
+
+// One line per resource created on the target. `source_key` is the record's id on
+// the source (serial number or name when it has none), which is how a resumed run
+// recognises it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PushedEntry {
+    pub kind: ResourceKind,
+    pub source_key: String,
+    // None when the target didn't return an id
+    pub created: Option<ResourceRef>,
+}
+
+// JSON Lines file appended to after every successful create, so an interrupted run
+// (crash, closed window, failed resource) can continue where it stopped
+pub struct Checkpoint {
+    path: PathBuf,
+    file: Mutex<File>,
+    header: CheckpointHeader,
+    pushed: Vec<PushedEntry>,
+}
+
+impl Checkpoint {
+    // Fails rather than truncating if `path` already exists, since that file may be
+    // another run's resume data
+    pub fn create(path: &Path, header: CheckpointHeader) -> Result<Self, MigrationError> {
+        let mut file = OpenOptions::new().write(true).create_new(true).open(path).map_err(|e| MigrationError::io(path, e))?;
+        writeln!(file, "{}", serde_json::to_string(&header)?).map_err(|e| MigrationError::io(path, e))?;
+        tracing::info!("Writing migration checkpoint to {}", path.display());
+        Ok(Self { path: path.to_path_buf(), file: Mutex::new(file), header, pushed: Vec::new() })
+    }
+
+    // Reads a checkpoint back and keeps it open for appending
+    pub fn open(path: &Path) -> Result<Self, MigrationError> {
+        let raw = std::fs::read_to_string(path).map_err(|e| MigrationError::io(path, e))?;
+        let mut lines = raw.lines().filter(|line| !line.trim().is_empty());
+
+        let first: Value = serde_json::from_str(lines.next().unwrap_or("{}"))?;
+        let version = first.get("version").and_then(Value::as_u64);
+        if version != Some(CHECKPOINT_VERSION as u64) {
+            return Err(MigrationError::invalid_input(format!(
+                "{} is not a version {} migration checkpoint (found version {})",
+                path.display(),
+                CHECKPOINT_VERSION,
+                version.map(|v| v.to_string()).unwrap_or_else(|| "none".to_string())
+            )));
+        }
+        let header: CheckpointHeader = serde_json::from_value(first)?;
+
+        // A crash mid-write can leave a truncated last line; that resource is
+        // simply created again
+        let mut pushed = Vec::new();
+        for line in lines {
+            match serde_json::from_str(line) {
+                Ok(entry) => pushed.push(entry),
+                Err(e) => tracing::warn!("Ignoring unreadable checkpoint line in {}: {}", path.display(), e),
+            }
+        }
+
+        let file = OpenOptions::new().append(true).open(path).map_err(|e| MigrationError::io(path, e))?;
+        Ok(Self { path: path.to_path_buf(), file: Mutex::new(file), header, pushed })
+    }
+
+    pub fn header(&self) -> &CheckpointHeader {
+        &self.header
+    }
+
+    // Entries already in the file when it was opened
+    pub fn pushed(&self) -> &[PushedEntry] {
+        &self.pushed
+    }
+
+    // Best-effort like the audit log: a full disk shouldn't fail the migration,
+    // it only means a resume would create that resource again
+    pub fn append(&self, entry: &PushedEntry) {
+        let line = match serde_json::to_string(entry) {
+            Ok(line) => line,
+            Err(e) => return tracing::warn!("Could not serialize checkpoint entry: {}", e),
+        };
+        let mut file = self.file.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Err(e) = writeln!(file, "{}", line).and_then(|_| file.flush()) {
+            tracing::warn!("Could not write to checkpoint {}: {}", self.path.display(), e);
+        }
+    }
+
+    // A finished run has nothing to resume
+    pub fn remove(self) {
+        for path in [self.path.as_path(), Path::new(&self.header.snapshot_file)] {
+            if let Err(e) = std::fs::remove_file(path) {
+                tracing::warn!("Could not remove {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+// New path under <app data>/checkpoints, named after the tenant and the start time
+// (see files::run_file_name)
+pub fn new_path<R: Runtime>(app: &AppHandle<R>, tenant_id: &str) -> Result<PathBuf, MigrationError> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| MigrationError::Io { path: "checkpoints".to_string(), message: format!("no app data directory: {}", e) })?
+        .join("checkpoints");
+    std::fs::create_dir_all(&dir).map_err(|e| MigrationError::io(&dir, e))?;
+    Ok(dir.join(files::run_file_name(tenant_id, "jsonl")))
+}
+
+// Where the source snapshot for the checkpoint at `path` is kept
+pub fn snapshot_path(path: &Path) -> PathBuf {
+    path.with_extension("snapshot.json")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path() -> PathBuf {
+        std::env::temp_dir().join(format!("checkpoint-{}.jsonl", uuid::Uuid::new_v4()))
+    }
+
+    fn header() -> CheckpointHeader {
+        CheckpointHeader {
+            version: CHECKPOINT_VERSION,
+            tenant_id: "t1".to_string(),
+            target_api_url: "https://api.example.test".to_string(),
+            snapshot_file: "unused.snapshot.json".to_string(),
+            tenant_payload: serde_json::json!({ "name": "Tenant" }),
+            transforms: Vec::new(),
+            target_tenant_id: None,
+        }
+    }
+
+    #[test]
+    fn reopened_checkpoint_has_pushed_entries() {
+        let path = temp_path();
+        let checkpoint = Checkpoint::create(&path, header()).unwrap();
+        checkpoint.append(&PushedEntry { kind: ResourceKind::Venue, source_key: "v1".to_string(), created: None });
+        drop(checkpoint);
+
+        let reopened = Checkpoint::open(&path).unwrap();
+        assert_eq!(reopened.header().tenant_id, "t1");
+        assert_eq!(reopened.pushed().len(), 1);
+        assert_eq!(reopened.pushed()[0].source_key, "v1");
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn never_overwrites_an_existing_checkpoint() {
+        let path = temp_path();
+        let checkpoint = Checkpoint::create(&path, header()).unwrap();
+        checkpoint.append(&PushedEntry { kind: ResourceKind::Venue, source_key: "v1".to_string(), created: None });
+        drop(checkpoint);
+
+        assert!(matches!(Checkpoint::create(&path, header()), Err(MigrationError::Io { .. })));
+        assert_eq!(Checkpoint::open(&path).unwrap().pushed().len(), 1);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn refuses_other_versions() {
+        let path = temp_path();
+        std::fs::write(&path, "{\"version\":2}\n").unwrap();
+        let Err(MigrationError::InvalidInput { message }) = Checkpoint::open(&path) else {
+            panic!("expected InvalidInput");
+        };
+        assert!(message.contains("found version 2"), "{}", message);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,1073 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT_LANGUAGE, AUTHORIZATION, CONTENT_TYPE, RETRY_AFTER};
+use reqwest::{Method, StatusCode, Url};
+use serde_json::Value;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tokio_util::io::StreamReader;
+
+use crate::audit;
+use crate::auth::Auth;
+use crate::backend::HttpBackend;
+use crate::backoff::Backoff;
+use crate::breaker::CircuitBreaker;
+use crate::bulk;
+use crate::cache::{self, ResponseCache};
+use crate::capabilities::{ApiPaths, PathStore};
+use crate::coalesce::{self, Coalescer};
+use crate::concurrency;
+use crate::keychain;
+use crate::error::{ApiErrorBody, MigrationError, NetworkFailure};
+use crate::oauth::{self, TokenStore};
+use crate::ratelimit::RateLimiter;
+use crate::redact;
+use crate::request_id;
+use crate::timing::{self, CallTiming};
+use crate::validation::ValidationProblem;
+
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+// Unreachable hosts fail after this instead of using up the whole request timeout
+const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+const TIMEOUT_ENV_VAR: &str = "R1_HTTP_TIMEOUT_SECS";
+
+// 4 attempts = the initial call plus retries after roughly 250ms, 500ms and 1s
+// (each plus up to 250ms of jitter, see backoff.rs)
+const DEFAULT_MAX_ATTEMPTS: u32 = 4;
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(250);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+// 429s are retried separately from the transient-failure budget above
+const MAX_RATE_LIMIT_RETRIES: u32 = 5;
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(5);
+// Don't let a misbehaving gateway park a command for minutes
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+// Largest response body read into memory; a full page of 500 APs is around 1MB
+const DEFAULT_MAX_RESPONSE_BYTES: u64 = 64 * 1024 * 1024;
+// Largest JSON payload sent; tenant and resource creates are a few KB
+const DEFAULT_MAX_REQUEST_BYTES: u64 = 1024 * 1024;
+// How often download reports progress
+const DOWNLOAD_PROGRESS_BYTES: u64 = 4 * 1024 * 1024;
+
+// R1 localizes error messages; English is what global support can read
+const DEFAULT_ACCEPT_LANGUAGE: &str = "en-US";
+
+// Managed by the client itself or per call; set_extra_headers refuses them
+const RESERVED_HEADERS: &[&str] = &["host", "content-length", "transfer-encoding", request_id::HEADER];
+
+// Checked in this order; the lowercase form is what most Linux tooling sets
+const PROXY_ENV_VARS: [&str; 2] = ["HTTPS_PROXY", "https_proxy"];
+// PEM file with an extra root CA, e.g. for a staging gateway on an internal CA
+const CA_CERT_ENV_VAR: &str = "R1_CA_CERT";
+// "auto", "http1" or "http2", see HttpVersion
+const HTTP_VERSION_ENV_VAR: &str = "R1_HTTP_VERSION";
+
+// Which HTTP version to speak to the API. What to suggest to users:
+// - Http1 when calls connect but then hang until the timeout, or fail with "connection
+//   reset"/"stream error" mid-query. Seen behind TLS-inspecting proxies and firewalls
+//   that accept HTTP/2 but mishandle long or concurrent streams.
+// - Http2 when a proxy negotiates HTTP/1.1 and large paged queries are slow because
+//   every parallel request needs its own connection. Fails outright against a
+//   proxy that only speaks HTTP/1.1, so only try it if Auto works.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HttpVersion {
+    // Let TLS (ALPN) pick; HTTP/2 when the gateway offers it
+    #[default]
+    Auto,
+    Http1,
+    // HTTP/2 without negotiation ("prior knowledge")
+    Http2,
+}
+
+impl HttpVersion {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "auto" => Some(HttpVersion::Auto),
+            "1" | "1.1" | "http1" | "http/1.1" => Some(HttpVersion::Http1),
+            "2" | "http2" | "http/2" => Some(HttpVersion::Http2),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RawResponse {
+    pub status: u16,
+    pub headers: HeaderMap,
+    pub body: String,
+    // The x-request-id this was sent with
+    pub request_id: String,
+    pub timing: CallTiming,
+}
+
+// What the *_verbose commands return: the successful response with its headers, so
+// the frontend can read Location, ETag, rate-limit or paging headers. Header names are
+// lowercase; repeated headers are joined with ", ".
+#[derive(Debug, serde::Serialize)]
+pub struct VerboseResponse {
+    pub status: u16,
+    pub headers: HashMap<String, String>,
+    pub body: String,
+}
+
+impl From<RawResponse> for VerboseResponse {
+    fn from(response: RawResponse) -> Self {
+        let mut headers: HashMap<String, String> = HashMap::new();
+        for (name, value) in &response.headers {
+            let value = String::from_utf8_lossy(value.as_bytes());
+            headers
+                .entry(name.as_str().to_string())
+                .and_modify(|joined| {
+                    joined.push_str(", ");
+                    joined.push_str(&value);
+                })
+                .or_insert_with(|| value.into_owned());
+        }
+        Self { status: response.status, headers, body: response.body }
+    }
+}
+
+// One reqwest::Client for the whole app, held in AppState (see state.rs).
+// Cloning/re-creating clients throws away the connection pool, so commands
+// should always go through this instead of calling reqwest::Client::new().
+// The client is only rebuilt when a connection setting (e.g. the proxy) changes.
+pub struct AppClient {
+    http: RwLock<reqwest::Client>,
+    // Sends instead of `http` when set; requests are still built with `http`
+    backend: Option<Arc<dyn HttpBackend>>,
+    settings: Mutex<ConnectionSettings>,
+    timeout_secs: AtomicU64,
+    max_attempts: AtomicU32,
+    max_response_bytes: AtomicU64,
+    max_request_bytes: AtomicU64,
+    // Sent as Accept-Language on every request
+    accept_language: RwLock<HeaderValue>,
+    // Deployment-specific headers added to every request, see set_extra_headers
+    extra_headers: RwLock<HeaderMap>,
+    tokens: TokenStore,
+    breaker: CircuitBreaker,
+    inflight: Coalescer<Result<RawResponse, MigrationError>>,
+    limiter: RateLimiter,
+    cache: ResponseCache,
+    paths: PathStore,
+    // Shared by every snapshot's resource fetches, see fetch_slot
+    fetch_slots: Semaphore,
+    // Sent for requests built with an empty token, see keychain.rs. The outer None
+    // means not looked up yet, so the keychain is only touched when it's needed.
+    fallback_token: Mutex<Option<Option<String>>>,
+}
+
+// Settings baked into the reqwest::Client at build time
+#[derive(Clone, Debug)]
+struct ConnectionSettings {
+    // TCP connect (and proxy handshake) limit; `timeout_secs` still caps the whole call
+    connect_timeout: Duration,
+    // http://, https:// or socks5:// URL, optionally with user:pass@ for proxy auth
+    proxy: Option<String>,
+    // Trusted in addition to the built-in roots
+    ca_cert: Option<reqwest::Certificate>,
+    // DANGER: disables TLS certificate verification entirely. Test environments only.
+    danger_accept_invalid_certs: bool,
+    http_version: HttpVersion,
+}
+
+impl ConnectionSettings {
+    // A CA cert named in R1_CA_CERT that can't be loaded is logged and skipped
+    // rather than stopping the app from starting
+    fn from_env() -> Self {
+        let proxy = PROXY_ENV_VARS
+            .iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .map(|v| v.trim().to_string())
+            .find(|v| !v.is_empty());
+
+        let ca_cert = std::env::var(CA_CERT_ENV_VAR)
+            .ok()
+            .filter(|path| !path.trim().is_empty())
+            .and_then(|path| match load_ca_cert(path.trim()) {
+                Ok(cert) => {
+                    tracing::info!("Trusting extra CA certificate from {}", path.trim());
+                    Some(cert)
+                }
+                Err(e) => {
+                    tracing::warn!("Ignoring {}: {}", CA_CERT_ENV_VAR, e);
+                    None
+                }
+            });
+
+        let http_version = match std::env::var(HTTP_VERSION_ENV_VAR) {
+            Ok(value) if !value.trim().is_empty() => HttpVersion::parse(&value).unwrap_or_else(|| {
+                tracing::warn!("Ignoring {}={:?}, expected auto, http1 or http2", HTTP_VERSION_ENV_VAR, value);
+                HttpVersion::Auto
+            }),
+            _ => HttpVersion::Auto,
+        };
+
+        Self {
+            connect_timeout: Duration::from_secs(DEFAULT_CONNECT_TIMEOUT_SECS),
+            proxy,
+            ca_cert,
+            danger_accept_invalid_certs: false,
+            http_version,
+        }
+    }
+
+    fn build_client(&self) -> Result<reqwest::Client, reqwest::Error> {
+        let mut builder = reqwest::Client::builder()
+            .pool_idle_timeout(Duration::from_secs(90))
+            .pool_max_idle_per_host(16)
+            .tcp_keepalive(Duration::from_secs(60))
+            .connect_timeout(self.connect_timeout)
+            // Sends Accept-Encoding: gzip, deflate and decompresses before the body is
+            // read, so size limits apply to the decoded JSON. Large query pages are
+            // repetitive JSON and compress well. Setting Accept-Encoding by hand would
+            // turn the decompression off.
+            .gzip(true)
+            .deflate(true);
+
+        // reqwest takes proxy credentials from the URL itself
+        if let Some(proxy) = &self.proxy {
+            builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+        }
+        if let Some(cert) = &self.ca_cert {
+            builder = builder.add_root_certificate(cert.clone());
+        }
+        if self.danger_accept_invalid_certs {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        builder = match self.http_version {
+            HttpVersion::Auto => builder,
+            HttpVersion::Http1 => builder.http1_only(),
+            HttpVersion::Http2 => builder.http2_prior_knowledge(),
+        };
+
+        builder.build()
+    }
+}
+
+impl AppClient {
+    pub fn new() -> Result<Self, reqwest::Error> {
+        let settings = ConnectionSettings::from_env();
+        let http = settings.build_client()?;
+        if let Some(proxy) = &settings.proxy {
+            tracing::info!("Using proxy {} from environment", redact_proxy(proxy));
+        }
+
+        // Fall back to the default if the env var is missing, unparsable or zero
+        let timeout_secs = std::env::var(TIMEOUT_ENV_VAR)
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|secs| *secs > 0)
+            .unwrap_or(DEFAULT_TIMEOUT_SECS);
+
+        Ok(Self {
+            http: RwLock::new(http),
+            backend: None,
+            settings: Mutex::new(settings),
+            timeout_secs: AtomicU64::new(timeout_secs),
+            max_attempts: AtomicU32::new(DEFAULT_MAX_ATTEMPTS),
+            max_response_bytes: AtomicU64::new(DEFAULT_MAX_RESPONSE_BYTES),
+            max_request_bytes: AtomicU64::new(DEFAULT_MAX_REQUEST_BYTES),
+            accept_language: RwLock::new(HeaderValue::from_static(DEFAULT_ACCEPT_LANGUAGE)),
+            extra_headers: RwLock::new(HeaderMap::new()),
+            tokens: TokenStore::default(),
+            breaker: CircuitBreaker::default(),
+            inflight: Coalescer::default(),
+            limiter: RateLimiter::default(),
+            cache: ResponseCache::default(),
+            paths: PathStore::default(),
+            fetch_slots: Semaphore::new(concurrency::DEFAULT_MAX_IN_FLIGHT),
+            fallback_token: Mutex::new(None),
+        })
+    }
+
+    // Same settings as new(), but every request goes to `backend`
+    #[cfg(test)]
+    pub fn with_backend(backend: Arc<dyn HttpBackend>) -> Result<Self, reqwest::Error> {
+        Ok(Self { backend: Some(backend), ..Self::new()? })
+    }
+
+    // Applied per request with RequestBuilder::timeout so it can be changed at runtime
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.load(Ordering::Relaxed))
+    }
+
+    pub fn set_timeout(&self, secs: u64) {
+        self.timeout_secs.store(secs, Ordering::Relaxed);
+    }
+
+    pub fn connect_timeout(&self) -> Duration {
+        self.settings.lock().unwrap_or_else(PoisonError::into_inner).connect_timeout
+    }
+
+    // Baked into the reqwest::Client, so changing it rebuilds the client
+    pub fn set_connect_timeout(&self, secs: u64) -> Result<(), MigrationError> {
+        self.update_settings(|settings| settings.connect_timeout = Duration::from_secs(secs))
+            .map_err(|e| MigrationError::invalid_input(format!("could not rebuild HTTP client: {}", e)))
+    }
+
+    // Failures in a row before a host's circuit opens, and how long it stays open
+    pub fn set_circuit_breaker(&self, threshold: u32, cooldown: Duration) {
+        self.breaker.configure(threshold, cooldown);
+    }
+
+    // At most `rps` requests per second with bursts of up to `burst`; None removes the limit
+    pub fn set_rate_limit(&self, rps: Option<f64>, burst: u32) {
+        self.limiter.configure(rps, burst);
+    }
+
+    // How long send_json reuses read responses; None turns the cache off
+    pub fn set_cache_ttl(&self, ttl: Option<Duration>) {
+        self.cache.set_ttl(ttl);
+    }
+
+    pub fn clear_cache(&self) -> usize {
+        self.cache.clear()
+    }
+
+    // Collection paths to use with `api_url`, see capabilities::detect
+    pub fn paths(&self, api_url: &str) -> ApiPaths {
+        self.paths.get(api_url)
+    }
+
+    pub fn set_paths(&self, api_url: &str, paths: ApiPaths) {
+        self.paths.set(api_url, paths);
+    }
+
+    // Held by each resource fetch of snapshot::fetch_snapshot, so however many snapshots
+    // are read side by side (export_all_tenants runs several) at most
+    // concurrency::DEFAULT_MAX_IN_FLIGHT of them page at once
+    pub async fn fetch_slot(&self) -> SemaphorePermit<'_> {
+        self.fetch_slots.acquire().await.expect("fetch_slots is never closed")
+    }
+
+    pub fn set_fallback_token(&self, token: Option<String>) {
+        *self.fallback_token.lock().unwrap_or_else(PoisonError::into_inner) = Some(token);
+    }
+
+    // Commands pass the token the UI sent straight into a Bearer header; an empty one
+    // is swapped for R1_TOKEN or the keychain token here, just before sending
+    fn fill_token(&self, request: &mut reqwest::Request) {
+        let empty = request.headers().get(AUTHORIZATION).and_then(|v| v.to_str().ok()).is_some_and(|v| v.trim() == "Bearer");
+        if !empty {
+            return;
+        }
+        let token = self.fallback_token.lock().unwrap_or_else(PoisonError::into_inner).get_or_insert_with(keychain::fallback_token).clone();
+        let Some(mut value) = token.and_then(|token| HeaderValue::from_str(&format!("Bearer {}", token)).ok()) else {
+            tracing::debug!("Request has no token and none is stored");
+            return;
+        };
+        value.set_sensitive(true);
+        request.headers_mut().insert(AUTHORIZATION, value);
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts.load(Ordering::Relaxed)
+    }
+
+    pub fn set_max_attempts(&self, attempts: u32) {
+        self.max_attempts.store(attempts, Ordering::Relaxed);
+    }
+
+    pub fn tokens(&self) -> &TokenStore {
+        &self.tokens
+    }
+
+    pub fn set_max_response_bytes(&self, bytes: u64) {
+        self.max_response_bytes.store(bytes, Ordering::Relaxed);
+    }
+
+    pub fn set_max_request_bytes(&self, bytes: u64) {
+        self.max_request_bytes.store(bytes, Ordering::Relaxed);
+    }
+
+    // e.g. "de-DE" or "de-DE, en;q=0.8"; None goes back to en-US
+    pub fn set_accept_language(&self, language: Option<&str>) -> Result<(), MigrationError> {
+        let value = match language {
+            Some(language) => HeaderValue::from_str(language)
+                .map_err(|_| MigrationError::invalid_input(format!("invalid Accept-Language value: {}", language)))?,
+            None => HeaderValue::from_static(DEFAULT_ACCEPT_LANGUAGE),
+        };
+        *self.accept_language.write().unwrap_or_else(PoisonError::into_inner) = value;
+        Ok(())
+    }
+
+    fn accept_language(&self) -> HeaderValue {
+        self.accept_language.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    // Replaces the headers added to every request, e.g. x-rks-version or an API gateway
+    // key. They win over a command's own headers of the same name, which is why
+    // Authorization is refused unless `allow_authorization` is set. All problems are
+    // reported together and nothing changes unless every header is valid.
+    pub fn set_extra_headers(&self, headers: &HashMap<String, String>, allow_authorization: bool) -> Result<(), MigrationError> {
+        let mut map = HeaderMap::new();
+        let mut problems = Vec::new();
+        for (name, value) in headers {
+            let Ok(header) = HeaderName::from_bytes(name.trim().as_bytes()) else {
+                problems.push(ValidationProblem::new(name, "not a valid header name"));
+                continue;
+            };
+            if RESERVED_HEADERS.contains(&header.as_str()) {
+                problems.push(ValidationProblem::new(name, "is set by the client and can't be overridden"));
+                continue;
+            }
+            if header == AUTHORIZATION && !allow_authorization {
+                problems.push(ValidationProblem::new(name, "would replace the bearer token; pass allow_authorization to do that"));
+                continue;
+            }
+            match HeaderValue::from_str(value.trim()) {
+                Ok(value) => {
+                    map.insert(header, value);
+                }
+                Err(_) => problems.push(ValidationProblem::new(name, "value contains characters not allowed in a header")),
+            }
+        }
+        if !problems.is_empty() {
+            return Err(MigrationError::Validation { problems });
+        }
+
+        tracing::info!("Extra headers set: {:?}", map.keys().map(HeaderName::as_str).collect::<Vec<_>>());
+        *self.extra_headers.write().unwrap_or_else(PoisonError::into_inner) = map;
+        Ok(())
+    }
+
+    // What every request gets on top of its own headers, apart from x-request-id
+    pub fn default_headers(&self) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT_LANGUAGE, self.accept_language());
+        headers.extend(self.extra_headers());
+        headers
+    }
+
+    fn extra_headers(&self) -> HeaderMap {
+        self.extra_headers.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    // `body` serialized once for every attempt of a request: execute's `build` closes
+    // over these bytes and sends .body(bytes.clone()), so a retry resends exactly
+    // what the first attempt did. A payload over the request size limit is rejected
+    // before anything is sent.
+    pub fn request_body(&self, body: &Value) -> Result<Vec<u8>, MigrationError> {
+        let limit = self.max_request_bytes.load(Ordering::Relaxed);
+        let bytes = serde_json::to_vec(body)?;
+        let size = bytes.len() as u64;
+        if size > limit {
+            return Err(MigrationError::RequestTooLarge { size, limit });
+        }
+        Ok(bytes)
+    }
+
+    // Routes all traffic through `proxy`; None goes back to reqwest's default of the
+    // system proxy variables (HTTP_PROXY, ALL_PROXY, NO_PROXY). The new
+    // client replaces the old one only if it builds, so a bad URL changes nothing.
+    pub fn set_proxy(&self, proxy: Option<String>) -> Result<(), MigrationError> {
+        self.update_settings(|settings| settings.proxy = proxy.clone())
+            .map_err(|e| MigrationError::invalid_input(format!("invalid proxy URL: {}", e)))?;
+        match &proxy {
+            Some(proxy) => tracing::info!("Using proxy {}", redact_proxy(proxy)),
+            None => tracing::info!("Explicit proxy cleared"),
+        }
+        Ok(())
+    }
+
+    // Trusts the PEM root certificate at `path` in addition to the built-in roots,
+    // or drops a previously added one when None
+    pub fn set_ca_cert(&self, path: Option<&str>) -> Result<(), MigrationError> {
+        let cert = path.map(load_ca_cert).transpose()?;
+        self.update_settings(|settings| settings.ca_cert = cert.clone())
+            .map_err(|e| MigrationError::invalid_input(format!("could not use CA certificate: {}", e)))?;
+        match path {
+            Some(path) => tracing::info!("Trusting extra CA certificate from {}", path),
+            None => tracing::info!("Extra CA certificate removed"),
+        }
+        Ok(())
+    }
+
+    // DANGER: with this on, any certificate is accepted and the connection can be
+    // intercepted. Only for test environments; off by default and never persisted.
+    pub fn set_danger_accept_invalid_certs(&self, accept: bool) -> Result<(), MigrationError> {
+        self.update_settings(|settings| settings.danger_accept_invalid_certs = accept)
+            .map_err(|e| MigrationError::invalid_input(format!("could not rebuild HTTP client: {}", e)))?;
+        if accept {
+            tracing::warn!("TLS certificate verification is DISABLED");
+        } else {
+            tracing::info!("TLS certificate verification enabled");
+        }
+        Ok(())
+    }
+
+    pub fn set_http_version(&self, version: HttpVersion) -> Result<(), MigrationError> {
+        self.update_settings(|settings| settings.http_version = version)
+            .map_err(|e| MigrationError::invalid_input(format!("could not rebuild HTTP client: {}", e)))?;
+        tracing::info!("HTTP version set to {:?}", version);
+        Ok(())
+    }
+
+    fn update_settings(&self, change: impl FnOnce(&mut ConnectionSettings)) -> Result<(), reqwest::Error> {
+        let mut settings = self.settings.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut updated = settings.clone();
+        change(&mut updated);
+
+        let http = updated.build_client()?;
+        *self.http.write().unwrap_or_else(PoisonError::into_inner) = http;
+        *settings = updated;
+        Ok(())
+    }
+
+    // reqwest::Client is an Arc inside, so handing out a clone is cheap and keeps
+    // in-flight requests on the client they started with
+    fn http(&self) -> reqwest::Client {
+        self.http.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    // Sends the request built by `build`, retrying connection errors and 502/503/504
+    // with jittered exponential backoff. `build` is called again for every attempt
+    // because a RequestBuilder is consumed by send(). 429s wait for the server's Retry-After
+    // before retrying. Other 4xx and successful responses are returned as-is.
+    // On success the response body is returned, otherwise a MigrationError that
+    // records how many attempts were made.
+    pub async fn execute<F>(&self, build: F) -> Result<String, MigrationError>
+    where
+        F: Fn(&reqwest::Client) -> reqwest::RequestBuilder,
+    {
+        self.execute_raw(build).await.map(|response| response.body)
+    }
+
+    // Same as execute but keeps the status and headers of the successful response,
+    // for callers that need e.g. the ETag. Every attempt carries the same x-request-id
+    // (request_id::current()), which also ends up on the response or error.
+    pub async fn execute_raw<F>(&self, build: F) -> Result<RawResponse, MigrationError>
+    where
+        F: Fn(&reqwest::Client) -> reqwest::RequestBuilder,
+    {
+        let request_id = request_id::current();
+        self.execute_with_id(&request_id, build).await.map_err(|e| e.with_request_id(&request_id))
+    }
+
+    // execute_raw for read-only calls that the UI may fire several times at once:
+    // callers with the same `key` (coalesce::key) while one is in flight get its result
+    pub async fn execute_coalesced<F>(&self, key: String, build: F) -> Result<RawResponse, MigrationError>
+    where
+        F: Fn(&reqwest::Client) -> reqwest::RequestBuilder,
+    {
+        self.inflight.run(key, self.execute_raw(build)).await
+    }
+
+    async fn execute_with_id<F>(&self, request_id: &str, build: F) -> Result<RawResponse, MigrationError>
+    where
+        F: Fn(&reqwest::Client) -> reqwest::RequestBuilder,
+    {
+        let http = self.http();
+        let max_attempts = self.max_attempts().max(1);
+        let mut attempt = 1;
+        let mut transient_attempts = 1;
+        let mut rate_limit_retries = 0;
+        let mut backoff = Backoff::new(RETRY_BASE_DELAY, MAX_RETRY_DELAY);
+        let started = Instant::now();
+        // Method and URL of the request, for timing::record
+        let mut target: Option<(Method, Url)> = None;
+
+        loop {
+            let request = build(&http)
+                .header(request_id::HEADER, request_id)
+                .header(ACCEPT_LANGUAGE, self.accept_language())
+                .headers(self.extra_headers())
+                .timeout(self.timeout())
+                .build()
+                .map(|mut request| {
+                    self.fill_token(&mut request);
+                    request
+                });
+            // Retries take a token too; the wait isn't part of the call's timing
+            self.limiter.acquire().await;
+            let sent = Instant::now();
+            let result = match request {
+                Ok(request) => {
+                    target.get_or_insert_with(|| (request.method().clone(), request.url().clone()));
+                    let host = request.url().host_str().unwrap_or_default().to_string();
+                    self.breaker.allow(&host)?;
+                    let result = self.send(&http, request).await;
+                    self.breaker.record(&host, match &result {
+                        Ok(response) => is_retryable_status(response.status()),
+                        Err(e) => e.is_connect() || e.is_timeout(),
+                    });
+                    result
+                }
+                Err(e) => Err(e),
+            };
+            let ttfb = sent.elapsed();
+
+            if let Ok(response) = &result {
+                if response.status() == StatusCode::TOO_MANY_REQUESTS && rate_limit_retries < MAX_RATE_LIMIT_RETRIES {
+                    let wait = retry_after(response.headers()).unwrap_or(DEFAULT_RETRY_AFTER).min(MAX_RETRY_AFTER);
+                    rate_limit_retries += 1;
+                    tracing::warn!("Rate limited (HTTP 429), waiting {:?} before retrying ({}/{})", wait, rate_limit_retries, MAX_RATE_LIMIT_RETRIES);
+                    tokio::time::sleep(wait).await;
+                    bulk::note_retry();
+                    attempt += 1;
+                    continue;
+                }
+            }
+
+            let retryable = match &result {
+                Ok(response) => is_retryable_status(response.status()),
+                Err(e) => e.is_connect(),
+            };
+
+            if retryable && transient_attempts < max_attempts {
+                let delay = backoff.delay(transient_attempts - 1);
+                match &result {
+                    Ok(response) => tracing::warn!("Got HTTP {}, retrying in {:?} (attempt {}/{})", response.status(), delay, transient_attempts, max_attempts),
+                    Err(e) => tracing::warn!("Request failed ({}), retrying in {:?} (attempt {}/{})", e, delay, transient_attempts, max_attempts),
+                }
+                tokio::time::sleep(delay).await;
+                bulk::note_retry();
+                attempt += 1;
+                transient_attempts += 1;
+                continue;
+            }
+
+            let record = |status: Option<StatusCode>, timing: CallTiming| {
+                if let Some((method, url)) = &target {
+                    timing::record(method, url, status.map(|s| s.as_u16()), timing);
+                }
+            };
+            let response = match result {
+                Ok(response) => response,
+                Err(e) => {
+                    record(None, CallTiming::new(ttfb, Duration::ZERO, started.elapsed()));
+                    return Err(self.request_error(e, attempt));
+                }
+            };
+            let status = response.status();
+            let headers = response.headers().clone();
+            let body_started = Instant::now();
+            let body = self.read_body(response).await;
+            let timing = CallTiming::new(ttfb, body_started.elapsed(), started.elapsed());
+            record(Some(status), timing);
+
+            return status_result(status, body?, attempt).map(|body| RawResponse {
+                status: status.as_u16(),
+                headers,
+                body,
+                request_id: request_id.to_string(),
+                timing,
+            });
+        }
+    }
+
+    // One authenticated GET with no retries, for connection checks where the first
+    // failure is the answer
+    pub async fn probe(&self, url: &str, auth: &Auth) -> Result<RawResponse, MigrationError> {
+        self.probe_with(Method::GET, url, auth, None).await
+    }
+
+    // probe with any method and an optional JSON body, e.g. for permission checks
+    pub async fn probe_with(&self, method: Method, url: &str, auth: &Auth, body: Option<&Value>) -> Result<RawResponse, MigrationError> {
+        let request_id = request_id::current();
+        let http = self.http();
+        let request = auth
+            .apply(http.request(method, url))
+            .header("Accept", "application/json")
+            .header(request_id::HEADER, &request_id)
+            .header(ACCEPT_LANGUAGE, self.accept_language())
+            .headers(self.extra_headers())
+            .timeout(self.timeout());
+        let request = match body {
+            Some(body) => request.json(body),
+            None => request,
+        };
+        let response = async {
+            self.limiter.acquire().await;
+            let started = Instant::now();
+            let response = match request.build() {
+                Ok(mut request) => {
+                    self.fill_token(&mut request);
+                    self.send(&http, request).await
+                }
+                Err(e) => Err(e),
+            };
+            let response = response.map_err(|e| self.request_error(e, 1))?;
+            let ttfb = started.elapsed();
+            let status = response.status();
+            let headers = response.headers().clone();
+            let body = self.read_body(response).await?;
+            let timing = CallTiming::new(ttfb, started.elapsed() - ttfb, started.elapsed());
+            status_result(status, body, 1).map(|body| RawResponse { status: status.as_u16(), headers, body, request_id: request_id.clone(), timing })
+        };
+        response.await.map_err(|e| e.with_request_id(&request_id))
+    }
+
+    // Streams the body of one request to `dest` as it arrives, for exports too big to
+    // hold in memory; max_response_bytes doesn't apply. Not retried, since a retry
+    // would mean starting over. The request timeout applies to the first byte and to
+    // each chunk after it rather than to the whole body. The body is written to
+    // `dest`.part and renamed when complete, so a failed download never leaves a
+    // truncated file under the real name. `on_progress` gets the bytes written so far
+    // every DOWNLOAD_PROGRESS_BYTES and once at the end. Returns the total.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn download(
+        &self,
+        method: Method,
+        url: &str,
+        auth: &Auth,
+        tenant_id: Option<&str>,
+        body: Option<&Value>,
+        dest: &Path,
+        on_progress: &(dyn Fn(u64) + Sync),
+    ) -> Result<u64, MigrationError> {
+        let request_id = request_id::current();
+        let http = self.http();
+        let mut request = auth
+            .apply(http.request(method, url))
+            .header("Accept", "application/json")
+            .header(request_id::HEADER, &request_id)
+            .header(ACCEPT_LANGUAGE, self.accept_language())
+            .headers(self.extra_headers());
+        request = with_tenant_header(request, tenant_id);
+        if let Some(body) = body {
+            request = request.json(body);
+        }
+
+        let download = async {
+            let mut request = request.build().map_err(|e| self.request_error(e, 1))?;
+            self.fill_token(&mut request);
+            let host = request.url().host_str().unwrap_or_default().to_string();
+            self.limiter.acquire().await;
+            self.breaker.allow(&host)?;
+            let response = match tokio::time::timeout(self.timeout(), self.send(&http, request)).await {
+                Ok(response) => response,
+                Err(_) => Err(self.timeout_error())?,
+            };
+            self.breaker.record(&host, match &response {
+                Ok(response) => is_retryable_status(response.status()),
+                Err(e) => e.is_connect() || e.is_timeout(),
+            });
+            let response = response.map_err(|e| self.request_error(e, 1))?;
+            let status = response.status();
+            if !status.is_success() {
+                let body = self.read_body(response).await?;
+                return status_result(status, body, 1).map(|_| 0);
+            }
+
+            let partial = dest.with_extension(match dest.extension() {
+                Some(extension) => format!("{}.part", extension.to_string_lossy()),
+                None => "part".to_string(),
+            });
+            let written = self.stream_to(response, &partial, on_progress).await;
+            match written {
+                Ok(written) => {
+                    tokio::fs::rename(&partial, dest).await.map_err(|e| MigrationError::io(dest, e))?;
+                    Ok(written)
+                }
+                Err(e) => {
+                    let _ = tokio::fs::remove_file(&partial).await;
+                    Err(e)
+                }
+            }
+        };
+        download.await.map_err(|e| e.with_request_id(&request_id))
+    }
+
+    async fn stream_to(&self, response: reqwest::Response, path: &Path, on_progress: &(dyn Fn(u64) + Sync)) -> Result<u64, MigrationError> {
+        // A failed read surfaces as an io::Error from copy; the MigrationError behind it is kept here
+        let read_error: Mutex<Option<MigrationError>> = Mutex::new(None);
+        let written = AtomicU64::new(0);
+        let reported = AtomicU64::new(0);
+
+        let chunks = futures::stream::try_unfold(response, |mut response| async {
+            let chunk = match tokio::time::timeout(self.timeout(), response.chunk()).await {
+                Ok(chunk) => chunk.map_err(|e| self.read_error(e)),
+                Err(_) => Err(self.timeout_error()),
+            };
+            match chunk {
+                Ok(Some(chunk)) => {
+                    let total = written.fetch_add(chunk.len() as u64, Ordering::Relaxed) + chunk.len() as u64;
+                    if total - reported.load(Ordering::Relaxed) >= DOWNLOAD_PROGRESS_BYTES {
+                        reported.store(total, Ordering::Relaxed);
+                        on_progress(total);
+                    }
+                    Ok(Some((chunk, response)))
+                }
+                Ok(None) => Ok(None),
+                Err(e) => {
+                    *read_error.lock().unwrap_or_else(PoisonError::into_inner) = Some(e);
+                    Err(std::io::Error::other("reading the response failed"))
+                }
+            }
+        });
+
+        let mut file = tokio::fs::File::create(path).await.map_err(|e| MigrationError::io(path, e))?;
+        let copied = tokio::io::copy(&mut StreamReader::new(Box::pin(chunks)), &mut file).await;
+        let copied = match copied {
+            Ok(_) => file.flush().await,
+            Err(e) => Err(e),
+        };
+        if let Err(e) = copied {
+            return Err(read_error.into_inner().unwrap_or_else(PoisonError::into_inner).unwrap_or_else(|| MigrationError::io(path, e)));
+        }
+
+        let written = written.load(Ordering::Relaxed);
+        on_progress(written);
+        Ok(written)
+    }
+
+    // JSON-in/JSON-out convenience for Rust-side callers (e.g. the migration orchestrator).
+    // `tenant_id` is sent as x-rks-tenantid unless it's None or blank. An empty body parses as Null
+    // because several create/delete endpoints reply with no content. Calls made inside
+    // audit::scope are written to that audit log.
+    //
+    // If the token was minted by refresh_token with remember=true, a 401 refreshes it
+    // once and retries, and later calls with the old token use the new one.
+    pub async fn send_json(&self, method: Method, url: &str, auth: &Auth, tenant_id: Option<&str>, body: Option<&Value>) -> Result<Value, MigrationError> {
+        self.send_json_with_key(method, url, auth, tenant_id, body, None).await
+    }
+
+    // send_json with an Idempotency-Key header, sent unchanged on every retry so the
+    // server can dedupe a create that succeeded before the connection dropped
+    pub async fn send_json_with_key(
+        &self,
+        method: Method,
+        url: &str,
+        auth: &Auth,
+        tenant_id: Option<&str>,
+        body: Option<&Value>,
+        idempotency_key: Option<&str>,
+    ) -> Result<Value, MigrationError> {
+        let auth = match auth {
+            Auth::Bearer(token) => Auth::Bearer(self.tokens.current(token)),
+            Auth::ApiKey(_) => auth.clone(),
+        };
+        // Only reads are cached
+        let cache_key = cache::is_read(&method, url).then(|| coalesce::key(&method, url, tenant_id.unwrap_or_default(), &auth, body.unwrap_or(&Value::Null)));
+        if let Some(cached) = cache_key.as_deref().and_then(|key| self.cache.get(key)) {
+            tracing::debug!("{} {} served from cache", method, url);
+            return Ok(cached);
+        }
+
+        let result = self.send_json_refreshing(&method, url, &auth, tenant_id, body, idempotency_key).await;
+        match (&result, cache_key) {
+            (Ok(value), Some(key)) => self.cache.insert(key, tenant_id, value),
+            (Ok(_), None) => self.cache.invalidate_tenant(tenant_id),
+            (Err(_), _) => {}
+        }
+        result
+    }
+
+    async fn send_json_refreshing(
+        &self,
+        method: &Method,
+        url: &str,
+        auth: &Auth,
+        tenant_id: Option<&str>,
+        body: Option<&Value>,
+        idempotency_key: Option<&str>,
+    ) -> Result<Value, MigrationError> {
+        let result = self.send_json_once(method, url, auth, tenant_id, body, idempotency_key).await;
+
+        let (Err(MigrationError::Auth { status: 401, .. }), Auth::Bearer(token)) = (&result, auth) else {
+            return result;
+        };
+        let Some(credentials) = self.tokens.credentials_for(token) else {
+            return result;
+        };
+        tracing::info!("Token rejected with 401, requesting a new one");
+        match oauth::request_token(self, &credentials).await {
+            Ok(fresh) => {
+                self.tokens.replace(token, &fresh.access_token, credentials);
+                self.send_json_once(method, url, &Auth::Bearer(fresh.access_token), tenant_id, body, idempotency_key).await
+            }
+            Err(e) => {
+                tracing::warn!("Token refresh failed: {}", e);
+                result
+            }
+        }
+    }
+
+    async fn send_json_once(
+        &self,
+        method: &Method,
+        url: &str,
+        auth: &Auth,
+        tenant_id: Option<&str>,
+        body: Option<&Value>,
+        idempotency_key: Option<&str>,
+    ) -> Result<Value, MigrationError> {
+        tracing::debug!("{} {} (token {})", method, url, redact::mask_token(auth.secret()));
+        let body = body.map(|body| self.request_body(body)).transpose()?;
+        let result = self
+            .execute_raw(|http| {
+                let request = auth.apply(http.request(method.clone(), url)).header("Accept", "application/json");
+                let mut request = with_tenant_header(request, tenant_id);
+                if let Some(key) = idempotency_key {
+                    request = request.header("Idempotency-Key", key);
+                }
+                if let Some(body) = &body {
+                    request = request.header(CONTENT_TYPE, "application/json").body(body.clone());
+                }
+                request
+            })
+            .await;
+        audit::record(method, url, auth, &result);
+        let raw = result?.body;
+
+        if raw.trim().is_empty() {
+            Ok(Value::Null)
+        } else {
+            Ok(serde_json::from_str(&raw)?)
+        }
+    }
+
+    async fn send(&self, http: &reqwest::Client, request: reqwest::Request) -> reqwest::Result<reqwest::Response> {
+        let backend: &dyn HttpBackend = match &self.backend {
+            Some(backend) => backend.as_ref(),
+            None => http,
+        };
+        backend.send(request).await
+    }
+
+    // Reads the body chunk by chunk so an oversized response is cut off at the limit
+    // instead of being buffered whole. A body that isn't UTF-8 is a Decode error
+    // carrying its Content-Type and first bytes.
+    async fn read_body(&self, mut response: reqwest::Response) -> Result<String, MigrationError> {
+        let status = response.status().as_u16();
+        let content_type = response.headers().get(CONTENT_TYPE).and_then(|v| v.to_str().ok()).map(str::to_string);
+        let limit = self.max_response_bytes.load(Ordering::Relaxed);
+        if response.content_length().is_some_and(|len| len > limit) {
+            return Err(MigrationError::ResponseTooLarge { limit });
+        }
+
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await.map_err(|e| self.read_error(e))? {
+            if (body.len() + chunk.len()) as u64 > limit {
+                return Err(MigrationError::ResponseTooLarge { limit });
+            }
+            body.extend_from_slice(&chunk);
+        }
+        String::from_utf8(body).map_err(|e| MigrationError::decode(status, content_type, e.as_bytes()))
+    }
+
+    // Timeouts get their own variant so the frontend can tell them apart from HTTP errors
+    fn request_error(&self, e: reqwest::Error, attempts: u32) -> MigrationError {
+        if e.is_timeout() && e.is_connect() {
+            MigrationError::Timeout { secs: self.connect_timeout().as_secs(), connect: true, request_id: None }
+        } else if e.is_timeout() {
+            self.timeout_error()
+        } else {
+            let host = e.url().and_then(|url| url.host_str()).map(str::to_string);
+            MigrationError::Network { message: e.to_string(), attempts, failure: classify(&e), host, request_id: None }
+        }
+    }
+
+    fn read_error(&self, e: reqwest::Error) -> MigrationError {
+        if e.is_timeout() {
+            self.timeout_error()
+        } else {
+            MigrationError::Network {
+                message: format!("failed to read response: {}", e),
+                attempts: 1,
+                failure: NetworkFailure::Other,
+                host: e.url().and_then(|url| url.host_str()).map(str::to_string),
+                request_id: None,
+            }
+        }
+    }
+
+    fn timeout_error(&self) -> MigrationError {
+        MigrationError::Timeout { secs: self.timeout().as_secs(), connect: false, request_id: None }
+    }
+}
+
+// The id to send as x-rks-tenantid. A blank one means the call isn't scoped to a
+// tenant, so the header is left off rather than sent empty.
+pub fn tenant_scope(tenant_id: Option<&str>) -> Option<&str> {
+    tenant_id.map(str::trim).filter(|id| !id.is_empty())
+}
+
+pub fn with_tenant_header(request: reqwest::RequestBuilder, tenant_id: Option<&str>) -> reqwest::RequestBuilder {
+    match tenant_scope(tenant_id) {
+        Some(tenant_id) => request.header("x-rks-tenantid", tenant_id),
+        None => request,
+    }
+}
+
+fn status_result(status: StatusCode, body: String, attempts: u32) -> Result<String, MigrationError> {
+    if status.is_success() {
+        Ok(body)
+    } else if status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN {
+        let api_error = ApiErrorBody::parse(&body);
+        Err(MigrationError::Auth { status: status.as_u16(), body, api_error, request_id: None })
+    } else if status == StatusCode::PRECONDITION_FAILED {
+        // Only sent in reply to If-Match: someone else changed the resource first
+        Err(MigrationError::Conflict { body })
+    } else if status == StatusCode::PAYLOAD_TOO_LARGE {
+        Err(MigrationError::PayloadTooLarge { body, request_id: None })
+    } else {
+        let api_error = ApiErrorBody::parse(&body);
+        Err(MigrationError::Http { status: status.as_u16(), body, attempts, api_error, request_id: None })
+    }
+}
+
+// reqwest doesn't expose the cause directly, so walk the source chain: refused
+// connections surface as io::Error, DNS and TLS failures only in the message text
+fn classify(e: &reqwest::Error) -> NetworkFailure {
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(e);
+    while let Some(err) = source {
+        if let Some(io) = err.downcast_ref::<std::io::Error>() {
+            if io.kind() == std::io::ErrorKind::ConnectionRefused {
+                return NetworkFailure::Refused;
+            }
+        }
+
+        let text = err.to_string().to_lowercase();
+        if text.contains("dns error") || text.contains("failed to lookup address") || text.contains("name or service not known") {
+            return NetworkFailure::Dns;
+        }
+        if text.contains("certificate") || text.contains("tls") || text.contains("ssl") || text.contains("handshake") {
+            return NetworkFailure::Tls;
+        }
+
+        source = err.source();
+    }
+    NetworkFailure::Other
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+// Retry-After is either a number of seconds or an HTTP-date
+fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(date.duration_since(SystemTime::now()).unwrap_or(Duration::ZERO))
+}
+
+fn load_ca_cert(path: &str) -> Result<reqwest::Certificate, MigrationError> {
+    let pem = std::fs::read(path).map_err(|e| MigrationError::io(path, e))?;
+    reqwest::Certificate::from_pem(&pem)
+        .map_err(|e| MigrationError::invalid_input(format!("{} is not a valid PEM certificate: {}", path, e)))
+}
+
+// Proxy URLs may carry credentials; only the scheme/host/port go to the log
+fn redact_proxy(proxy: &str) -> String {
+    match reqwest::Url::parse(proxy) {
+        Ok(url) => format!("{}://{}:{}", url.scheme(), url.host_str().unwrap_or(""), url.port_or_known_default().unwrap_or(0)),
+        Err(_) => "<unparsable>".to_string(),
+    }
+}
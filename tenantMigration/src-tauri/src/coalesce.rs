@@ -0,0 +1,77 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use reqwest::Method;
+use serde_json::Value;
+use tokio::sync::OnceCell;
+
+use crate::auth::Auth;
+
+// Shares one in-flight call between identical callers: while a call for `key` is
+// running, later callers with the same key wait for its result instead of sending a
+// duplicate. The key is forgotten as soon as the call completes, so nothing is cached.
+// If the caller running the call is dropped, one of the waiters runs its own instead.
+pub struct Coalescer<T> {
+    inflight: Mutex<HashMap<String, Arc<OnceCell<T>>>>,
+}
+
+impl<T> Default for Coalescer<T> {
+    fn default() -> Self {
+        Self { inflight: Mutex::new(HashMap::new()) }
+    }
+}
+
+impl<T: Clone> Coalescer<T> {
+    pub async fn run<F>(&self, key: String, call: F) -> T
+    where
+        F: Future<Output = T>,
+    {
+        let cell = self.inflight.lock().unwrap_or_else(PoisonError::into_inner).entry(key.clone()).or_default().clone();
+        let value = cell.get_or_init(|| call).await.clone();
+
+        let mut inflight = self.inflight.lock().unwrap_or_else(PoisonError::into_inner);
+        if inflight.get(&key).is_some_and(|current| Arc::ptr_eq(current, &cell)) {
+            inflight.remove(&key);
+        }
+        value
+    }
+}
+
+// Calls are identical when they go to the same URL with the same tenant, credentials
+// and body; the credentials are only part of the hash so they aren't kept in the map
+// in clear
+pub fn key(method: &Method, url: &str, tenant_id: &str, auth: &Auth, body: &Value) -> String {
+    let mut hasher = DefaultHasher::new();
+    (tenant_id, auth, body.to_string()).hash(&mut hasher);
+    format!("{} {} {:016x}", method, url, hasher.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn identical_calls_share_one_run() {
+        let coalescer = Coalescer::<u32>::default();
+        let runs = AtomicUsize::new(0);
+        let call = || async {
+            runs.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            7
+        };
+
+        let (a, b) = tokio::join!(coalescer.run("venues".to_string(), call()), coalescer.run("venues".to_string(), call()));
+        assert_eq!((a, b, runs.load(Ordering::SeqCst)), (7, 7, 1));
+        assert!(coalescer.inflight.lock().unwrap().is_empty());
+
+        // Done calls are not cached
+        coalescer.run("venues".to_string(), call()).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,24 @@
+use std::future::Future;
+
+use futures::stream::{self, StreamExt, TryStreamExt};
+
+use crate::error::MigrationError;
+
+// Conservative default; R1 starts rate limiting well before hundreds of parallel
+// requests
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 5;
+
+// Runs `f` over every item with at most `max_in_flight` futures running at once.
+// Results come back in input order; the first error stops the remaining work.
+pub async fn try_map_bounded<I, T, F, Fut>(items: I, max_in_flight: usize, f: F) -> Result<Vec<T>, MigrationError>
+where
+    I: IntoIterator,
+    F: FnMut(I::Item) -> Fut,
+    Fut: Future<Output = Result<T, MigrationError>>,
+{
+    if max_in_flight == 0 {
+        return Err(MigrationError::invalid_input("max_in_flight must be at least 1"));
+    }
+
+    stream::iter(items).map(f).buffered(max_in_flight).try_collect().await
+}
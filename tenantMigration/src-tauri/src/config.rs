@@ -0,0 +1,154 @@
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{PoisonError, RwLock};
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, Runtime, State};
+use tauri_plugin_fs::{FsExt, OpenOptions};
+
+use crate::apiurl;
+use crate::client::AppClient;
+use crate::error::MigrationError;
+use crate::region::Region;
+use crate::state::AppState;
+
+const CONFIG_FILE: &str = "config.json";
+
+// Settings remembered between sessions. There is deliberately no token field: tokens
+// are never written to disk, and anything extra the frontend sends is dropped when
+// deserializing.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub api_url: Option<String>,
+    pub region: Option<Region>,
+    pub timeout_secs: Option<u64>,
+    pub connect_timeout_secs: Option<u64>,
+    // Accept-Language for API calls, e.g. "de-DE"; unset means en-US
+    pub accept_language: Option<String>,
+}
+
+// The last saved/loaded config, part of AppState
+#[derive(Default)]
+pub struct ConfigStore(RwLock<AppConfig>);
+
+impl ConfigStore {
+    pub fn get(&self) -> AppConfig {
+        self.0.read().unwrap_or_else(PoisonError::into_inner).clone()
+    }
+
+    fn set(&self, config: AppConfig) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = config;
+    }
+
+    // Commands take api_url as an Option; leaving it out (or sending it empty) means
+    // "use the saved api_url, or the selected region's API host"
+    pub fn api_url_or(&self, given: Option<&str>) -> Result<String, MigrationError> {
+        if let Some(given) = given.map(str::trim).filter(|url| !url.is_empty()) {
+            return apiurl::normalize(given);
+        }
+        let config = self.get();
+        let url = config
+            .api_url
+            .filter(|url| !url.trim().is_empty())
+            .or_else(|| config.region.map(|region| region.api_url().to_string()))
+            .ok_or_else(|| MigrationError::invalid_input("no api_url was given and no api_url or region has been set"))?;
+        apiurl::normalize(&url)
+    }
+}
+
+#[tauri::command]
+pub fn save_config(app: AppHandle, state: State<'_, AppState>, config: AppConfig) -> Result<(), MigrationError> {
+    let AppState { client, config: store, .. } = state.inner();
+    if config.timeout_secs == Some(0) {
+        return Err(MigrationError::invalid_input("timeout_secs must be at least 1"));
+    }
+    if config.connect_timeout_secs == Some(0) {
+        return Err(MigrationError::invalid_input("connect_timeout_secs must be at least 1"));
+    }
+    let config = AppConfig { accept_language: config.accept_language.map(|l| l.trim().to_string()).filter(|l| !l.is_empty()), ..config };
+    // Check it now rather than fail on every request later
+    client.set_accept_language(config.accept_language.as_deref())?;
+
+    let path = config_path(&app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| MigrationError::io(dir, e))?;
+    }
+
+    let json = serde_json::to_vec_pretty(&config)?;
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    let mut file = app.fs().open(&path, options).map_err(|e| MigrationError::io(&path, e))?;
+    file.write_all(&json).map_err(|e| MigrationError::io(&path, e))?;
+
+    apply(&config, client);
+    store.set(config);
+    tracing::info!("Saved config to {}", path.display());
+    Ok(())
+}
+
+// Selects the region whose API host is used when a command gets no api_url.
+// Any saved api_url is dropped so it doesn't shadow the new region. Call save_config
+// to keep the choice for the next session.
+#[tauri::command]
+pub fn set_region(state: State<'_, AppState>, region: Region) {
+    let AppState { config: store, .. } = state.inner();
+    let mut config = store.get();
+    config.region = Some(region);
+    config.api_url = None;
+    store.set(config);
+    tracing::info!("Region set to {:?} ({})", region, region.api_url());
+}
+
+// Re-reads the config file; a missing file gives the defaults
+#[tauri::command]
+pub fn load_config(app: AppHandle, state: State<'_, AppState>) -> Result<AppConfig, MigrationError> {
+    let AppState { client, config: store, .. } = state.inner();
+    let config = read_config(&app)?;
+    apply(&config, client);
+    store.set(config.clone());
+    Ok(config)
+}
+
+// Called from setup() so saved settings are in place before the first command runs.
+// A broken config file is logged rather than stopping the app from starting.
+pub fn init<R: Runtime>(app: &AppHandle<R>) {
+    match read_config(app) {
+        Ok(config) => {
+            let state = app.state::<AppState>();
+            apply(&config, &state.client);
+            state.config.set(config);
+        }
+        Err(e) => tracing::warn!("Ignoring saved config: {}", e),
+    }
+}
+
+fn read_config<R: Runtime>(app: &AppHandle<R>) -> Result<AppConfig, MigrationError> {
+    let path = config_path(app)?;
+    match app.fs().read_to_string(&path) {
+        Ok(text) => Ok(serde_json::from_str(&text)?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(AppConfig::default()),
+        Err(e) => Err(MigrationError::io(&path, e)),
+    }
+}
+
+fn apply(config: &AppConfig, client: &AppClient) {
+    if let Some(secs) = config.timeout_secs.filter(|secs| *secs > 0) {
+        client.set_timeout(secs);
+    }
+    if let Some(secs) = config.connect_timeout_secs.filter(|secs| *secs > 0) {
+        if let Err(e) = client.set_connect_timeout(secs) {
+            tracing::warn!("Ignoring saved connect_timeout_secs: {}", e);
+        }
+    }
+    if let Err(e) = client.set_accept_language(config.accept_language.as_deref()) {
+        tracing::warn!("Ignoring saved accept_language: {}", e);
+    }
+}
+
+fn config_path<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, MigrationError> {
+    app.path()
+        .app_config_dir()
+        .map(|dir| dir.join(CONFIG_FILE))
+        .map_err(|e| MigrationError::Io { path: CONFIG_FILE.to_string(), message: format!("no app config directory: {}", e) })
+}
@@ -0,0 +1,57 @@
+use std::time::Instant;
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::State;
+
+use crate::auth::Auth;
+use crate::error::MigrationError;
+use crate::region::Region;
+use crate::state::AppState;
+use crate::timing::CallTiming;
+
+// Cheap authenticated endpoint that every valid token can read
+const CHECK_PATH: &str = "tenants/self";
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ConnectionInfo {
+    pub api_url: String,
+    // None for anything but the public R1 API hosts
+    pub region: Option<Region>,
+    pub tenant_id: Option<String>,
+    pub tenant_name: Option<String>,
+    pub latency_ms: u64,
+    // x-request-id of the check, as it appears in R1's logs
+    pub request_id: String,
+    // latency_ms split into time to first byte and body read
+    pub timing: CallTiming,
+}
+
+// "Test Connection" button. Errors tell the cases apart: Network with failure
+// dns/tls/refused when the host can't be reached, Auth for a bad token, Http when
+// the API answered but with an error. Without an api_url the saved one is checked.
+#[tauri::command]
+pub async fn check_connection(state: State<'_, AppState>, api_url: Option<String>, auth: Auth) -> Result<ConnectionInfo, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let api_url = config.api_url_or(api_url.as_deref())?;
+    let url = reqwest::Url::parse(&format!("{}/{}", api_url, CHECK_PATH))
+        .map_err(|e| MigrationError::invalid_input(format!("invalid api_url: {}", e)))?;
+
+    let started = Instant::now();
+    let response = client.probe(url.as_str(), &auth).await?;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    // The check succeeded once we got a 2xx; account details are best effort
+    let account: Value = serde_json::from_str(&response.body).unwrap_or(Value::Null);
+    let field = |name: &str| account.get(name).and_then(Value::as_str).map(str::to_string);
+
+    Ok(ConnectionInfo {
+        region: url.host_str().and_then(Region::from_api_host),
+        tenant_id: field("tenant_id").or_else(|| field("id")),
+        tenant_name: field("name"),
+        api_url,
+        latency_ms,
+        request_id: response.request_id,
+        timing: response.timing,
+    })
+}
@@ -0,0 +1,98 @@
+use reqwest::header::{HeaderMap, ACCEPT_LANGUAGE};
+use reqwest::Method;
+use serde_json::Value;
+use tauri::State;
+
+use crate::auth::Auth;
+use crate::client;
+use crate::error::MigrationError;
+use crate::redact;
+use crate::state::AppState;
+
+// The curl command equivalent to a call this app would make, to reproduce an R1
+// issue from a terminal: method, URL, the headers commands send plus the client-wide
+// ones (Accept-Language, set_extra_headers) and the JSON body. The token and extra
+// header values are masked unless reveal_secrets is set - the output tends to get
+// pasted into tickets.
+#[tauri::command]
+pub fn build_curl(
+    state: State<'_, AppState>,
+    method: String,
+    url: String,
+    auth: Auth,
+    tenant_id: Option<String>,
+    body: Option<Value>,
+    reveal_secrets: Option<bool>,
+) -> Result<String, MigrationError> {
+    let AppState { client, .. } = state.inner();
+    curl_command(&method, &url, &auth, tenant_id.as_deref(), body.as_ref(), &client.default_headers(), reveal_secrets.unwrap_or(false))
+}
+
+fn curl_command(
+    method: &str,
+    url: &str,
+    auth: &Auth,
+    tenant_id: Option<&str>,
+    body: Option<&Value>,
+    default_headers: &HeaderMap,
+    reveal: bool,
+) -> Result<String, MigrationError> {
+    let method = Method::from_bytes(method.trim().to_ascii_uppercase().as_bytes())
+        .map_err(|_| MigrationError::invalid_input(format!("invalid HTTP method: {}", method)))?;
+    reqwest::Url::parse(url.trim()).map_err(|e| MigrationError::invalid_input(format!("invalid URL {}: {}", url, e)))?;
+    let secret = |value: &str| if reveal { value.to_string() } else { redact::mask_token(value) };
+
+    let (auth_name, auth_value) = auth.header(secret);
+    let mut headers = vec![(auth_name.to_string(), auth_value), ("Accept".to_string(), "application/json".to_string())];
+    if body.is_some() {
+        headers.push(("Content-Type".to_string(), "application/json".to_string()));
+    }
+    if let Some(tenant_id) = client::tenant_scope(tenant_id) {
+        headers.push(("x-rks-tenantid".to_string(), tenant_id.to_string()));
+    }
+    for (name, value) in default_headers {
+        let value = value.to_str().unwrap_or_default();
+        let value = if name == ACCEPT_LANGUAGE { value.to_string() } else { secret(value) };
+        headers.push((name.to_string(), value));
+    }
+
+    let mut parts = vec![format!("curl -X {} {}", method, quote(url.trim()))];
+    parts.extend(headers.iter().map(|(name, value)| format!("-H {}", quote(&format!("{}: {}", name, value)))));
+    if let Some(body) = body {
+        parts.push(format!("--data {}", quote(&serde_json::to_string(body)?)));
+    }
+    Ok(parts.join(" \\\n  "))
+}
+
+// POSIX shell single quotes; an embedded ' becomes '\''
+fn quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::HeaderValue;
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn masks_secrets_and_quotes_the_body() {
+        let mut defaults = HeaderMap::new();
+        defaults.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("en-US"));
+        defaults.insert("x-api-key", HeaderValue::from_static("gateway-secret"));
+        let body = json!({ "name": "O'Brien's venue" });
+
+        let curl = curl_command("post", "https://api.example.test/venues", &Auth::Bearer("header.payload.signature".to_string()), Some("t-1"), Some(&body), &defaults, false).unwrap();
+        assert!(curl.starts_with("curl -X POST 'https://api.example.test/venues'"));
+        assert!(curl.contains("-H 'Authorization: Bearer header…'"));
+        assert!(curl.contains("-H 'x-rks-tenantid: t-1'"));
+        assert!(curl.contains("-H 'accept-language: en-US'"));
+        assert!(curl.contains("-H 'x-api-key: gatewa…'"));
+        assert!(curl.contains(r#"--data '{"name":"O'\''Brien'\''s venue"}'"#));
+
+        let revealed = curl_command("GET", "https://api.example.test/venues", &Auth::Bearer("header.payload.signature".to_string()), None, None, &defaults, true).unwrap();
+        assert!(revealed.contains("Bearer header.payload.signature"));
+        assert!(!revealed.contains("Content-Type"));
+    }
+}
@@ -0,0 +1,339 @@
+use std::path::Path;
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+use tauri::State;
+
+use crate::auth::Auth;
+use crate::client::AppClient;
+use crate::error::MigrationError;
+use crate::query::{self, Resource};
+use crate::snapshot;
+use crate::state::AppState;
+
+// Server-assigned fields that always differ between two copies of the same config
+pub const DEFAULT_IGNORED_FIELDS: &[&str] = &["id", "createdDate", "updatedDate"];
+
+// Result of comparing a source document to a target one. `added` is what only the
+// target has, `removed` what only the source has. Paths look like `address.city`
+// `venues.Main Office.name` or `tagList[0]`.
+#[derive(Debug, Default, Serialize)]
+pub struct JsonDiff {
+    pub added: Vec<DiffEntry>,
+    pub removed: Vec<DiffEntry>,
+    pub changed: Vec<ChangedEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiffEntry {
+    pub path: String,
+    pub value: Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChangedEntry {
+    pub path: String,
+    pub source: Value,
+    pub target: Value,
+}
+
+// Fetches both tenants (and, with include_venues, their venues matched up by name)
+// and returns where the target differs from the source. `ignore_fields` replaces
+// DEFAULT_IGNORED_FIELDS and applies at every depth.
+#[tauri::command]
+pub async fn diff_tenants(
+    state: State<'_, AppState>,
+    api_url: Option<String>,
+    source_id: String,
+    target_id: String,
+    auth: Auth,
+    include_venues: Option<bool>,
+    ignore_fields: Option<Vec<String>>,
+) -> Result<JsonDiff, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let api_url = &config.api_url_or(api_url.as_deref())?;
+    let include_venues = include_venues.unwrap_or(false);
+
+    let source = fetch_comparable(client, api_url, &auth, &source_id, include_venues).await?;
+    let target = fetch_comparable(client, api_url, &auth, &target_id, include_venues).await?;
+    Ok(diff_values(&source, &target, &ignored(ignore_fields)))
+}
+
+// diff_tenants with an export_tenant file as the source and the live tenant as the
+// target, to review edits to a snapshot before import_tenant pushes them back.
+// `removed` is what the file has and the tenant doesn't, i.e. what an import adds.
+#[tauri::command]
+pub async fn diff_snapshot_vs_live(
+    state: State<'_, AppState>,
+    file_path: String,
+    api_url: Option<String>,
+    tenant_id: String,
+    auth: Auth,
+    include_venues: Option<bool>,
+    ignore_fields: Option<Vec<String>>,
+) -> Result<JsonDiff, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let api_url = &config.api_url_or(api_url.as_deref())?;
+    let include_venues = include_venues.unwrap_or(false);
+
+    let file = snapshot::read_snapshot(Path::new(&file_path)).await?;
+    let source = comparable(file.tenant, include_venues.then_some(file.venues));
+    let target = fetch_comparable(client, api_url, &auth, &tenant_id, include_venues).await?;
+    Ok(diff_values(&source, &target, &ignored(ignore_fields)))
+}
+
+fn ignored(ignore_fields: Option<Vec<String>>) -> Vec<String> {
+    ignore_fields.unwrap_or_else(|| DEFAULT_IGNORED_FIELDS.iter().map(|f| f.to_string()).collect())
+}
+
+async fn fetch_comparable(client: &AppClient, api_url: &str, auth: &Auth, tenant_id: &str, include_venues: bool) -> Result<Value, MigrationError> {
+    let tenant = query::fetch_tenant(client, api_url, auth, tenant_id).await?;
+    if !include_venues {
+        return Ok(tenant);
+    }
+
+    let scoped_id = query::scoped_tenant_id(&tenant, tenant_id);
+    let venues = query::fetch_resource(client, api_url, auth, &scoped_id, Resource::Venues).await?;
+    Ok(comparable(tenant, Some(venues)))
+}
+
+// The document both diffs compare: the tenant alone, or with its venues by name
+fn comparable(tenant: Value, venues: Option<Vec<Value>>) -> Value {
+    let Some(venues) = venues else {
+        return tenant;
+    };
+    let mut document = Map::new();
+    document.insert("tenant".to_string(), tenant);
+    document.insert("venues".to_string(), Value::Object(keyed_by_name(venues)));
+    Value::Object(document)
+}
+
+// Wifi network fields compare_ssids checks by default: the ones that decide how
+// clients authenticate
+pub const DEFAULT_SSID_FIELDS: &[&str] = &["securityProtocol", "nwSubType", "captiveType"];
+
+#[derive(Debug, Default, Serialize)]
+pub struct SsidComparison {
+    pub only_in_source: Vec<String>,
+    pub only_in_target: Vec<String>,
+    // SSIDs on both sides whose compared fields differ; `path` is the field name
+    pub mismatched: Vec<SsidMismatch>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SsidMismatch {
+    pub ssid: String,
+    pub fields: Vec<ChangedEntry>,
+}
+
+// Post-migration check that the target broadcasts the same SSIDs as the source with
+// the same security. `compare_fields` (default DEFAULT_SSID_FIELDS) are top-level
+// wifi network fields; they're added to the query so any field R1 returns works.
+// A tenant with the same SSID on several networks is compared by its first one.
+#[tauri::command]
+pub async fn compare_ssids(
+    state: State<'_, AppState>,
+    api_url: Option<String>,
+    source_id: String,
+    target_id: String,
+    auth: Auth,
+    compare_fields: Option<Vec<String>>,
+) -> Result<SsidComparison, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let api_url = &config.api_url_or(api_url.as_deref())?;
+    let fields: Vec<String> = compare_fields
+        .unwrap_or_else(|| DEFAULT_SSID_FIELDS.iter().map(|f| f.to_string()).collect())
+        .into_iter()
+        .map(|f| f.trim().to_string())
+        .filter(|f| !f.is_empty())
+        .collect();
+
+    let source = fetch_networks(client, api_url, &auth, &source_id, &fields).await?;
+    let target = fetch_networks(client, api_url, &auth, &target_id, &fields).await?;
+    Ok(compare_networks(&source, &target, &fields))
+}
+
+async fn fetch_networks(client: &AppClient, api_url: &str, auth: &Auth, tenant_id: &str, fields: &[String]) -> Result<Vec<Value>, MigrationError> {
+    let tenant = query::fetch_tenant(client, api_url, auth, tenant_id).await?;
+    let mut query = Resource::WifiNetworks.default_query();
+    if let Some(Value::Array(requested)) = query.get_mut("fields") {
+        for field in fields {
+            if !requested.iter().any(|f| f.as_str() == Some(field)) {
+                requested.push(Value::from(field.as_str()));
+            }
+        }
+    }
+    let url = format!("{}/{}", api_url, Resource::WifiNetworks.query_path(client.paths(api_url)));
+    query::fetch_all_pages(client, &url, auth, &query::scoped_tenant_id(&tenant, tenant_id), &query, query::DEFAULT_PAGE_SIZE).await
+}
+
+fn compare_networks(source: &[Value], target: &[Value], fields: &[String]) -> SsidComparison {
+    let by_ssid = |networks: &[Value]| {
+        let mut map: Map<String, Value> = Map::new();
+        for network in networks {
+            if let Some(ssid) = network.get("ssid").and_then(Value::as_str) {
+                map.entry(ssid.to_string()).or_insert_with(|| network.clone());
+            }
+        }
+        map
+    };
+    let (source, target) = (by_ssid(source), by_ssid(target));
+
+    let mut comparison = SsidComparison::default();
+    for (ssid, network) in &source {
+        let Some(other) = target.get(ssid) else {
+            comparison.only_in_source.push(ssid.clone());
+            continue;
+        };
+        let changed: Vec<ChangedEntry> = fields
+            .iter()
+            .filter_map(|field| {
+                let (a, b) = (network.get(field).unwrap_or(&Value::Null), other.get(field).unwrap_or(&Value::Null));
+                (a != b).then(|| ChangedEntry { path: field.clone(), source: a.clone(), target: b.clone() })
+            })
+            .collect();
+        if !changed.is_empty() {
+            comparison.mismatched.push(SsidMismatch { ssid: ssid.clone(), fields: changed });
+        }
+    }
+    comparison.only_in_target = target.keys().filter(|ssid| !source.contains_key(*ssid)).cloned().collect();
+    comparison
+}
+
+// Venue ids differ between tenants, so pair them up by name instead of by position
+fn keyed_by_name(records: Vec<Value>) -> Map<String, Value> {
+    records
+        .into_iter()
+        .enumerate()
+        .map(|(i, record)| {
+            let name = record
+                .get("name")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("#{}", i));
+            (name, record)
+        })
+        .collect()
+}
+
+// Stable text form of an exported tenant config for keeping in git: keys sorted at
+// every depth, `strip_fields` (default DEFAULT_IGNORED_FIELDS) removed at every depth
+// and two-space pretty-printing with a trailing newline, so two exports of an
+// unchanged tenant are byte-identical. Array order is kept as it carries meaning.
+#[tauri::command]
+pub fn canonicalize_tenant(json: Value, strip_fields: Option<Vec<String>>) -> Result<String, MigrationError> {
+    let strip = strip_fields.unwrap_or_else(|| DEFAULT_IGNORED_FIELDS.iter().map(|f| f.to_string()).collect());
+    let mut text = serde_json::to_string_pretty(&canonical(&json, &strip))?;
+    text.push('\n');
+    Ok(text)
+}
+
+fn canonical(value: &Value, strip: &[String]) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().filter(|(key, _)| !strip.contains(key)).collect();
+            entries.sort_by_key(|(key, _)| *key);
+            Value::Object(entries.into_iter().map(|(key, value)| (key.clone(), canonical(value, strip))).collect())
+        }
+        Value::Array(items) => Value::Array(items.iter().map(|item| canonical(item, strip)).collect()),
+        leaf => leaf.clone(),
+    }
+}
+
+pub fn diff_values(source: &Value, target: &Value, ignore: &[String]) -> JsonDiff {
+    let mut diff = JsonDiff::default();
+    diff_at("", source, target, ignore, &mut diff);
+    diff
+}
+
+fn diff_at(path: &str, source: &Value, target: &Value, ignore: &[String], diff: &mut JsonDiff) {
+    match (source, target) {
+        (Value::Object(source), Value::Object(target)) => {
+            for (key, source_value) in source {
+                if ignore.contains(key) {
+                    continue;
+                }
+                let child = child_path(path, key);
+                match target.get(key) {
+                    Some(target_value) => diff_at(&child, source_value, target_value, ignore, diff),
+                    None => diff.removed.push(DiffEntry { path: child, value: source_value.clone() }),
+                }
+            }
+            for (key, target_value) in target {
+                if !ignore.contains(key) && !source.contains_key(key) {
+                    diff.added.push(DiffEntry { path: child_path(path, key), value: target_value.clone() });
+                }
+            }
+        }
+        (Value::Array(source), Value::Array(target)) => {
+            for (i, source_value) in source.iter().enumerate() {
+                let child = format!("{}[{}]", path, i);
+                match target.get(i) {
+                    Some(target_value) => diff_at(&child, source_value, target_value, ignore, diff),
+                    None => diff.removed.push(DiffEntry { path: child, value: source_value.clone() }),
+                }
+            }
+            for (i, target_value) in target.iter().enumerate().skip(source.len()) {
+                diff.added.push(DiffEntry { path: format!("{}[{}]", path, i), value: target_value.clone() });
+            }
+        }
+        _ if source != target => diff.changed.push(ChangedEntry {
+            path: path.to_string(),
+            source: source.clone(),
+            target: target.clone(),
+        }),
+        _ => {}
+    }
+}
+
+fn child_path(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", parent, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn canonical_form_ignores_key_order_and_volatile_fields() {
+        let first = json!({ "name": "Acme", "id": "a1", "address": { "city": "Leeds", "updatedDate": "2026-01-01" } });
+        let second = json!({ "address": { "updatedDate": "2026-02-02", "city": "Leeds" }, "id": "b2", "name": "Acme" });
+        let text = canonicalize_tenant(first, None).unwrap();
+        assert_eq!(text, canonicalize_tenant(second, None).unwrap());
+        assert_eq!(text, "{\n  \"address\": {\n    \"city\": \"Leeds\"\n  },\n  \"name\": \"Acme\"\n}\n");
+    }
+
+    #[test]
+    fn edited_snapshot_venues_are_diffed_by_name() {
+        let file = comparable(json!({ "name": "Acme" }), Some(vec![json!({ "id": "v1", "name": "HQ", "city": "York" }), json!({ "name": "Depot" })]));
+        let live = comparable(json!({ "name": "Acme", "id": "t1" }), Some(vec![json!({ "id": "v9", "name": "HQ", "city": "Leeds" })]));
+
+        let diff = diff_values(&file, &live, &ignored(None));
+        assert!(diff.added.is_empty());
+        assert_eq!(diff.removed.iter().map(|e| e.path.as_str()).collect::<Vec<_>>(), ["venues.Depot"]);
+        assert_eq!(diff.changed.iter().map(|e| e.path.as_str()).collect::<Vec<_>>(), ["venues.HQ.city"]);
+        assert_eq!(comparable(json!({ "name": "Acme" }), None), json!({ "name": "Acme" }));
+    }
+
+    #[test]
+    fn ssids_are_matched_and_security_compared() {
+        let source = [
+            json!({ "ssid": "Corp", "securityProtocol": "WPA3", "nwSubType": "aaa" }),
+            json!({ "ssid": "Guest", "securityProtocol": "OPEN" }),
+        ];
+        let target = [json!({ "ssid": "Corp", "securityProtocol": "WPA2", "nwSubType": "aaa" }), json!({ "ssid": "IoT" })];
+        let fields: Vec<String> = DEFAULT_SSID_FIELDS.iter().map(|f| f.to_string()).collect();
+
+        let comparison = compare_networks(&source, &target, &fields);
+        assert_eq!(comparison.only_in_source, ["Guest"]);
+        assert_eq!(comparison.only_in_target, ["IoT"]);
+        assert_eq!(comparison.mismatched.len(), 1);
+        assert_eq!(comparison.mismatched[0].fields.iter().map(|f| f.path.as_str()).collect::<Vec<_>>(), ["securityProtocol"]);
+    }
+}
@@ -0,0 +1,80 @@
+use reqwest::header::ALLOW;
+use reqwest::Method;
+use serde::Serialize;
+use tauri::State;
+
+use crate::auth::Auth;
+use crate::client::AppClient;
+use crate::error::MigrationError;
+use crate::request_id;
+use crate::state::AppState;
+
+// What discover_endpoint learned about a path. Unsupported means the server doesn't
+// answer OPTIONS (405/501, or no Allow header), so the UI should leave every action
+// enabled rather than guess.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "lowercase")]
+pub enum EndpointMethods {
+    // Uppercase method names from the Allow header, e.g. ["GET", "DELETE"]
+    Allowed { methods: Vec<String> },
+    Unsupported,
+}
+
+// Sends OPTIONS to {api_url}/{path} so the UI can hide actions the API tier doesn't
+// offer, e.g. delete on a read-only path. Auth and network errors are still errors.
+#[tauri::command]
+pub async fn discover_endpoint(state: State<'_, AppState>, api_url: Option<String>, path: String, auth: Auth) -> Result<EndpointMethods, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let path = path.trim().trim_matches('/');
+    if path.is_empty() {
+        return Err(MigrationError::invalid_input("path must not be empty"));
+    }
+    let url = format!("{}/{}", config.api_url_or(api_url.as_deref())?, path);
+    request_id::scope(request_id::new(), discover(client, &url, &auth)).await
+}
+
+async fn discover(client: &AppClient, url: &str, auth: &Auth) -> Result<EndpointMethods, MigrationError> {
+    let response = match client.probe_with(Method::OPTIONS, url, auth, None).await {
+        Ok(response) => response,
+        Err(MigrationError::Http { status: 405 | 501, .. }) => return Ok(EndpointMethods::Unsupported),
+        Err(e) => return Err(e),
+    };
+
+    let methods: Vec<String> = response
+        .headers
+        .get_all(ALLOW)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .map(|method| method.trim().to_ascii_uppercase())
+        .filter(|method| !method.is_empty())
+        .collect();
+    if methods.is_empty() {
+        tracing::debug!("OPTIONS {} returned no Allow header", url);
+        return Ok(EndpointMethods::Unsupported);
+    }
+    Ok(EndpointMethods::Allowed { methods })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::backend::mock::MockBackend;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn reads_allow_header_and_falls_back_to_unsupported() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond_with_headers(204, &[("Allow", "GET, head,DELETE")], "").respond(405, "").respond(200, "{}");
+        let client = AppClient::with_backend(mock.clone()).unwrap();
+        let url = "https://api.example.test/venues/v1";
+
+        let methods = EndpointMethods::Allowed { methods: vec!["GET".into(), "HEAD".into(), "DELETE".into()] };
+        assert_eq!(discover(&client, url, &Auth::Bearer("token".to_string())).await.unwrap(), methods);
+        assert_eq!(mock.requests()[0].method, Method::OPTIONS);
+        assert_eq!(discover(&client, url, &Auth::Bearer("token".to_string())).await.unwrap(), EndpointMethods::Unsupported);
+        assert_eq!(discover(&client, url, &Auth::Bearer("token".to_string())).await.unwrap(), EndpointMethods::Unsupported);
+    }
+}
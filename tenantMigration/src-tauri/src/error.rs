@@ -0,0 +1,333 @@
+use std::fmt;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::migration::{FetchFailure, PushFailure, ResourceRef};
+use crate::query::ResourceCounts;
+use crate::validation::ValidationProblem;
+
+// How much of an undecodable body MigrationError::Decode shows
+pub const DECODE_PREVIEW_BYTES: usize = 64;
+
+// Error type returned by every command. Tauri serializes it to a tagged object,
+// e.g. { "kind": "Http", "status": 404, "body": "...", "attempts": 1 }, so the
+// frontend can switch on `kind` instead of parsing message text. Errors from the
+// HTTP client carry the x-request-id that was sent, to quote to R1 support.
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "kind")]
+pub enum MigrationError {
+    // The request never got a response; `failure` says why when it could be told.
+    // `host` is the host the request went to, for the message.
+    Network { message: String, attempts: u32, failure: NetworkFailure, host: Option<String>, request_id: Option<String> },
+    // Any non-success status that isn't an auth failure. `api_error` is the body
+    // parsed as an R1 error when it is one; `body` is always the raw text.
+    Http { status: u16, body: String, attempts: u32, api_error: Option<ApiErrorBody>, request_id: Option<String> },
+    // `connect` is true when no connection was made within the connect timeout
+    Timeout { secs: u64, connect: bool, request_id: Option<String> },
+    // 401/403 - the UI uses this to prompt for a fresh login
+    Auth { status: u16, body: String, api_error: Option<ApiErrorBody>, request_id: Option<String> },
+    // Too many failures in a row to `host` (see breaker.rs); nothing was sent
+    CircuitOpen { host: String, retry_in_secs: u64 },
+    // Response body bigger than the configured limit; reading stopped at `limit` bytes
+    ResponseTooLarge { limit: u64 },
+    // Body wasn't valid UTF-8 (a binary gateway page, a mis-negotiated encoding);
+    // `preview_hex` is its first DECODE_PREVIEW_BYTES bytes for diagnosis
+    Decode { status: u16, content_type: Option<String>, length: usize, preview_hex: String, request_id: Option<String> },
+    // Outgoing JSON payload bigger than the configured limit; nothing was sent
+    RequestTooLarge { size: u64, limit: u64 },
+    // 413: the API refused the body as too large. Sending less per request (fewer
+    // venues per put_venues, a smaller put_aps chunk_size) is the fix.
+    PayloadTooLarge { body: String, request_id: Option<String> },
+    // 412 on a request sent with If-Match: the resource changed since its ETag was read
+    Conflict { body: String },
+    Serde { message: String },
+    InvalidInput { message: String },
+    // Payload failed local checks before anything was sent; lists every problem found
+    Validation { problems: Vec<ValidationProblem> },
+    // A migration/import was stopped with cancel_migration; `created` is what already
+    // exists on the target and may need cleaning up
+    Cancelled { created: Vec<ResourceRef> },
+    // migrate_tenant ran past its max_duration_secs and stopped between resources;
+    // `created` is as for Cancelled and the checkpoint is kept for resume_migration
+    DeadlineExceeded { limit_secs: u64, created: Vec<ResourceRef> },
+    // migrate_tenant was pointed at an existing tenant that already has resources
+    // (see preflight_target) and force wasn't set; nothing was written
+    TargetNotEmpty { tenant_id: String, counts: ResourceCounts },
+    // Reading the source failed for more than one resource type, each for its own reason
+    FetchFailed { failures: Vec<FetchFailure> },
+    // migrate_tenant created the tenant but then failed on `failure`; `created` lists
+    // everything already on the target, for rollback_migration
+    PartialMigration { failure: Box<PushFailure>, created: Vec<ResourceRef> },
+    // Reading or writing a local file (exports, snapshots) failed
+    Io { path: String, message: String },
+    // The OS secure store refused a store_token/load_token/delete_token
+    Keychain { message: String },
+    // The API answered successfully but not with what we needed (e.g. no id for a created resource)
+    UnexpectedResponse { message: String },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkFailure {
+    Dns,
+    Tls,
+    Refused,
+    Other,
+}
+
+// The useful parts of an R1 error response. The API sends either flat
+// { code, message, requestId } or { requestId, errors: [{ code, message }] }.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ApiErrorBody {
+    pub code: Option<String>,
+    pub message: Option<String>,
+    // Quote this in support tickets
+    pub request_id: Option<String>,
+}
+
+impl ApiErrorBody {
+    // None when the body isn't JSON or has none of the fields
+    pub fn parse(body: &str) -> Option<Self> {
+        let value: Value = serde_json::from_str(body).ok()?;
+        let first_error = value.get("errors").and_then(Value::as_array).and_then(|errors| errors.first());
+        let text = |v: &Value, key: &str| match v.get(key)? {
+            Value::String(s) => Some(s.clone()),
+            Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        };
+        let field = |key: &str| text(&value, key).or_else(|| first_error.and_then(|e| text(e, key)));
+
+        let parsed = ApiErrorBody {
+            code: field("code"),
+            message: field("message"),
+            request_id: text(&value, "requestId").or_else(|| text(&value, "request_id")),
+        };
+        (parsed.code.is_some() || parsed.message.is_some() || parsed.request_id.is_some()).then_some(parsed)
+    }
+}
+
+impl MigrationError {
+    // Failures that may go through if sent again later (the network or the API had a
+    // bad moment), as opposed to ones that fail the same way until the request changes
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            MigrationError::Network { failure, .. } => !matches!(failure, NetworkFailure::Dns | NetworkFailure::Tls),
+            MigrationError::Timeout { .. } | MigrationError::CircuitOpen { .. } => true,
+            MigrationError::Http { status, .. } => matches!(status, 408 | 429 | 500 | 502 | 503 | 504),
+            _ => false,
+        }
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        MigrationError::InvalidInput { message: message.into() }
+    }
+
+    pub fn io(path: impl AsRef<std::path::Path>, e: std::io::Error) -> Self {
+        MigrationError::Io { path: path.as_ref().display().to_string(), message: e.to_string() }
+    }
+
+    // For a body that failed to decode: its length and first bytes as hex
+    pub fn decode(status: u16, content_type: Option<String>, body: &[u8]) -> Self {
+        let preview: Vec<String> = body.iter().take(DECODE_PREVIEW_BYTES).map(|b| format!("{:02x}", b)).collect();
+        MigrationError::Decode { status, content_type, length: body.len(), preview_hex: preview.join(" "), request_id: None }
+    }
+
+    pub fn unexpected_response(message: impl Into<String>) -> Self {
+        MigrationError::UnexpectedResponse { message: message.into() }
+    }
+
+    // Tags an error from a request sent with x-request-id `id`; other kinds are unchanged
+    pub fn with_request_id(mut self, id: &str) -> Self {
+        if let MigrationError::Network { request_id, .. }
+        | MigrationError::Http { request_id, .. }
+        | MigrationError::Timeout { request_id, .. }
+        | MigrationError::Auth { request_id, .. }
+        | MigrationError::Decode { request_id, .. }
+        | MigrationError::PayloadTooLarge { request_id, .. } = &mut self
+        {
+            *request_id = Some(id.to_string());
+        }
+        self
+    }
+
+    pub fn request_id(&self) -> Option<&str> {
+        match self {
+            MigrationError::Network { request_id, .. }
+            | MigrationError::Http { request_id, .. }
+            | MigrationError::Timeout { request_id, .. }
+            | MigrationError::Auth { request_id, .. }
+            | MigrationError::Decode { request_id, .. }
+            | MigrationError::PayloadTooLarge { request_id, .. } => request_id.as_deref(),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MigrationError::Network { message, attempts, failure, host, .. } => {
+                let host = host.as_deref().unwrap_or("the API host");
+                match failure {
+                    NetworkFailure::Dns => write!(f, "DNS resolution failed for host {}; check the API URL", host)?,
+                    NetworkFailure::Tls => write!(f, "TLS handshake with {} failed: {}", host, message)?,
+                    NetworkFailure::Refused => write!(f, "Connection refused by {}; is the API URL's port right?", host)?,
+                    NetworkFailure::Other => write!(f, "Request failed: {}", message)?,
+                }
+                write_attempts(f, *attempts)
+            }
+            MigrationError::Http { status, body, attempts, api_error, .. } => {
+                write_http(f, *status, body, api_error.as_ref())?;
+                write_attempts(f, *attempts)
+            }
+            MigrationError::Timeout { secs, connect: true, .. } => write!(f, "Could not connect within {}s", secs),
+            MigrationError::Timeout { secs, .. } => write!(f, "Request timed out after {}s", secs),
+            MigrationError::Auth { status, body, api_error, .. } => write_http(f, *status, body, api_error.as_ref()),
+            MigrationError::CircuitOpen { host, retry_in_secs } => {
+                write!(f, "{} is failing, not sending requests to it for another {}s", host, retry_in_secs)
+            }
+            MigrationError::ResponseTooLarge { limit } => write!(f, "Response is larger than the {} byte limit", limit),
+            MigrationError::Decode { status, content_type, length, preview_hex, .. } => write!(
+                f,
+                "HTTP {} response is not valid UTF-8 ({} bytes, Content-Type {}), starts with: {}",
+                status,
+                length,
+                content_type.as_deref().unwrap_or("missing"),
+                preview_hex
+            ),
+            MigrationError::RequestTooLarge { size, limit } => write!(f, "Request body is {} bytes, over the {} byte limit", size, limit),
+            MigrationError::PayloadTooLarge { .. } => {
+                f.write_str("HTTP 413: the API refused the request as too large; split it into smaller requests (e.g. fewer venues or APs per call)")
+            }
+            MigrationError::Conflict { body } => write!(f, "Conflict: the resource was modified since it was fetched ({})", body),
+            MigrationError::Serde { message } => write!(f, "Invalid JSON: {}", message),
+            MigrationError::InvalidInput { message } => f.write_str(message),
+            MigrationError::Validation { problems } => {
+                let problems: Vec<String> = problems
+                    .iter()
+                    .map(|p| if p.field.is_empty() { p.message.clone() } else { format!("{}: {}", p.field, p.message) })
+                    .collect();
+                write!(f, "Validation failed: {}", problems.join(", "))
+            }
+            MigrationError::Cancelled { created } => write!(f, "Cancelled after creating {} resources", created.len()),
+            MigrationError::TargetNotEmpty { tenant_id, counts } => {
+                let found = [counts.venues, counts.wifi_networks, counts.aps, counts.switches].iter().any(|count| count.is_some_and(|n| n > 0));
+                if found {
+                    write!(f, "Target tenant {} already has resources; migrating into it would create duplicates (set force to do it anyway)", tenant_id)
+                } else {
+                    write!(f, "Could not confirm target tenant {} is empty; migrating into it could create duplicates (set force to do it anyway)", tenant_id)
+                }
+            }
+            MigrationError::DeadlineExceeded { limit_secs, created } => {
+                write!(f, "Stopped at the {}s time limit after creating {} resources", limit_secs, created.len())
+            }
+            MigrationError::FetchFailed { failures } => {
+                let failures: Vec<String> = failures.iter().map(|f| format!("{:?}: {}", f.resource, f.error)).collect();
+                write!(f, "Reading the source failed: {}", failures.join("; "))
+            }
+            MigrationError::PartialMigration { failure, created } => write!(
+                f,
+                "Migration stopped at {:?} {} after creating {} resources: {}",
+                failure.resource,
+                failure.item,
+                created.len(),
+                failure.error
+            ),
+            MigrationError::Io { path, message } => write!(f, "{}: {}", path, message),
+            MigrationError::Keychain { message } => write!(f, "Keychain error: {}", message),
+            MigrationError::UnexpectedResponse { message } => write!(f, "Unexpected response: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for MigrationError {}
+
+impl From<serde_json::Error> for MigrationError {
+    fn from(e: serde_json::Error) -> Self {
+        MigrationError::Serde { message: e.to_string() }
+    }
+}
+
+// "HTTP 400: Venue name exists (code WIFI-10001, requestId abc)" when the body parsed,
+// the raw body otherwise
+fn write_http(f: &mut fmt::Formatter<'_>, status: u16, body: &str, api_error: Option<&ApiErrorBody>) -> fmt::Result {
+    let Some(ApiErrorBody { code, message: Some(message), request_id }) = api_error else {
+        return write!(f, "HTTP {}: {}", status, body);
+    };
+    write!(f, "HTTP {}: {}", status, message)?;
+    let details: Vec<String> = [code.as_ref().map(|c| format!("code {}", c)), request_id.as_ref().map(|r| format!("requestId {}", r))]
+        .into_iter()
+        .flatten()
+        .collect();
+    if !details.is_empty() {
+        write!(f, " ({})", details.join(", "))?;
+    }
+    Ok(())
+}
+
+fn write_attempts(f: &mut fmt::Formatter<'_>, attempts: u32) -> fmt::Result {
+    if attempts > 1 {
+        write!(f, " (after {} attempts)", attempts)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_flat_and_nested_error_bodies() {
+        let flat = ApiErrorBody::parse(r#"{"code":"TNT-001","message":"Tenant exists","requestId":"req-1"}"#).unwrap();
+        assert_eq!(flat.code.as_deref(), Some("TNT-001"));
+        assert_eq!(flat.message.as_deref(), Some("Tenant exists"));
+        assert_eq!(flat.request_id.as_deref(), Some("req-1"));
+
+        let nested = ApiErrorBody::parse(r#"{"requestId":"req-2","errors":[{"code":10001,"message":"Name in use"}]}"#).unwrap();
+        assert_eq!(nested.code.as_deref(), Some("10001"));
+        assert_eq!(nested.message.as_deref(), Some("Name in use"));
+        assert_eq!(nested.request_id.as_deref(), Some("req-2"));
+    }
+
+    #[test]
+    fn non_json_or_unrelated_bodies_are_not_api_errors() {
+        assert_eq!(ApiErrorBody::parse("<html>Bad Gateway</html>"), None);
+        assert_eq!(ApiErrorBody::parse(r#"{"data":[]}"#), None);
+    }
+
+    #[test]
+    fn display_prefers_parsed_message_and_falls_back_to_body() {
+        let body = r#"{"message":"Name in use","requestId":"req-3"}"#.to_string();
+        let parsed = MigrationError::Http { status: 400, api_error: ApiErrorBody::parse(&body), body, attempts: 1, request_id: None };
+        assert_eq!(parsed.to_string(), "HTTP 400: Name in use (requestId req-3)");
+
+        let raw = MigrationError::Http { status: 502, body: "Bad Gateway".to_string(), attempts: 1, api_error: None, request_id: None };
+        assert_eq!(raw.to_string(), "HTTP 502: Bad Gateway");
+    }
+
+    #[test]
+    fn network_display_names_the_failure_and_host() {
+        let network = |failure| MigrationError::Network {
+            message: "error sending request".to_string(),
+            attempts: 1,
+            failure,
+            host: Some("api.example.test".to_string()),
+            request_id: None,
+        };
+        assert_eq!(network(NetworkFailure::Dns).to_string(), "DNS resolution failed for host api.example.test; check the API URL");
+        assert_eq!(network(NetworkFailure::Refused).to_string(), "Connection refused by api.example.test; is the API URL's port right?");
+        assert_eq!(network(NetworkFailure::Other).to_string(), "Request failed: error sending request");
+    }
+
+    #[test]
+    fn decode_error_previews_the_first_bytes() {
+        let body: Vec<u8> = [0x1f, 0x8b, 0x08].iter().copied().chain(std::iter::repeat_n(0xff, 100)).collect();
+        let MigrationError::Decode { content_type, length, preview_hex, .. } = MigrationError::decode(200, Some("application/json".to_string()), &body) else {
+            panic!("expected Decode");
+        };
+        assert_eq!((content_type.as_deref(), length), (Some("application/json"), 103));
+        assert!(preview_hex.starts_with("1f 8b 08 ff"));
+        assert_eq!(preview_hex.split(' ').count(), DECODE_PREVIEW_BYTES);
+    }
+}
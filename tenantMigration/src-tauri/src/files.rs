@@ -0,0 +1,26 @@
+// Ids are used in file names; anything unusual in one becomes '_'
+pub fn label(id: &str) -> String {
+    id.chars().map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' }).collect()
+}
+
+// File name for one run's checkpoint or audit log: the start time to the millisecond,
+// a random suffix, then `id`. Two runs for the same id started together still get
+// different files, and callers create them with create_new so one can never
+// overwrite or append to another's.
+pub fn run_file_name(id: &str, extension: &str) -> String {
+    let suffix = uuid::Uuid::new_v4().simple().to_string();
+    format!("{}-{}-{}.{}", chrono::Utc::now().format("%Y%m%dT%H%M%S%3fZ"), &suffix[..8], label(id), extension)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_started_together_get_their_own_file() {
+        assert_eq!(label("t/1 x"), "t_1_x");
+        let (first, second) = (run_file_name("t/1", "jsonl"), run_file_name("t/1", "jsonl"));
+        assert_ne!(first, second);
+        assert!(first.ends_with("-t_1.jsonl"), "{}", first);
+    }
+}
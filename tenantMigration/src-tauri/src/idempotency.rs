@@ -0,0 +1,118 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+// A repeat of the same submit within this long gets the first result back
+pub const DEFAULT_WINDOW: Duration = Duration::from_secs(60);
+
+// Managed state that collapses duplicate submits: calls with the same key inside
+// the window share one execution. A second call that arrives while the first is
+// still running waits for it instead of sending again. Only successes are kept,
+// so a failed submit can be retried straight away.
+pub struct SubmitGuard<T> {
+    window: Duration,
+    entries: Mutex<HashMap<u64, Entry<T>>>,
+}
+
+struct Entry<T> {
+    started: Instant,
+    result: Arc<tokio::sync::Mutex<Option<T>>>,
+}
+
+impl<T> Default for SubmitGuard<T> {
+    fn default() -> Self {
+        Self { window: DEFAULT_WINDOW, entries: Mutex::new(HashMap::new()) }
+    }
+}
+
+impl<T: Clone> SubmitGuard<T> {
+    // Ok((result, true)) when `key` was already submitted inside the window
+    pub async fn run<E, F, Fut>(&self, key: u64, submit: F) -> Result<(T, bool), E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let slot = {
+            let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+            entries.retain(|_, entry| entry.started.elapsed() < self.window);
+            entries
+                .entry(key)
+                .or_insert_with(|| Entry { started: Instant::now(), result: Arc::default() })
+                .result
+                .clone()
+        };
+
+        // Held across the submit so a concurrent duplicate queues up behind it
+        let mut result = slot.lock().await;
+        if let Some(previous) = result.as_ref() {
+            return Ok((previous.clone(), true));
+        }
+        let value = submit().await?;
+        *result = Some(value.clone());
+        Ok((value, false))
+    }
+}
+
+// Idempotency keys of bulk creates the server confirmed, so running put_venues or
+// put_aps again with the same run_id skips what already exists instead of relying
+// on the server to dedupe. Managed state; lives as long as the app.
+#[derive(Default)]
+pub struct ConfirmedKeys {
+    keys: Mutex<HashSet<String>>,
+}
+
+impl ConfirmedKeys {
+    pub fn contains(&self, key: &str) -> bool {
+        self.keys.lock().unwrap_or_else(PoisonError::into_inner).contains(key)
+    }
+
+    pub fn confirm(&self, key: String) {
+        self.keys.lock().unwrap_or_else(PoisonError::into_inner).insert(key);
+    }
+}
+
+// Idempotency-Key for creating the record known as `source_key` on the source (see
+// migration::source_key) in run `run_id`. Readable and the same on every retry.
+pub fn resource_key(run_id: &str, kind: &str, source_key: &str) -> String {
+    format!("{}:{}:{}", run_id, kind, source_key)
+}
+
+// Stable within one app run, which is all the guard needs
+pub fn key(parts: &[&str]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    parts.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn duplicate_submits_share_one_execution() {
+        let guard = SubmitGuard::<u32>::default();
+        let calls = AtomicU32::new(0);
+        let submit = || async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok::<_, ()>(calls.fetch_add(1, Ordering::SeqCst))
+        };
+
+        let key = key(&["tenant", "{}"]);
+        let (first, second) = tokio::join!(guard.run(key, submit), guard.run(key, submit));
+        assert_eq!(first, Ok((0, false)));
+        assert_eq!(second, Ok((0, true)));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn failures_are_not_remembered() {
+        let guard = SubmitGuard::<u32>::default();
+        assert_eq!(guard.run(1, || async { Err("down") }).await, Err("down"));
+        assert_eq!(guard.run(1, || async { Ok::<_, &str>(7) }).await, Ok((7, false)));
+    }
+}
@@ -0,0 +1,75 @@
+use keyring::Entry;
+use tauri::State;
+
+use crate::error::MigrationError;
+use crate::state::AppState;
+
+// Tokens saved in the OS secure store (Keychain, Credential Manager, Secret Service)
+// under this service name, one entry per account. Requests sent with an empty token
+// use R1_TOKEN if it's set, otherwise the "default" account's entry, so a token
+// stored once never has to pass through the UI or the clipboard again.
+const SERVICE: &str = "r1-tenant-migration";
+const DEFAULT_ACCOUNT: &str = "default";
+const TOKEN_ENV_VAR: &str = "R1_TOKEN";
+
+fn entry(account: Option<&str>) -> Result<Entry, MigrationError> {
+    let account = account.map(str::trim).filter(|a| !a.is_empty()).unwrap_or(DEFAULT_ACCOUNT);
+    Entry::new(SERVICE, account).map_err(keychain_error)
+}
+
+fn keychain_error(e: keyring::Error) -> MigrationError {
+    MigrationError::Keychain { message: e.to_string() }
+}
+
+fn read(account: Option<&str>) -> Result<Option<String>, MigrationError> {
+    match entry(account)?.get_password() {
+        Ok(token) => Ok(Some(token)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(keychain_error(e)),
+    }
+}
+
+// The token for requests sent without one, or None. A keychain that can't be read
+// (no Secret Service on a headless Linux box) counts as empty.
+pub fn fallback_token() -> Option<String> {
+    if let Some(token) = std::env::var(TOKEN_ENV_VAR).ok().map(|t| t.trim().to_string()).filter(|t| !t.is_empty()) {
+        return Some(token);
+    }
+    read(None).unwrap_or_else(|e| {
+        tracing::debug!("No token from the keychain: {}", e);
+        None
+    })
+}
+
+// `account` defaults to "default", the entry requests with an empty token fall back to
+#[tauri::command]
+pub fn store_token(state: State<'_, AppState>, token: String, account: Option<String>) -> Result<(), MigrationError> {
+    let AppState { client, .. } = state.inner();
+    let token = token.trim();
+    let token = token.strip_prefix("Bearer ").unwrap_or(token).trim();
+    if token.is_empty() {
+        return Err(MigrationError::invalid_input("token must not be empty"));
+    }
+    entry(account.as_deref())?.set_password(token).map_err(keychain_error)?;
+    client.set_fallback_token(fallback_token());
+    tracing::info!("Stored token in the keychain");
+    Ok(())
+}
+
+#[tauri::command]
+pub fn load_token(account: Option<String>) -> Result<Option<String>, MigrationError> {
+    read(account.as_deref())
+}
+
+// Returns false when there was nothing stored
+#[tauri::command]
+pub fn delete_token(state: State<'_, AppState>, account: Option<String>) -> Result<bool, MigrationError> {
+    let AppState { client, .. } = state.inner();
+    let deleted = match entry(account.as_deref())?.delete_password() {
+        Ok(()) => true,
+        Err(keyring::Error::NoEntry) => false,
+        Err(e) => return Err(keychain_error(e)),
+    };
+    client.set_fallback_token(fallback_token());
+    Ok(deleted)
+}
@@ -1,5 +1,68 @@
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
-use serde_json::Value;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use reqwest::Method;
+use serde_json::{json, Value};
+use tauri::{Emitter, State};
+use tracing::{debug, trace};
+use tracing_subscriber::EnvFilter;
+
+mod activity;
+mod apiurl;
+mod audit;
+mod auth;
+mod backend;
+mod backoff;
+mod breaker;
+mod bulk;
+mod cache;
+mod capabilities;
+mod checkpoint;
+mod client;
+mod coalesce;
+mod concurrency;
+mod config;
+mod connection;
+mod curl;
+mod diff;
+mod discovery;
+mod error;
+mod files;
+mod idempotency;
+mod keychain;
+mod merge;
+mod migration;
+mod models;
+mod oauth;
+mod permissions;
+mod plan;
+mod preflight;
+mod query;
+mod ratelimit;
+mod redact;
+mod region;
+mod report;
+mod request_id;
+mod rollback;
+mod schema;
+mod snapshot;
+mod state;
+mod templates;
+mod tenants;
+mod timing;
+mod token;
+mod transform;
+mod validation;
+mod verify;
+
+use auth::Auth;
+use bulk::BulkSummary;
+use client::{AppClient, HttpVersion, VerboseResponse};
+use error::MigrationError;
+use idempotency::{ConfirmedKeys, SubmitGuard};
+use migration::ItemResult;
+use state::AppState;
 
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -7,155 +70,1473 @@ fn greet(name: &str) -> String {
 }
 
 #[tauri::command]
-async fn get_tenant(api_url: String, tenant_id: String, token: String) -> Result<String, String> {
-    let url = format!("{}/tenants/{}", api_url, tenant_id);
+fn set_timeout(state: State<'_, AppState>, secs: u64) -> Result<(), MigrationError> {
+    let AppState { client, .. } = state.inner();
+    if secs == 0 {
+        return Err(MigrationError::invalid_input("Timeout must be at least 1 second"));
+    }
+    client.set_timeout(secs);
+    Ok(())
+}
+
+// How long to wait for a connection before giving up; set_timeout still caps the
+// whole call, so this can be short while big AP queries get a long overall timeout
+#[tauri::command]
+fn set_connect_timeout(state: State<'_, AppState>, secs: u64) -> Result<(), MigrationError> {
+    let AppState { client, .. } = state.inner();
+    if secs == 0 {
+        return Err(MigrationError::invalid_input("Connect timeout must be at least 1 second"));
+    }
+    client.set_connect_timeout(secs)
+}
+
+// After `threshold` consecutive connection failures / 502-504s from a host, calls to it
+// fail fast with CircuitOpen for `cooldown_secs` before one probe is let through
+#[tauri::command]
+fn set_circuit_breaker(state: State<'_, AppState>, threshold: u32, cooldown_secs: u64) -> Result<(), MigrationError> {
+    let AppState { client, .. } = state.inner();
+    if threshold == 0 || cooldown_secs == 0 {
+        return Err(MigrationError::invalid_input("Circuit breaker threshold and cooldown must be at least 1"));
+    }
+    client.set_circuit_breaker(threshold, Duration::from_secs(cooldown_secs));
+    Ok(())
+}
+
+// Caps outbound requests at `rps` per second across all commands, allowing bursts of
+// `burst` (default: one second's worth). None or no rps turns the limit off.
+#[tauri::command]
+fn set_rate_limit(state: State<'_, AppState>, rps: Option<f64>, burst: Option<u32>) -> Result<(), MigrationError> {
+    let AppState { client, .. } = state.inner();
+    if rps.is_some_and(|rps| !rps.is_finite() || rps <= 0.0) {
+        return Err(MigrationError::invalid_input("rps must be a positive number"));
+    }
+    let burst = burst.unwrap_or_else(|| rps.map_or(1, |rps| rps.ceil() as u32));
+    client.set_rate_limit(rps, burst);
+    Ok(())
+}
+
+// Reuses successful reads (GETs and /query searches) made during migrations, diffs and
+// verifies for `ttl_secs`, so repeated passes over the same source skip the network.
+// None or 0 turns caching off.
+#[tauri::command]
+fn set_cache_ttl(state: State<'_, AppState>, ttl_secs: Option<u64>) {
+    let AppState { client, .. } = state.inner();
+    client.set_cache_ttl(ttl_secs.filter(|secs| *secs > 0).map(Duration::from_secs));
+}
+
+// Drops every cached response, e.g. after changing the source outside the app.
+// Returns how many there were.
+#[tauri::command]
+fn clear_cache(state: State<'_, AppState>) -> usize {
+    let AppState { client, .. } = state.inner();
+    client.clear_cache()
+}
+
+#[tauri::command]
+fn set_max_attempts(state: State<'_, AppState>, attempts: u32) -> Result<(), MigrationError> {
+    let AppState { client, .. } = state.inner();
+    if attempts == 0 {
+        return Err(MigrationError::invalid_input("Max attempts must be at least 1"));
+    }
+    client.set_max_attempts(attempts);
+    Ok(())
+}
+
+// Size caps in bytes for response bodies read and JSON payloads sent; a missing
+// value leaves that limit unchanged
+#[tauri::command]
+fn set_body_limits(state: State<'_, AppState>, max_response_bytes: Option<u64>, max_request_bytes: Option<u64>) -> Result<(), MigrationError> {
+    let AppState { client, .. } = state.inner();
+    if max_response_bytes == Some(0) || max_request_bytes == Some(0) {
+        return Err(MigrationError::invalid_input("Body size limits must be at least 1 byte"));
+    }
+    if let Some(bytes) = max_response_bytes {
+        client.set_max_response_bytes(bytes);
+    }
+    if let Some(bytes) = max_request_bytes {
+        client.set_max_request_bytes(bytes);
+    }
+    Ok(())
+}
+
+// Language R1 should write error messages in (Accept-Language), e.g. "de-DE".
+// Empty or missing goes back to en-US. Not persisted; see AppConfig::accept_language.
+#[tauri::command]
+fn set_accept_language(state: State<'_, AppState>, language: Option<String>) -> Result<(), MigrationError> {
+    let AppState { client, .. } = state.inner();
+    let language = language.map(|l| l.trim().to_string()).filter(|l| !l.is_empty());
+    client.set_accept_language(language.as_deref())
+}
+
+// Headers some deployments or gateways need on every call, e.g. {"x-rks-version": "2"}.
+// Replaces the previous set; an empty map removes them. Not persisted, since they
+// can hold gateway keys. Overriding Authorization needs allow_authorization.
+#[tauri::command]
+fn set_extra_headers(state: State<'_, AppState>, headers: HashMap<String, String>, allow_authorization: Option<bool>) -> Result<(), MigrationError> {
+    let AppState { client, .. } = state.inner();
+    client.set_extra_headers(&headers, allow_authorization.unwrap_or(false))
+}
+
+// Empty or missing url clears the proxy set here or picked up from HTTPS_PROXY at startup
+#[tauri::command]
+fn set_proxy(state: State<'_, AppState>, url: Option<String>) -> Result<(), MigrationError> {
+    let AppState { client, .. } = state.inner();
+    let url = url.map(|u| u.trim().to_string()).filter(|u| !u.is_empty());
+    client.set_proxy(url)
+}
+
+// Extra root CA (PEM file) for self-hosted or staging gateways; empty path removes it
+#[tauri::command]
+fn set_ca_cert(state: State<'_, AppState>, path: Option<String>) -> Result<(), MigrationError> {
+    let AppState { client, .. } = state.inner();
+    let path = path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    client.set_ca_cert(path.as_deref())
+}
+
+// Workaround for intermediaries that break HTTP/2 (or HTTP/1.1); see HttpVersion for
+// which mode helps with which symptom. Overrides R1_HTTP_VERSION until restart.
+#[tauri::command]
+fn set_http_version(state: State<'_, AppState>, version: HttpVersion) -> Result<(), MigrationError> {
+    let AppState { client, .. } = state.inner();
+    client.set_http_version(version)
+}
+
+// DANGER: turns off TLS certificate verification. Test environments only.
+#[tauri::command]
+fn danger_accept_invalid_certs(state: State<'_, AppState>, accept: bool) -> Result<(), MigrationError> {
+    let AppState { client, .. } = state.inner();
+    client.set_danger_accept_invalid_certs(accept)
+}
+
+// A missing or empty api_url falls back to the saved config (see config::ConfigStore)
+#[tauri::command]
+async fn get_tenant(state: State<'_, AppState>, api_url: Option<String>, tenant_id: String, auth: Auth) -> Result<String, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let api_url = config.api_url_or(api_url.as_deref())?;
+    let url = format!("{}/{}/{}", api_url, client.paths(&api_url).tenant_records, tenant_id);
     
-    let client = reqwest::Client::new();
-    let response = client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", token))
-        .header("Accept", "application/json")
-        .send()
+    client
+        .execute(|http| {
+            let request = auth
+                .apply(http.get(&url))
+                .header("Accept", "application/json");
+            client::with_tenant_header(request, Some(&tenant_id))
+        })
         .await
-        .map_err(|e| format!("Request failed: {}", e))?;
-    
-    let status = response.status();
-    let body = response.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
-    
-    if status.is_success() {
-        Ok(body)
-    } else {
-        Err(format!("HTTP {}: {}", status, body))
+}
+
+#[derive(serde::Serialize)]
+struct VersionedTenant {
+    etag: Option<String>,
+    body: String,
+}
+
+// get_tenant plus the response's ETag, to pass back to put_tenant as if_match
+#[tauri::command]
+async fn get_tenant_with_etag(state: State<'_, AppState>, api_url: Option<String>, tenant_id: String, auth: Auth) -> Result<VersionedTenant, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let api_url = config.api_url_or(api_url.as_deref())?;
+    let url = format!("{}/{}/{}", api_url, client.paths(&api_url).tenant_records, tenant_id);
+
+    let response = client
+        .execute_raw(|http| {
+            let request = auth
+                .apply(http.get(&url))
+                .header("Accept", "application/json");
+            client::with_tenant_header(request, Some(&tenant_id))
+        })
+        .await?;
+
+    let etag = response
+        .headers
+        .get(reqwest::header::ETAG)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string);
+    Ok(VersionedTenant { etag, body: response.body })
+}
+
+// Result of put_tenant. `id`/`tenant_id` are pulled out of the response when present
+// (null otherwise); `raw` is always the untouched body.
+#[derive(Clone, Debug, serde::Serialize)]
+struct CreatedTenant {
+    id: Option<String>,
+    tenant_id: Option<String>,
+    raw: String,
+    // None for a dry run
+    status: Option<u16>,
+    // Final state of the async activity when put_tenant was asked to wait for it
+    activity: Option<activity::ActivityResult>,
+    // x-request-id sent with the create and any activity polls; None for a dry run
+    request_id: Option<String>,
+    // How long the create call took; None for a dry run
+    timing: Option<timing::CallTiming>,
+    // True when this was a duplicate submit and the earlier result is being returned
+    replayed: bool,
+    // Absolute URL from the response's Location header, when it sent one
+    location: Option<String>,
+    // The created tenant as GET `location` returns it, with server-populated fields;
+    // only with fetch_created = true
+    resource: Option<Value>,
+}
+
+impl CreatedTenant {
+    fn from_body(raw: String, status: Option<u16>) -> Self {
+        let parsed: Value = serde_json::from_str(&raw).unwrap_or(Value::Null);
+        let field = |names: &[&str]| names.iter().find_map(|name| parsed.get(*name).and_then(Value::as_str)).map(str::to_string);
+        CreatedTenant {
+            id: field(&["id"]),
+            tenant_id: field(&["tenantId", "tenant_id"]),
+            raw,
+            status,
+            activity: None,
+            request_id: None,
+            timing: None,
+            replayed: false,
+            location: None,
+            resource: None,
+        }
     }
 }
 
+// With dry_run = true nothing is sent: `raw` is a JSON description of the request
+// (URL, headers with the token masked, body) for checking the payload first.
+// if_match (an ETag from get_tenant_with_etag) is sent as If-Match; a 412 comes back
+// as MigrationError::Conflict so the UI can ask the user to re-fetch.
+// With wait = true a 202 Accepted is followed up by polling the activity it names
+// (see activity::wait_for_activity) and the outcome lands in `activity`.
+// With fetch_created = true a 200/201 with a Location on the same host is followed by
+// a GET of that URL, so later steps use the server's copy of the tenant (`resource`).
+//
+// Double-submits are caught twice over: idempotency_key is sent as Idempotency-Key for
+// the server to dedupe on, and the same payload submitted again within
+// idempotency::DEFAULT_WINDOW gets the first result back (replayed = true) without
+// another request.
+#[allow(clippy::too_many_arguments)]
 #[tauri::command]
-async fn put_tenant(api_url: String, tenant_id: String, token: String, tenant_data: Value) -> Result<String, String> {
+async fn put_tenant(
+    state: State<'_, AppState>,
+    guard: State<'_, SubmitGuard<CreatedTenant>>,
+    api_url: Option<String>,
+    tenant_id: String,
+    auth: Auth,
+    tenant_data: Value,
+    dry_run: Option<bool>,
+    if_match: Option<String>,
+    wait: Option<bool>,
+    idempotency_key: Option<String>,
+    fetch_created: Option<bool>,
+) -> Result<CreatedTenant, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let api_url = config.api_url_or(api_url.as_deref())?;
+    let dry_run = dry_run.unwrap_or(false);
+    let idempotency_key = idempotency_key.map(|k| k.trim().to_string()).filter(|k| !k.is_empty());
+    let key = idempotency::key(&[
+        &api_url,
+        tenant_id.trim(),
+        &serde_json::to_string(&tenant_data)?,
+        if_match.as_deref().unwrap_or_default(),
+        idempotency_key.as_deref().unwrap_or_default(),
+    ]);
+
+    // One x-request-id for the create and every activity poll that follows it
+    let submit = || request_id::scope(request_id::new(), async {
+        let mut created = create_tenant(client, &api_url, &tenant_id, &auth, tenant_data, dry_run, if_match.as_deref(), idempotency_key.as_deref()).await?;
+
+        if wait.unwrap_or(false) && created.status == Some(202) {
+            let response: Value = serde_json::from_str(&created.raw).unwrap_or(Value::Null);
+            let activity_id = activity::activity_id(&response)
+                .ok_or_else(|| MigrationError::unexpected_response("202 response did not name an activity to wait for"))?;
+            created.activity = Some(activity::poll(
+                client,
+                &api_url,
+                &auth,
+                &activity_id,
+                Duration::from_secs(activity::DEFAULT_POLL_INTERVAL_SECS),
+                Duration::from_secs(activity::DEFAULT_WAIT_TIMEOUT_SECS),
+            )
+            .await?);
+        }
+        if fetch_created.unwrap_or(false) && matches!(created.status, Some(200 | 201)) {
+            created.resource = fetch_location(client, &api_url, created.location.as_deref(), &auth).await?;
+        }
+        Ok(created)
+    });
+
+    if dry_run {
+        return submit().await;
+    }
+    let (created, replayed) = guard.run(key, submit).await?;
+    if replayed {
+        tracing::warn!("Duplicate put_tenant for tenant {}, returning the earlier result", created.id.as_deref().unwrap_or("<no id>"));
+    }
+    Ok(CreatedTenant { replayed, ..created })
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn create_tenant(client: &AppClient, api_url: &str, tenant_id: &str, auth: &Auth, tenant_data: Value, dry_run: bool, if_match: Option<&str>, idempotency_key: Option<&str>) -> Result<CreatedTenant, MigrationError> {
     // Use /mspCustomers endpoint as per official Postman collection
-    let url = format!("{}/mspCustomers", api_url);
+    let url = format!("{}/{}", api_url, client.paths(api_url).tenants);
     
-    println!("Request URL: {}", url);
-    println!("Tenant Data: {}", serde_json::to_string_pretty(&tenant_data).unwrap());
+    validation::validate_tenant_data(&tenant_data)?;
+    let body = client.request_body(&tenant_data)?;
 
     // Use flat payload structure - NO data wrapper (as per Postman collection)
     let body_data = tenant_data;
-    
-    println!("Request Body: {}", serde_json::to_string_pretty(&body_data).unwrap());
 
-    let client = reqwest::Client::new();
+    debug!("Request URL: {}", url);
+    trace!("Request Body: {}", body_data);
+
+    if dry_run {
+        let (auth_header, auth_value) = auth.header(redact::mask_token);
+        let preview = json!({
+            "dry_run": true,
+            "method": "POST",
+            "url": url,
+            "headers": {
+                auth_header: auth_value,
+                "Content-Type": "application/json",
+                "x-rks-tenantid": client::tenant_scope(Some(tenant_id)),
+                "If-Match": if_match,
+                "Idempotency-Key": idempotency_key,
+            },
+            "body": body_data,
+        });
+        return Ok(CreatedTenant::from_body(serde_json::to_string_pretty(&preview)?, None));
+    }
+
     let response = client
-        .post(&url)
-        .header("Authorization", format!("Bearer {}", token))
-        .header("Content-Type", "application/json")
-        .json(&body_data)
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
-    
-    let status = response.status();
-    let body = response.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
-    
-    if status.is_success() {
-        Ok(body)
-    } else {
-        Err(format!("HTTP {}: {}", status, body))
+        .execute_raw(|http| {
+            let request = auth
+                .apply(http.post(&url))
+                .header("Content-Type", "application/json");
+            let request = client::with_tenant_header(request, Some(tenant_id));
+            let request = match if_match {
+                Some(etag) => request.header("If-Match", etag),
+                None => request,
+            };
+            let request = match idempotency_key {
+                Some(key) => request.header("Idempotency-Key", key),
+                None => request,
+            };
+            request.body(body.clone())
+        })
+        .await?;
+    // Relative Locations are resolved against the create URL
+    let location = response
+        .headers
+        .get(reqwest::header::LOCATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|location| reqwest::Url::parse(&url).and_then(|base| base.join(location)).ok())
+        .map(String::from);
+    Ok(CreatedTenant {
+        request_id: Some(response.request_id),
+        timing: Some(response.timing),
+        location,
+        ..CreatedTenant::from_body(response.body, Some(response.status))
+    })
+}
+
+// GET of a create's Location. The token is only sent to the API host itself; any
+// other Location (or none) is skipped with a warning rather than failing the create.
+async fn fetch_location(client: &AppClient, api_url: &str, location: Option<&str>, auth: &Auth) -> Result<Option<Value>, MigrationError> {
+    let Some(location) = location else {
+        tracing::warn!("Create response had no Location header to fetch");
+        return Ok(None);
+    };
+    let origin = |url: &str| reqwest::Url::parse(url).ok().map(|url| url.origin()).filter(|origin| origin.is_tuple());
+    if origin(location).is_none() || origin(location) != origin(api_url) {
+        tracing::warn!("Not following Location {} outside {}", location, api_url);
+        return Ok(None);
     }
+    client.send_json(Method::GET, location, auth, None, None).await.map(Some)
 }
 
+// Changes only the fields in `partial` on an existing customer, via PATCH
+// {api_url}/mspCustomers/{tenant_id}. Empty partials and server-assigned fields like
+// id fail with Validation before anything is sent. if_match works as for put_tenant.
 #[tauri::command]
-async fn query_venues(api_url: String, tenant_id: String, token: String, query_data: Value) -> Result<String, String> {
-    let url = format!("{}/venues/query", api_url);
-    
-    println!("Venues Query URL: {}", url);
-    println!("Query Data: {}", serde_json::to_string_pretty(&query_data).unwrap());
+async fn patch_tenant(state: State<'_, AppState>, api_url: Option<String>, tenant_id: String, auth: Auth, partial: Value, if_match: Option<String>) -> Result<String, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    update_tenant(client, &config.api_url_or(api_url.as_deref())?, &tenant_id, &auth, &partial, if_match.as_deref()).await
+}
 
-    let client = reqwest::Client::new();
-    let response = client
-        .post(&url)
-        .header("Authorization", format!("Bearer {}", token))
-        .header("Content-Type", "application/json")
-        .header("x-rks-tenantid", tenant_id)
-        .json(&query_data)
-        .send()
+async fn update_tenant(client: &AppClient, api_url: &str, tenant_id: &str, auth: &Auth, partial: &Value, if_match: Option<&str>) -> Result<String, MigrationError> {
+    let tenant_id = tenant_id.trim();
+    if tenant_id.is_empty() {
+        return Err(MigrationError::invalid_input("tenant_id must not be empty"));
+    }
+    validation::validate_tenant_patch(partial)?;
+    let body = client.request_body(partial)?;
+
+    let url = format!("{}/{}/{}", api_url.trim_end_matches('/'), client.paths(api_url).tenants, tenant_id);
+    debug!("PATCH {}", url);
+    client
+        .execute(|http| {
+            let request = auth
+                .apply(http.patch(&url))
+                .header("Content-Type", "application/json");
+            let request = match if_match {
+                Some(etag) => request.header("If-Match", etag),
+                None => request,
+            };
+            request.body(body.clone())
+        })
         .await
-        .map_err(|e| format!("Request failed: {}", e))?;
-    
-    let status = response.status();
-    let body = response.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
-    
-    if status.is_success() {
-        Ok(body)
-    } else {
-        Err(format!("HTTP {}: {}", status, body))
+}
+
+// POSTs each venue to {api_url}/venues. Failures don't stop the batch; every venue
+// gets an entry (name, id or error) in the returned list, in input order.
+// With a run_id each venue is sent with an Idempotency-Key derived from it and its
+// source id, and calling again with the same run_id skips the venues already created
+// (skipped = true), so a batch that failed part-way can simply be retried.
+// `summary` counts attempts, retries and failures for the whole batch.
+#[tauri::command]
+async fn put_venues(
+    state: State<'_, AppState>,
+    confirmed: State<'_, ConfirmedKeys>,
+    api_url: Option<String>,
+    tenant_id: String,
+    auth: Auth,
+    venues: Vec<Value>,
+    run_id: Option<String>,
+) -> Result<BulkVenueResult, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let api_url = config.api_url_or(api_url.as_deref())?;
+    let run = run_id.as_deref().map(str::trim).filter(|id| !id.is_empty()).map(|id| (id, &*confirmed));
+    Ok(request_id::scope(request_id::new(), create_venues(client, &api_url, &tenant_id, &auth, &venues, run)).await)
+}
+
+#[derive(Debug, serde::Serialize)]
+struct BulkVenueResult {
+    // One per input venue, in input order
+    items: Vec<ItemResult>,
+    summary: BulkSummary,
+}
+
+async fn create_venues(client: &AppClient, api_url: &str, tenant_id: &str, auth: &Auth, venues: &[Value], run: Option<(&str, &ConfirmedKeys)>) -> BulkVenueResult {
+    let url = format!("{}/venues", api_url);
+    debug!("Creating {} venues at {}", venues.len(), url);
+
+    let mut results = Vec::with_capacity(venues.len());
+    let mut summary = BulkSummary::default();
+    for venue in venues {
+        let key = run.map(|(run_id, _)| idempotency::resource_key(run_id, "venue", &migration::source_key(venue).unwrap_or_default()));
+        if let (Some(key), Some((_, confirmed))) = (&key, run) {
+            if confirmed.contains(key) {
+                results.push(ItemResult::skipped(venue));
+                continue;
+            }
+        }
+
+        // Venues copied from another tenant still carry server-side fields like id
+        let payload = migration::venue_create_payload(venue);
+        let (result, retries) = bulk::count_retries(client.send_json_with_key(Method::POST, &url, auth, Some(tenant_id), Some(&payload), key.as_deref())).await;
+        summary.add(&migration::record_label(venue), retries, result.as_ref().map(|_| ()));
+        if let (true, Some(key), Some((_, confirmed))) = (result.is_ok(), key, run) {
+            confirmed.confirm(key);
+        }
+        results.push(ItemResult::from_result(venue, result));
+    }
+    BulkVenueResult { items: results, summary }
+}
+
+const DEFAULT_AP_CHUNK_SIZE: usize = 50;
+
+#[derive(Debug, serde::Serialize)]
+struct ApChunkResult {
+    venue_id: String,
+    serial_numbers: Vec<String>,
+    success: bool,
+    error: Option<MigrationError>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct BulkApResult {
+    chunks: Vec<ApChunkResult>,
+    // Counted per AP; every AP of a chunk shares the chunk's attempts and error
+    summary: BulkSummary,
+    // Serial numbers of every AP in a failed chunk, to pass back to put_aps
+    not_created: Vec<String>,
+    // Serial numbers left out because an earlier call with the same run_id created them
+    skipped: Vec<String>,
+}
+
+// Adds APs in chunks of `chunk_size` (default 50) to {api_url}/venues/{venueId}/aps,
+// grouped by each AP's venueId, so a timeout only costs one chunk. A failed chunk
+// doesn't stop the others. Every AP needs a venueId and serialNumber; their absence
+// fails the whole call with Validation before anything is sent.
+// run_id works as for put_venues: chunks carry an Idempotency-Key and APs of chunks
+// that succeeded are skipped when the call is repeated.
+// A chunk refused as too large (413, or over the request body limit) is split in
+// half and both halves sent instead, down to single APs.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+async fn put_aps(
+    state: State<'_, AppState>,
+    confirmed: State<'_, ConfirmedKeys>,
+    api_url: Option<String>,
+    tenant_id: String,
+    auth: Auth,
+    aps: Vec<Value>,
+    chunk_size: Option<usize>,
+    run_id: Option<String>,
+) -> Result<BulkApResult, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let api_url = config.api_url_or(api_url.as_deref())?;
+    let run = run_id.as_deref().map(str::trim).filter(|id| !id.is_empty()).map(|id| (id, &*confirmed));
+    let chunk_size = chunk_size.unwrap_or(DEFAULT_AP_CHUNK_SIZE);
+    request_id::scope(request_id::new(), create_aps(client, &api_url, &tenant_id, &auth, &aps, chunk_size, run)).await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn create_aps(
+    client: &AppClient,
+    api_url: &str,
+    tenant_id: &str,
+    auth: &Auth,
+    aps: &[Value],
+    chunk_size: usize,
+    run: Option<(&str, &ConfirmedKeys)>,
+) -> Result<BulkApResult, MigrationError> {
+    if chunk_size == 0 {
+        return Err(MigrationError::invalid_input("chunk_size must be at least 1"));
+    }
+
+    // Venues in order of first appearance, each with its APs
+    let mut problems = Vec::new();
+    let mut skipped = Vec::new();
+    let mut by_venue: Vec<(&str, Vec<&Value>)> = Vec::new();
+    for (i, ap) in aps.iter().enumerate() {
+        let venue_id = ap.get("venueId").and_then(Value::as_str).filter(|id| !id.is_empty());
+        let has_serial = ap.get("serialNumber").is_some_and(Value::is_string);
+        if !has_serial {
+            problems.push(validation::ValidationProblem::new(format!("aps[{}].serialNumber", i), "must be a string"));
+        }
+        let Some(venue_id) = venue_id else {
+            problems.push(validation::ValidationProblem::new(format!("aps[{}].venueId", i), "must be a non-empty string"));
+            continue;
+        };
+        if let Some((run_id, confirmed)) = run {
+            if confirmed.contains(&ap_key(run_id, ap)) {
+                skipped.push(migration::record_label(ap));
+                continue;
+            }
+        }
+        match by_venue.iter_mut().find(|(id, _)| *id == venue_id) {
+            Some((_, group)) => group.push(ap),
+            None => by_venue.push((venue_id, vec![ap])),
+        }
     }
+    if !problems.is_empty() {
+        return Err(MigrationError::Validation { problems });
+    }
+
+    let api_url = api_url.trim_end_matches('/');
+    let mut result = BulkApResult { chunks: Vec::new(), summary: BulkSummary::default(), not_created: Vec::new(), skipped };
+    for (venue_id, group) in by_venue {
+        let url = format!("{}/venues/{}/aps", api_url, venue_id);
+        let mut pending: VecDeque<&[&Value]> = group.chunks(chunk_size).collect();
+        while let Some(chunk) = pending.pop_front() {
+            let serial_numbers: Vec<String> = chunk.iter().filter_map(|ap| ap.get("serialNumber").and_then(Value::as_str)).map(str::to_string).collect();
+            let payload = Value::Array(chunk.iter().map(|ap| migration::ap_create_payload(ap)).collect());
+            debug!("Adding {} APs to venue {}", chunk.len(), venue_id);
+
+            // The same unconfirmed APs form the same chunks on a retry, so the key repeats
+            let key = run.map(|(run_id, _)| idempotency::resource_key(run_id, "aps", &serial_numbers.join(",")));
+            let (sent, retries) = bulk::count_retries(client.send_json_with_key(Method::POST, &url, auth, Some(tenant_id), Some(&payload), key.as_deref())).await;
+            let error = sent.err();
+            if chunk.len() > 1 && matches!(error, Some(MigrationError::PayloadTooLarge { .. } | MigrationError::RequestTooLarge { .. })) {
+                let (first, second) = chunk.split_at(chunk.len() / 2);
+                tracing::warn!("{} APs for venue {} are too large for one request, sending {} and {}", chunk.len(), venue_id, first.len(), second.len());
+                pending.push_front(second);
+                pending.push_front(first);
+                continue;
+            }
+            for serial_number in &serial_numbers {
+                result.summary.add(serial_number, retries, error.as_ref().map_or(Ok(()), Err));
+            }
+            match (&error, run) {
+                (Some(error), _) => {
+                    tracing::warn!("Failed to add {} APs to venue {}: {}", chunk.len(), venue_id, error);
+                    result.not_created.extend(serial_numbers.iter().cloned());
+                }
+                (None, Some((run_id, confirmed))) => chunk.iter().for_each(|ap| confirmed.confirm(ap_key(run_id, ap))),
+                (None, None) => {}
+            }
+            result.chunks.push(ApChunkResult { venue_id: venue_id.to_string(), serial_numbers, success: error.is_none(), error });
+        }
+    }
+    Ok(result)
 }
 
+fn ap_key(run_id: &str, ap: &Value) -> String {
+    idempotency::resource_key(run_id, "ap", &migration::source_key(ap).unwrap_or_default())
+}
 
 #[tauri::command]
-async fn querywNetworks(api_url: String, tenant_id: String, token: String, query_data: Value) -> Result<String, String> {
-    let url = format!("{}/wifiNetworks/query", api_url);
-    
-    println!("Wifi Networks Query URL: {}", url);
-    println!("Query Data: {}", serde_json::to_string_pretty(&query_data).unwrap());
+async fn delete_tenant(state: State<'_, AppState>, api_url: Option<String>, tenant_id: String, auth: Auth) -> Result<String, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    // An empty id would turn this into a DELETE on the whole /mspCustomers collection
+    let tenant_id = tenant_id.trim();
+    if tenant_id.is_empty() {
+        return Err(MigrationError::invalid_input("tenant_id must not be empty"));
+    }
 
-    let client = reqwest::Client::new();
-    let response = client
-        .post(&url)
-        .header("Authorization", format!("Bearer {}", token))
-        .header("Content-Type", "application/json")
-        .header("x-rks-tenantid", tenant_id)
-        .json(&query_data)
-        .send()
+    let api_url = config.api_url_or(api_url.as_deref())?;
+    let url = format!("{}/{}/{}", api_url, client.paths(&api_url).tenants, tenant_id);
+    debug!("Delete URL: {}", url);
+
+    client
+        .execute(|http| {
+            auth.apply(http.delete(&url))
+                .header("Accept", "application/json")
+        })
         .await
-        .map_err(|e| format!("Request failed: {}", e))?;
-    
-    let status = response.status();
-    let body = response.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
-    
-    if status.is_success() {
-        Ok(body)
-    } else {
-        Err(format!("HTTP {}: {}", status, body))
+}
+
+#[derive(Debug, serde::Serialize)]
+struct DeletedResource {
+    status: u16,
+    body: String,
+}
+
+// Deletes one child resource, e.g. path "venues" or "venues/{venueId}/aps" with the
+// venue or AP id, to clean up after a bad run by hand. `id` must not be empty so a
+// typo can't turn this into a DELETE on the collection. A missing or empty api_url
+// falls back to the saved config.
+#[tauri::command]
+async fn delete_resource(state: State<'_, AppState>, api_url: Option<String>, path: String, id: String, tenant_id: String, auth: Auth) -> Result<DeletedResource, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let api_url = config.api_url_or(api_url.as_deref())?;
+    remove_resource(client, &api_url, &path, &id, &tenant_id, &auth).await
+}
+
+async fn remove_resource(client: &AppClient, api_url: &str, path: &str, id: &str, tenant_id: &str, auth: &Auth) -> Result<DeletedResource, MigrationError> {
+    let path = path.trim().trim_matches('/');
+    let id = id.trim();
+    if path.is_empty() {
+        return Err(MigrationError::invalid_input("path must not be empty"));
     }
+    if id.is_empty() || id.contains('/') {
+        return Err(MigrationError::invalid_input("id must be a single non-empty resource id"));
+    }
+
+    let url = format!("{}/{}/{}", api_url.trim_end_matches('/'), path, id);
+    debug!("Delete URL: {}", url);
+
+    let response = client
+        .execute_raw(|http| {
+            let request = auth
+                .apply(http.delete(&url))
+                .header("Accept", "application/json");
+            client::with_tenant_header(request, Some(tenant_id))
+        })
+        .await?;
+    Ok(DeletedResource { status: response.status, body: response.body })
 }
 
+// Shared implementation for every `{api_url}/<resource>/query` endpoint - they all take
+// the same headers and a JSON query body, only the path differs. A missing api_url
+// falls back to the saved config.
 #[tauri::command]
-async fn query_aps(api_url: String, tenant_id: String, token: String, query_data: Value) -> Result<String, String> {
-    let url = format!("{}/venues/aps/query", api_url);
+async fn query_resource(state: State<'_, AppState>, api_url: Option<String>, tenant_id: String, auth: Auth, resource_path: String, query_data: Value) -> Result<String, MigrationError> {
+    query_resource_verbose(state, api_url, tenant_id, auth, resource_path, query_data).await.map(|response| response.body)
+}
+
+// query_resource returning the status and headers along with the body
+#[tauri::command]
+async fn query_resource_verbose(state: State<'_, AppState>, api_url: Option<String>, tenant_id: String, auth: Auth, resource_path: String, query_data: Value) -> Result<VerboseResponse, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let resource_path = resource_path.trim_matches('/');
+    if resource_path.is_empty() {
+        return Err(MigrationError::invalid_input("resource_path must not be empty"));
+    }
+    validation::validate_query(query_data.clone())?;
+
+    let url = format!("{}/{}", config.api_url_or(api_url.as_deref())?, resource_path);
     
-    println!("APs Query URL: {}", url);
-    println!("Query Data: {}", serde_json::to_string_pretty(&query_data).unwrap());
+    debug!("Query URL: {}", url);
+    trace!("Query Data: {}", query_data);
+    post_query(client, &url, &tenant_id, &auth, &query_data).await
+}
 
-    let client = reqwest::Client::new();
+async fn post_query(client: &AppClient, url: &str, tenant_id: &str, auth: &Auth, query_data: &Value) -> Result<VerboseResponse, MigrationError> {
+    // Re-renders in the frontend can send the same query several times at once
+    let key = coalesce::key(&reqwest::Method::POST, url, tenant_id, auth, query_data);
     let response = client
-        .post(&url)
-        .header("Authorization", format!("Bearer {}", token))
-        .header("Content-Type", "application/json")
-        .header("x-rks-tenantid", tenant_id)
-        .json(&query_data)
-        .send()
-        .await
-        .map_err(|e| format!("Request failed: {}", e))?;
-    
-    let status = response.status();
-    let body = response.text().await.map_err(|e| format!("Failed to read response: {}", e))?;
-    
-    if status.is_success() {
-        Ok(body)
-    } else {
-        Err(format!("HTTP {}: {}", status, body))
+        .execute_coalesced(key, |http| {
+            let request = auth
+                .apply(http.post(url))
+                .header("Content-Type", "application/json")
+                .json(query_data);
+            client::with_tenant_header(request, Some(tenant_id))
+        })
+        .await?;
+    Ok(response.into())
+}
+
+// Escape hatch for endpoints without their own command (e.g. venues/{id}/lan):
+// authenticated GET of {api_url}/{path}, returning the raw body. x-rks-tenantid is
+// only sent when tenant_id is given.
+#[tauri::command]
+async fn get_resource(state: State<'_, AppState>, api_url: Option<String>, path: String, tenant_id: Option<String>, auth: Auth) -> Result<String, MigrationError> {
+    get_resource_verbose(state, api_url, path, tenant_id, auth).await.map(|response| response.body)
+}
+
+// get_resource returning the status and headers along with the body
+#[tauri::command]
+async fn get_resource_verbose(state: State<'_, AppState>, api_url: Option<String>, path: String, tenant_id: Option<String>, auth: Auth) -> Result<VerboseResponse, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let path = path.trim().trim_matches('/');
+    if path.is_empty() {
+        return Err(MigrationError::invalid_input("path must not be empty"));
+    }
+
+    let url = format!("{}/{}", config.api_url_or(api_url.as_deref())?, path);
+    debug!("GET {}", url);
+
+    let response = client
+        .execute_raw(|http| {
+            let request = auth
+                .apply(http.get(&url))
+                .header("Accept", "application/json");
+            client::with_tenant_header(request, tenant_id.as_deref())
+        })
+        .await?;
+    Ok(response.into())
+}
+
+// Like query_resource but follows pagination and returns every record as one array.
+// `page`/`pageSize` in query_data are overwritten; page_size defaults to 500.
+#[tauri::command]
+async fn query_resource_all(state: State<'_, AppState>, api_url: Option<String>, tenant_id: String, auth: Auth, resource_path: String, query_data: Value, page_size: Option<u64>) -> Result<Vec<Value>, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let resource_path = resource_path.trim_matches('/');
+    if resource_path.is_empty() {
+        return Err(MigrationError::invalid_input("resource_path must not be empty"));
     }
+    validation::validate_query(query_data.clone())?;
+
+    let url = format!("{}/{}", config.api_url_or(api_url.as_deref())?, resource_path);
+    debug!("Paginated Query URL: {}", url);
+
+    let pages = query::fetch_all_pages(client, &url, &auth, &tenant_id, &query_data, page_size.unwrap_or(query::DEFAULT_PAGE_SIZE));
+    request_id::scope(request_id::new(), pages).await
 }
 
+#[tauri::command]
+async fn query_venues(state: State<'_, AppState>, api_url: Option<String>, tenant_id: String, auth: Auth, query_data: Value) -> Result<String, MigrationError> {
+    query_resource(state, api_url, tenant_id, auth, "venues/query".to_string(), query_data).await
+}
 
+#[tauri::command]
+async fn query_wifi_networks(state: State<'_, AppState>, api_url: Option<String>, tenant_id: String, auth: Auth, query_data: Value) -> Result<String, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let api_url = config.api_url_or(api_url.as_deref())?;
+    let path = format!("{}/query", client.paths(&api_url).wifi_networks);
+    query_resource(state, Some(api_url), tenant_id, auth, path, query_data).await
+}
+
+// Wifi networks of one venue, with the same fields as the frontend's querywNetworks.
+// query_wifi_networks stays available for anything this filter doesn't cover.
+#[tauri::command]
+async fn query_networks_by_venue(state: State<'_, AppState>, api_url: Option<String>, tenant_id: String, venue_id: String, auth: Auth) -> Result<String, MigrationError> {
+    if venue_id.trim().is_empty() {
+        return Err(MigrationError::invalid_input("venue_id must not be empty"));
+    }
+    let mut query_data = query::Resource::WifiNetworks.venue_query(&venue_id);
+    query_data["page"] = json!(1);
+    query_data["pageSize"] = json!(validation::MAX_QUERY_PAGE_SIZE);
+    query_wifi_networks(state, api_url, tenant_id, auth, query_data).await
+}
+
+// DEPRECATED: old misspelled name for query_wifi_networks, kept registered so existing
+// callers don't break. Will be removed in the next major version.
+#[allow(non_snake_case)]
+#[tauri::command]
+async fn querywNetworks(state: State<'_, AppState>, api_url: Option<String>, tenant_id: String, auth: Auth, query_data: Value) -> Result<String, MigrationError> {
+    query_wifi_networks(state, api_url, tenant_id, auth, query_data).await
+}
+
+#[tauri::command]
+async fn query_aps(state: State<'_, AppState>, api_url: Option<String>, tenant_id: String, auth: Auth, query_data: Value) -> Result<String, MigrationError> {
+    query_resource(state, api_url, tenant_id, auth, "venues/aps/query".to_string(), query_data).await
+}
+
+// query_venues / query_wifi_networks / query_aps with the records parsed into
+// models:: structs, for callers that want an array rather than the response text
+#[tauri::command]
+async fn query_venues_typed(state: State<'_, AppState>, api_url: Option<String>, tenant_id: String, auth: Auth, query_data: Value) -> Result<Vec<models::Venue>, MigrationError> {
+    models::parse_records(&query_venues(state, api_url, tenant_id, auth, query_data).await?)
+}
+
+#[tauri::command]
+async fn query_wifi_networks_typed(state: State<'_, AppState>, api_url: Option<String>, tenant_id: String, auth: Auth, query_data: Value) -> Result<Vec<models::WifiNetwork>, MigrationError> {
+    models::parse_records(&query_wifi_networks(state, api_url, tenant_id, auth, query_data).await?)
+}
+
+#[tauri::command]
+async fn query_aps_typed(state: State<'_, AppState>, api_url: Option<String>, tenant_id: String, auth: Auth, query_data: Value) -> Result<Vec<models::Ap>, MigrationError> {
+    models::parse_records(&query_aps(state, api_url, tenant_id, auth, query_data).await?)
+}
+
+// ICX switches, same query body/header pattern as query_aps
+#[tauri::command]
+async fn query_switches(state: State<'_, AppState>, api_url: Option<String>, tenant_id: String, auth: Auth, query_data: Value) -> Result<String, MigrationError> {
+    query_resource(state, api_url, tenant_id, auth, "venues/switches/query".to_string(), query_data).await
+}
+
+// Every AP in the tenant, queried venue by venue with at most `max_in_flight`
+// requests running at once (default concurrency::DEFAULT_MAX_IN_FLIGHT)
+#[tauri::command]
+async fn query_all_aps(state: State<'_, AppState>, api_url: Option<String>, tenant_id: String, auth: Auth, max_in_flight: Option<usize>) -> Result<Vec<Value>, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    request_id::scope(request_id::new(), fetch_all_aps(client, &config.api_url_or(api_url.as_deref())?, &tenant_id, &auth, max_in_flight)).await
+}
+
+async fn fetch_all_aps(client: &AppClient, api_url: &str, tenant_id: &str, auth: &Auth, max_in_flight: Option<usize>) -> Result<Vec<Value>, MigrationError> {
+    let venues = query::fetch_resource(client, api_url, auth, tenant_id, query::Resource::Venues).await?;
+    let venue_ids: Vec<String> = venues
+        .iter()
+        .filter_map(|venue| venue.get("id").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect();
+    debug!("Querying APs for {} venues", venue_ids.len());
+
+    let url = format!("{}/{}", api_url, query::Resource::Aps.query_path(client.paths(api_url)));
+    let per_venue = concurrency::try_map_bounded(venue_ids, max_in_flight.unwrap_or(concurrency::DEFAULT_MAX_IN_FLIGHT), |venue_id| {
+        let query_data = query::Resource::Aps.venue_query(&venue_id);
+        let url = &url;
+        async move { query::fetch_all_pages(client, url, auth, tenant_id, &query_data, query::DEFAULT_PAGE_SIZE).await }
+    })
+    .await?;
+
+    Ok(per_venue.into_iter().flatten().collect())
+}
+
+// Cheap preflight: the size of each resource type on the tenant without fetching the
+// records, e.g. for a progress bar maximum. A count is null when its endpoint doesn't
+// return totalCount. A missing or empty api_url falls back to the saved config.
+#[tauri::command]
+async fn resource_counts(state: State<'_, AppState>, api_url: Option<String>, tenant_id: String, auth: Auth) -> Result<query::ResourceCounts, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let api_url = config.api_url_or(api_url.as_deref())?;
+    request_id::scope(request_id::new(), query::count_all(client, &api_url, &auth, &tenant_id)).await
+}
+
+#[derive(Debug, serde::Serialize)]
+struct ApFirmware {
+    ap_serial: String,
+    model: Option<String>,
+    // None when R1 hasn't reported a version yet, e.g. an AP that never came online
+    firmware_version: Option<String>,
+}
+
+// Firmware version of every AP in one venue, for checking that a venue runs a single
+// version before and after a migration. Taken from the AP query, all pages.
+#[tauri::command]
+async fn query_ap_firmware(state: State<'_, AppState>, api_url: Option<String>, tenant_id: String, venue_id: String, auth: Auth) -> Result<Vec<ApFirmware>, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let api_url = config.api_url_or(api_url.as_deref())?;
+    request_id::scope(request_id::new(), fetch_ap_firmware(client, &api_url, &tenant_id, &venue_id, &auth)).await
+}
+
+async fn fetch_ap_firmware(client: &AppClient, api_url: &str, tenant_id: &str, venue_id: &str, auth: &Auth) -> Result<Vec<ApFirmware>, MigrationError> {
+    if venue_id.trim().is_empty() {
+        return Err(MigrationError::invalid_input("venue_id must not be empty"));
+    }
+    let mut query_data = query::Resource::Aps.venue_query(venue_id);
+    query_data["fields"] = json!(["serialNumber", "model", "firmwareVersion"]);
+    let url = format!("{}/{}", api_url, query::Resource::Aps.query_path(client.paths(api_url)));
+    let aps = query::fetch_all_pages(client, &url, auth, tenant_id, &query_data, query::DEFAULT_PAGE_SIZE).await?;
+
+    let field = |ap: &Value, name: &str| ap.get(name).and_then(Value::as_str).map(str::to_string);
+    Ok(aps
+        .iter()
+        .filter_map(|ap| {
+            Some(ApFirmware { ap_serial: field(ap, "serialNumber")?, model: field(ap, "model"), firmware_version: field(ap, "firmwareVersion") })
+        })
+        .collect())
+}
+
+#[derive(Clone, serde::Serialize)]
+struct ApPage {
+    venue_id: String,
+    page: u64,
+    records: Vec<Value>,
+}
+
+#[derive(Clone, serde::Serialize)]
+struct ApsDone {
+    venues: usize,
+    total: u64,
+}
+
+// query_all_aps for inventories too big to hold at once: every page is emitted as an
+// "aps-page" event (venue id, page number, records) as soon as it arrives and then
+// dropped, followed by "aps-done" with the totals. Pages of different venues can
+// interleave. On failure no "aps-done" is sent and the command returns the error.
+#[tauri::command]
+async fn query_all_aps_streaming(app: tauri::AppHandle, state: State<'_, AppState>, api_url: Option<String>, tenant_id: String, auth: Auth, max_in_flight: Option<usize>) -> Result<ApsDone, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let api_url = config.api_url_or(api_url.as_deref())?;
+    let emit = |page: ApPage| {
+        let _ = app.emit("aps-page", page);
+    };
+    let done = request_id::scope(request_id::new(), stream_all_aps(client, &api_url, &tenant_id, &auth, max_in_flight, &emit)).await?;
+    let _ = app.emit("aps-done", done.clone());
+    Ok(done)
+}
+
+async fn stream_all_aps(
+    client: &AppClient,
+    api_url: &str,
+    tenant_id: &str,
+    auth: &Auth,
+    max_in_flight: Option<usize>,
+    emit: &(dyn Fn(ApPage) + Sync),
+) -> Result<ApsDone, MigrationError> {
+    let venues = query::fetch_resource(client, api_url, auth, tenant_id, query::Resource::Venues).await?;
+    let venue_ids: Vec<String> = venues
+        .iter()
+        .filter_map(|venue| venue.get("id").and_then(Value::as_str))
+        .map(str::to_string)
+        .collect();
+    let venue_count = venue_ids.len();
+
+    let url = format!("{}/{}", api_url, query::Resource::Aps.query_path(client.paths(api_url)));
+    let per_venue = concurrency::try_map_bounded(venue_ids, max_in_flight.unwrap_or(concurrency::DEFAULT_MAX_IN_FLIGHT), |venue_id| {
+        let query_data = query::Resource::Aps.venue_query(&venue_id);
+        let url = &url;
+        async move {
+            let on_page = |page, records| emit(ApPage { venue_id: venue_id.clone(), page, records });
+            query::fetch_pages(client, url, auth, tenant_id, &query_data, query::DEFAULT_PAGE_SIZE, on_page).await
+        }
+    })
+    .await?;
+
+    Ok(ApsDone { venues: venue_count, total: per_venue.into_iter().sum() })
+}
+
+// Log level comes from RUST_LOG (e.g. RUST_LOG=tenantmigration_lib=trace to see request
+// bodies). Without it release builds log at info so payloads never hit stdout.
+fn init_logging() {
+    let default_level = if cfg!(debug_assertions) { "debug" } else { "info" };
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default_level));
+    let _ = tracing_subscriber::fmt().with_env_filter(filter).try_init();
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    init_logging();
+
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_http::init())
         .plugin(tauri_plugin_fs::init())
-        .invoke_handler(tauri::generate_handler![greet, get_tenant, put_tenant, query_venues, querywNetworks, query_aps])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
\ No newline at end of file
+        .manage(AppState::new().expect("failed to build HTTP client"))
+        .manage(SubmitGuard::<CreatedTenant>::default())
+        .manage(ConfirmedKeys::default())
+        .setup(|app| {
+            config::init(app.handle());
+            Ok(())
+        })
+        .invoke_handler(tauri::generate_handler![greet, set_timeout, set_connect_timeout, set_circuit_breaker, set_rate_limit, set_cache_ttl, clear_cache, set_max_attempts, set_body_limits, set_accept_language, set_extra_headers, set_proxy, set_ca_cert, set_http_version, danger_accept_invalid_certs, get_tenant, get_tenant_with_etag, put_tenant, patch_tenant, put_venues, put_aps, delete_tenant, delete_resource, query_resource, query_resource_verbose, get_resource, get_resource_verbose, query_resource_all, query_venues, query_wifi_networks, query_networks_by_venue, querywNetworks, query_aps, query_venues_typed, query_wifi_networks_typed, query_aps_typed, query_switches, query_all_aps, query_all_aps_streaming, query_ap_firmware, resource_counts, apiurl::normalize_api_url, connection::check_connection, permissions::check_permissions, discovery::discover_endpoint, capabilities::detect_capabilities, plan::plan_migration, preflight::preflight_target, merge::merge_tenants, migration::migrate_tenant, migration::migrate_tenants, migration::cancel_migration, migration::resume_migration, rollback::rollback_migration, report::export_report_csv, snapshot::export_tenant, snapshot::export_all_tenants, snapshot::import_tenant, snapshot::download_export, diff::diff_tenants, diff::diff_snapshot_vs_live, diff::canonicalize_tenant, diff::compare_ssids, token::token_info, keychain::store_token, keychain::load_token, keychain::delete_token, config::save_config, config::load_config, config::set_region, region::region_urls, oauth::refresh_token, activity::wait_for_activity, tenants::list_tenants, schema::schema_of, schema::diff_schemas, validation::validate_query, templates::query_template, verify::verify_migration, curl::build_curl])
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::ExitRequested { api, .. } = &event {
+                migration::on_exit_requested(app, api);
+            }
+        });
+}
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use backend::mock::MockBackend;
+
+    const API: &str = "https://api.example.test";
+    const MSP: &str = "msp-tenant";
+    const TOKEN: &str = "header.payload.signature";
+
+    fn tenant() -> Value {
+        json!({
+            "name": "Acme",
+            "tenant_type": "MSP_EC",
+            "service_effective_date": "2026-01-01",
+            "service_expiration_date": "2027-01-01",
+            "admin_email": "admin@acme.test",
+            "admin_firstname": "Ada",
+            "admin_lastname": "Admin",
+            "admin_role": "PRIME_ADMIN"
+        })
+    }
+
+    fn auth() -> Auth {
+        Auth::Bearer(TOKEN.to_string())
+    }
+
+    fn client_with(mock: &Arc<MockBackend>) -> AppClient {
+        AppClient::with_backend(mock.clone()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn commands_without_an_api_url_use_the_selected_region() {
+        use tauri::Manager;
+
+        let mock = Arc::new(MockBackend::default());
+        let app = tauri::test::mock_app();
+        app.manage(AppState { client: client_with(&mock), config: config::ConfigStore::default(), control: migration::MigrationControl::default() });
+        config::set_region(app.state(), region::Region::Europe);
+
+        get_tenant(app.state(), None, "t1".to_string(), auth()).await.unwrap();
+        assert_eq!(mock.requests()[0].url, "https://api.eu.ruckus.cloud/tenants/t1");
+    }
+
+    #[tokio::test]
+    async fn put_tenant_posts_to_msp_customers() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(201, r#"{"id":"new-tenant","tenantId":"t-1"}"#);
+        let client = client_with(&mock);
+
+        let created = create_tenant(&client, API, MSP, &auth(), tenant(), false, None, None).await.unwrap();
+        assert_eq!(created.id.as_deref(), Some("new-tenant"));
+        assert_eq!(created.tenant_id.as_deref(), Some("t-1"));
+        assert_eq!(created.raw, r#"{"id":"new-tenant","tenantId":"t-1"}"#);
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 1);
+        let request = &requests[0];
+        assert_eq!(request.method, Method::POST);
+        assert_eq!(request.url, format!("{}/mspCustomers", API));
+        assert_eq!(request.headers["authorization"], format!("Bearer {}", TOKEN));
+        assert_eq!(request.headers["content-type"], "application/json");
+        assert_eq!(request.headers["x-rks-tenantid"], MSP);
+        assert!(request.headers.get("if-match").is_none());
+        assert_eq!(request.body, Some(tenant()));
+    }
+
+    #[tokio::test]
+    async fn put_tenant_omits_tenant_header_when_id_is_empty() {
+        let mock = Arc::new(MockBackend::default());
+        let client = client_with(&mock);
+
+        create_tenant(&client, API, "  ", &auth(), tenant(), false, None, None).await.unwrap();
+        assert!(mock.requests()[0].headers.get("x-rks-tenantid").is_none());
+    }
+
+    #[tokio::test]
+    async fn queries_send_the_tenant_header_only_when_given() {
+        let mock = Arc::new(MockBackend::default());
+        let client = client_with(&mock);
+        let url = format!("{}/venues/query", API);
+        let query_data = json!({ "page": 1, "pageSize": 10 });
+
+        post_query(&client, &url, MSP, &auth(), &query_data).await.unwrap();
+        post_query(&client, &url, "", &auth(), &query_data).await.unwrap();
+
+        let requests = mock.requests();
+        assert_eq!(requests[0].headers["x-rks-tenantid"], MSP);
+        assert!(requests[1].headers.get("x-rks-tenantid").is_none());
+        assert_eq!(requests[1].body, Some(query_data));
+    }
+
+    #[tokio::test]
+    async fn paginated_queries_without_a_tenant_leave_the_header_off() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(200, r#"{"data":[{"id":"a"}],"totalCount":2}"#).respond(200, r#"{"data":[{"id":"b"}],"totalCount":2}"#);
+        let client = client_with(&mock);
+
+        let url = format!("{}/venues/query", API);
+        let records = query::fetch_all_pages(&client, &url, &auth(), "", &json!({}), 1).await.unwrap();
+        assert_eq!(records.len(), 2);
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|request| request.headers.get("x-rks-tenantid").is_none()));
+    }
+
+    #[tokio::test]
+    async fn put_tenant_keeps_raw_body_when_no_id() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(202, "accepted");
+        let client = client_with(&mock);
+
+        let created = create_tenant(&client, API, MSP, &auth(), tenant(), false, None, None).await.unwrap();
+        assert!(created.id.is_none() && created.tenant_id.is_none());
+        assert_eq!(created.raw, "accepted");
+    }
+
+    #[tokio::test]
+    async fn put_tenant_sends_if_match_and_maps_412_to_conflict() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(412, "stale");
+        let client = client_with(&mock);
+
+        let error = create_tenant(&client, API, MSP, &auth(), tenant(), false, Some("\"v1\""), None).await.unwrap_err();
+        assert!(matches!(error, MigrationError::Conflict { ref body } if body == "stale"), "{:?}", error);
+        assert_eq!(mock.requests()[0].headers["if-match"], "\"v1\"");
+    }
+
+    #[tokio::test]
+    async fn put_tenant_sends_idempotency_key() {
+        let mock = Arc::new(MockBackend::default());
+        let client = client_with(&mock);
+
+        create_tenant(&client, API, MSP, &auth(), tenant(), false, None, Some("submit-1")).await.unwrap();
+        assert_eq!(mock.requests()[0].headers["idempotency-key"], "submit-1");
+    }
+
+    #[tokio::test]
+    async fn put_tenant_dry_run_sends_nothing() {
+        let mock = Arc::new(MockBackend::default());
+        let client = client_with(&mock);
+
+        let created = create_tenant(&client, API, MSP, &auth(), tenant(), true, None, None).await.unwrap();
+        assert!(created.id.is_none());
+        let preview: Value = serde_json::from_str(&created.raw).unwrap();
+        assert_eq!(preview["url"], format!("{}/mspCustomers", API));
+        assert!(!preview["headers"]["Authorization"].as_str().unwrap().contains("payload"));
+
+        let api_key = Auth::ApiKey("0123456789abcdef".to_string());
+        let created = create_tenant(&client, API, MSP, &api_key, tenant(), true, None, None).await.unwrap();
+        let preview: Value = serde_json::from_str(&created.raw).unwrap();
+        assert!(preview["headers"].get("Authorization").is_none());
+        assert_eq!(preview["headers"]["x-api-key"], redact::mask_token("0123456789abcdef"));
+        assert!(mock.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn put_tenant_rejects_invalid_payload_before_sending() {
+        let mock = Arc::new(MockBackend::default());
+        let client = client_with(&mock);
+
+        let error = create_tenant(&client, API, MSP, &auth(), json!({ "name": "Acme" }), false, None, None).await.unwrap_err();
+        assert!(matches!(error, MigrationError::Validation { .. }), "{:?}", error);
+        assert!(mock.requests().is_empty());
+    }
+
+    #[tokio::test]
+    async fn execute_retries_service_unavailable() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(503, "").respond(200, "ok");
+        let client = client_with(&mock);
+
+        let body = client.execute(|http| http.get(format!("{}/tenants/1", API))).await.unwrap();
+        assert_eq!(body, "ok");
+        assert_eq!(mock.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn retries_share_request_id_and_errors_carry_it() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(503, "").respond(400, "bad");
+        let client = client_with(&mock);
+
+        let error = request_id::scope("req-123".to_string(), client.execute(|http| http.get(format!("{}/tenants/1", API))))
+            .await
+            .unwrap_err();
+        assert_eq!(error.request_id(), Some("req-123"));
+        let requests = mock.requests();
+        assert!(requests.iter().all(|r| r.headers[request_id::HEADER] == "req-123"));
+
+        // Outside a scope every call gets its own id
+        mock.respond(200, "").respond(200, "");
+        client.execute(|http| http.get(format!("{}/tenants/1", API))).await.unwrap();
+        client.execute(|http| http.get(format!("{}/tenants/1", API))).await.unwrap();
+        let requests = mock.requests();
+        assert_ne!(requests[2].headers[request_id::HEADER], requests[3].headers[request_id::HEADER]);
+    }
+
+    #[tokio::test]
+    async fn sends_configured_accept_language() {
+        let mock = Arc::new(MockBackend::default());
+        let client = client_with(&mock);
+
+        client.execute(|http| http.get(format!("{}/tenants/1", API))).await.unwrap();
+        client.set_accept_language(Some("de-DE")).unwrap();
+        client.execute(|http| http.get(format!("{}/tenants/1", API))).await.unwrap();
+        assert!(client.set_accept_language(Some("de\nDE")).is_err());
+
+        let requests = mock.requests();
+        assert_eq!(requests[0].headers["accept-language"], "en-US");
+        assert_eq!(requests[1].headers["accept-language"], "de-DE");
+    }
+
+    #[tokio::test]
+    async fn empty_token_falls_back_to_the_stored_one() {
+        let mock = Arc::new(MockBackend::default());
+        let client = client_with(&mock);
+        client.set_fallback_token(Some("stored".to_string()));
+
+        let url = format!("{}/venues", API);
+        client.send_json(Method::GET, &url, &Auth::Bearer("".to_string()), None, None).await.unwrap();
+        client.send_json(Method::GET, &url, &auth(), None, None).await.unwrap();
+        let requests = mock.requests();
+        assert_eq!(requests[0].headers["authorization"], "Bearer stored");
+        assert_eq!(requests[1].headers["authorization"], format!("Bearer {}", TOKEN));
+    }
+
+    #[tokio::test]
+    async fn api_keys_are_sent_as_x_api_key() {
+        let mock = Arc::new(MockBackend::default());
+        let client = client_with(&mock);
+        let bearer: Auth = serde_json::from_value(json!(" key-1 ")).unwrap();
+        let api_key: Auth = serde_json::from_value(json!({ "scheme": "api_key", "value": " key-1 " })).unwrap();
+        assert_eq!(bearer, Auth::Bearer("key-1".to_string()));
+
+        // The scheme comes with each call, so the same value can be sent either way
+        let url = format!("{}/venues", API);
+        client.send_json(Method::GET, &url, &api_key, None, None).await.unwrap();
+        client.send_json(Method::GET, &url, &bearer, None, None).await.unwrap();
+        let requests = mock.requests();
+        assert_eq!(requests[0].headers["x-api-key"], "key-1");
+        assert!(!requests[0].headers.contains_key("authorization"));
+        assert_eq!(requests[1].headers["authorization"], "Bearer key-1");
+        assert!(!requests[1].headers.contains_key("x-api-key"));
+    }
+
+    #[tokio::test]
+    async fn cached_reads_are_reused_until_a_write() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(200, r#"{"data":[]}"#).respond(200, r#"{"id":"v1"}"#).respond(200, r#"{"data":[{"id":"v1"}]}"#);
+        let client = client_with(&mock);
+        client.set_cache_ttl(Some(Duration::from_secs(60)));
+
+        let (url, query, auth) = (format!("{}/venues/query", API), json!({ "page": 1 }), auth());
+        let read = || client.send_json(Method::POST, &url, &auth, Some(MSP), Some(&query));
+        assert_eq!(read().await.unwrap(), json!({ "data": [] }));
+        assert_eq!(read().await.unwrap(), json!({ "data": [] }));
+        assert_eq!(mock.requests().len(), 1);
+
+        client.send_json(Method::POST, &format!("{}/venues", API), &auth, Some(MSP), Some(&json!({ "name": "HQ" }))).await.unwrap();
+        assert_eq!(read().await.unwrap(), json!({ "data": [{ "id": "v1" }] }));
+        assert_eq!(mock.requests().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn send_json_refreshes_remembered_token_on_401() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(401, "expired")
+            .respond(200, r#"{"access_token":"fresh","expires_in":3600}"#)
+            .respond(200, r#"{"ok":true}"#);
+        let client = client_with(&mock);
+        client.tokens().remember(
+            "stale",
+            Arc::new(oauth::OAuthCredentials {
+                auth_url: "https://auth.example.test".to_string(),
+                tenant_id: "msp-1".to_string(),
+                client_id: "id".to_string(),
+                client_secret: "secret".to_string(),
+            }),
+        );
+
+        let url = format!("{}/venues", API);
+        let value = client.send_json(Method::GET, &url, &Auth::Bearer("stale".to_string()), None, None).await.unwrap();
+        assert_eq!(value, json!({ "ok": true }));
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[1].url, "https://auth.example.test/oauth2/token/msp-1");
+        assert_eq!(requests[1].body, Some(json!("grant_type=client_credentials&client_id=id&client_secret=secret")));
+        assert_eq!(requests[2].headers["authorization"], "Bearer fresh");
+
+        // Later calls still holding the old token go straight to the new one
+        client.send_json(Method::GET, &url, &Auth::Bearer("stale".to_string()), None, None).await.unwrap();
+        assert_eq!(mock.requests()[3].headers["authorization"], "Bearer fresh");
+    }
+
+    #[tokio::test]
+    async fn delete_resource_targets_one_id() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(202, r#"{"requestId":"r-1"}"#);
+        let client = client_with(&mock);
+
+        let deleted = remove_resource(&client, API, "/venues/v1/aps/", "123456789012", MSP, &auth()).await.unwrap();
+        assert_eq!((deleted.status, deleted.body.as_str()), (202, r#"{"requestId":"r-1"}"#));
+        let request = &mock.requests()[0];
+        assert_eq!(request.method, Method::DELETE);
+        assert_eq!(request.url, format!("{}/venues/v1/aps/123456789012", API));
+        assert_eq!(request.headers["x-rks-tenantid"], MSP);
+
+        let error = remove_resource(&client, API, "venues", " ", MSP, &auth()).await.unwrap_err();
+        assert!(matches!(error, MigrationError::InvalidInput { .. }));
+        assert_eq!(mock.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn streaming_ap_query_emits_each_page() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(200, r#"{"data":[{"id":"v1"}],"totalCount":1}"#)
+            .respond(200, r#"{"data":[{"serialNumber":"1"},{"serialNumber":"2"}],"totalCount":2}"#);
+        let client = client_with(&mock);
+
+        let pages = std::sync::Mutex::new(Vec::new());
+        let emit = |page: ApPage| pages.lock().unwrap().push((page.venue_id, page.page, page.records.len()));
+        let done = stream_all_aps(&client, API, MSP, &auth(), None, &emit).await.unwrap();
+        assert_eq!((done.venues, done.total), (1, 2));
+        assert_eq!(pages.into_inner().unwrap(), [("v1".to_string(), 1, 2)]);
+    }
+
+    #[tokio::test]
+    async fn extra_headers_are_sent_and_authorization_is_guarded() {
+        let mock = Arc::new(MockBackend::default());
+        let client = client_with(&mock);
+
+        let headers = HashMap::from([("Authorization".to_string(), "Basic x".to_string()), ("bad header".to_string(), "1".to_string())]);
+        let Err(MigrationError::Validation { problems }) = client.set_extra_headers(&headers, false) else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(problems.len(), 2);
+
+        client.set_extra_headers(&HashMap::from([("x-rks-version".to_string(), "2".to_string())]), false).unwrap();
+        client.send_json(Method::GET, &format!("{}/venues", API), &auth(), None, None).await.unwrap();
+        let request = &mock.requests()[0];
+        assert_eq!(request.headers["x-rks-version"], "2");
+        assert_eq!(request.headers["authorization"], format!("Bearer {}", TOKEN));
+    }
+
+    #[test]
+    fn verbose_response_joins_repeated_headers() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.append("Location", "/venues/v1".parse().unwrap());
+        headers.append("Link", "<page=2>".parse().unwrap());
+        headers.append("Link", "<page=9>".parse().unwrap());
+        let raw = client::RawResponse { status: 201, headers, body: "{}".to_string(), request_id: "r".to_string(), timing: Default::default() };
+
+        let verbose = VerboseResponse::from(raw);
+        assert_eq!(verbose.status, 201);
+        assert_eq!(verbose.headers["location"], "/venues/v1");
+        assert_eq!(verbose.headers["link"], "<page=2>, <page=9>");
+    }
+
+    #[tokio::test]
+    async fn ap_firmware_is_condensed_from_the_venue_ap_query() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(200, r#"{"data":[{"serialNumber":"1","model":"R750","firmwareVersion":"7.0.0"},{"serialNumber":"2","model":"R650"}],"totalCount":2}"#);
+        let client = client_with(&mock);
+
+        let aps = fetch_ap_firmware(&client, API, MSP, "v1", &auth()).await.unwrap();
+        assert_eq!(aps.len(), 2);
+        assert_eq!((aps[0].ap_serial.as_str(), aps[0].firmware_version.as_deref()), ("1", Some("7.0.0")));
+        assert_eq!(aps[1].firmware_version, None);
+        assert_eq!(mock.requests()[0].body.as_ref().unwrap()["filters"], json!({ "venueId": ["v1"] }));
+    }
+
+    #[tokio::test]
+    async fn put_aps_chunks_per_venue_and_reports_failed_chunks() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(200, "{}").respond(503, "").respond(200, "{}");
+        let client = client_with(&mock);
+        client.set_max_attempts(1);
+
+        let ap = |serial: &str, venue: &str| json!({ "serialNumber": serial, "venueId": venue, "name": serial });
+        let aps = [ap("1", "v1"), ap("2", "v1"), ap("3", "v1"), ap("4", "v2")];
+        let result = create_aps(&client, API, MSP, &auth(), &aps, 2, None).await.unwrap();
+
+        let urls: Vec<String> = mock.requests().into_iter().map(|r| r.url).collect();
+        assert_eq!(urls, [format!("{}/venues/v1/aps", API), format!("{}/venues/v1/aps", API), format!("{}/venues/v2/aps", API)]);
+        assert_eq!(mock.requests()[0].body.as_ref().unwrap().as_array().unwrap().len(), 2);
+        assert_eq!(result.chunks.iter().map(|c| c.success).collect::<Vec<_>>(), [true, false, true]);
+        assert_eq!(result.not_created, ["3"]);
+    }
+
+    #[tokio::test]
+    async fn put_aps_splits_chunks_refused_as_too_large() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(413, "").respond(200, "{}").respond(413, "");
+        let client = client_with(&mock);
+        client.set_max_attempts(1);
+
+        let ap = |serial: &str| json!({ "serialNumber": serial, "venueId": "v1", "name": serial });
+        let result = create_aps(&client, API, MSP, &auth(), &[ap("1"), ap("2"), ap("3")], 3, None).await.unwrap();
+
+        let sizes: Vec<usize> = mock.requests().iter().map(|r| r.body.as_ref().unwrap().as_array().unwrap().len()).collect();
+        assert_eq!(sizes, [3, 1, 2, 1, 1]);
+        assert_eq!(result.summary.succeeded, 3);
+        assert!(result.not_created.is_empty());
+
+        // A single AP can't be split any further
+        mock.respond(413, "");
+        let result = create_aps(&client, API, MSP, &auth(), &[ap("4")], 3, None).await.unwrap();
+        let error = result.chunks[0].error.as_ref().unwrap();
+        assert!(matches!(error, MigrationError::PayloadTooLarge { .. }) && error.to_string().contains("split it into smaller requests"));
+    }
+
+    #[tokio::test]
+    async fn patch_tenant_sends_only_the_partial() {
+        let mock = Arc::new(MockBackend::default());
+        let client = client_with(&mock);
+
+        let Err(MigrationError::Validation { problems }) = update_tenant(&client, API, "t1", &auth(), &json!({ "id": "x", "name": "New" }), None).await else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(problems[0].field, "id");
+        assert!(update_tenant(&client, API, "t1", &auth(), &json!({}), None).await.is_err());
+        assert!(mock.requests().is_empty());
+
+        update_tenant(&client, API, "t1", &auth(), &json!({ "name": "New" }), Some("\"v2\"")).await.unwrap();
+        let request = &mock.requests()[0];
+        assert_eq!((request.method.as_str(), request.url.as_str()), ("PATCH", format!("{}/mspCustomers/t1", API).as_str()));
+        assert_eq!(request.body, Some(json!({ "name": "New" })));
+        assert_eq!(request.headers["if-match"], "\"v2\"");
+    }
+
+    #[tokio::test]
+    async fn retried_create_resends_the_same_body() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(503, "").respond(201, r#"{"id":"t-9"}"#);
+        let client = client_with(&mock);
+
+        create_tenant(&client, API, MSP, &auth(), tenant(), false, None, Some("key-1")).await.unwrap();
+
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 2);
+        for request in &requests {
+            assert_eq!(request.body, Some(tenant()));
+            assert_eq!(request.headers["content-type"], "application/json");
+            assert_eq!(request.headers["idempotency-key"], "key-1");
+        }
+    }
+
+    #[tokio::test]
+    async fn created_tenant_location_is_resolved_and_fetched() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond_with_headers(201, &[("Location", "/mspCustomers/t-9")], r#"{"id":"t-9"}"#);
+        let client = client_with(&mock);
+
+        let created = create_tenant(&client, API, MSP, &auth(), tenant(), false, None, None).await.unwrap();
+        let location = created.location.unwrap();
+        assert_eq!(location, format!("{}/mspCustomers/t-9", API));
+
+        mock.respond(200, r#"{"id":"t-9","status":"ACTIVE"}"#);
+        let resource = fetch_location(&client, API, Some(&location), &auth()).await.unwrap();
+        assert_eq!(resource.unwrap()["status"], "ACTIVE");
+        assert!(fetch_location(&client, API, Some("https://elsewhere.test/t-9"), &auth()).await.unwrap().is_none());
+        assert_eq!(mock.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn put_venues_with_run_id_skips_confirmed_venues() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(200, r#"{"id":"new-1"}"#).respond(400, "{}");
+        let client = client_with(&mock);
+        let confirmed = ConfirmedKeys::default();
+        let venues = [json!({ "id": "v1", "name": "HQ" }), json!({ "id": "v2", "name": "Depot" })];
+
+        let first = create_venues(&client, API, MSP, &auth(), &venues, Some(("run-1", &confirmed))).await;
+        assert_eq!(first.items.iter().map(|r| r.success).collect::<Vec<_>>(), [true, false]);
+        assert_eq!((first.summary.succeeded, first.summary.failed), (1, 1));
+        assert!(!first.summary.failures[0].retryable);
+        assert_eq!(mock.requests()[0].headers["idempotency-key"], "run-1:venue:v1");
+
+        let retry = create_venues(&client, API, MSP, &auth(), &venues, Some(("run-1", &confirmed))).await;
+        assert!(retry.items[0].skipped && !retry.items[1].skipped);
+        assert_eq!(retry.summary.attempted, 1);
+        let requests = mock.requests();
+        assert_eq!(requests.len(), 3);
+        assert_eq!(requests[2].headers["idempotency-key"], "run-1:venue:v2");
+    }
+}
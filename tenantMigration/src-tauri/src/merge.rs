@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::State;
+
+use crate::auth::Auth;
+use crate::client::AppClient;
+use crate::error::MigrationError;
+use crate::migration::{self, ItemResult};
+use crate::query::{self, Resource};
+use crate::request_id;
+use crate::state::AppState;
+
+// What merge_tenants does with a venue whose key is already taken, by a venue of an
+// earlier source or one the target already has
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MergeStrategy {
+    // Keep the first one; the duplicate isn't sent
+    #[default]
+    Skip,
+    // Send the duplicate as well, with the source tenant's name appended to its name
+    Rename,
+    // The later one wins; a venue already on the target is updated in place
+    Overwrite,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MergeCollision {
+    pub key: String,
+    pub source_id: String,
+    // Source tenant id of the venue it collided with, or "target" for an existing venue
+    pub existing: String,
+    pub resolution: String,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MergeReport {
+    pub created: Vec<ItemResult>,
+    // Target venues replaced with Overwrite
+    pub updated: Vec<ItemResult>,
+    pub collisions: Vec<MergeCollision>,
+}
+
+// Consolidates several source tenants into one target: fetches every source's venues,
+// deduplicates them by `dedup_field` (default "name", compared case-insensitively) and
+// creates the union on the target. Venues already on the target count as taken, so
+// running the merge twice doesn't duplicate anything with Skip. Like put_venues, a
+// failed create doesn't stop the rest.
+#[tauri::command]
+pub async fn merge_tenants(
+    state: State<'_, AppState>,
+    api_url: Option<String>,
+    source_ids: Vec<String>,
+    target_id: String,
+    auth: Auth,
+    strategy: Option<MergeStrategy>,
+    dedup_field: Option<String>,
+) -> Result<MergeReport, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let source_ids: Vec<String> = source_ids.iter().map(|id| id.trim().to_string()).filter(|id| !id.is_empty()).collect();
+    let target_id = target_id.trim();
+    if source_ids.is_empty() {
+        return Err(MigrationError::invalid_input("source_ids must not be empty"));
+    }
+    if target_id.is_empty() {
+        return Err(MigrationError::invalid_input("target_id must not be empty"));
+    }
+    if source_ids.iter().any(|id| id == target_id) {
+        return Err(MigrationError::invalid_input("target_id must not be one of the source_ids"));
+    }
+    let field = dedup_field.as_deref().map(str::trim).filter(|f| !f.is_empty()).unwrap_or("name");
+
+    let api_url = config.api_url_or(api_url.as_deref())?;
+    let merge = merge(client, &api_url, &source_ids, target_id, &auth, strategy.unwrap_or_default(), field);
+    request_id::scope(request_id::new(), merge).await
+}
+
+struct Source {
+    id: String,
+    name: String,
+    venues: Vec<Value>,
+}
+
+async fn merge(
+    client: &AppClient,
+    api_url: &str,
+    source_ids: &[String],
+    target_id: &str,
+    auth: &Auth,
+    strategy: MergeStrategy,
+    field: &str,
+) -> Result<MergeReport, MigrationError> {
+    let mut sources = Vec::with_capacity(source_ids.len());
+    for id in source_ids {
+        let tenant = query::fetch_tenant(client, api_url, auth, id).await?;
+        let venues = query::fetch_resource(client, api_url, auth, &query::scoped_tenant_id(&tenant, id), Resource::Venues).await?;
+        sources.push(Source { id: id.clone(), name: migration::record_label(&tenant), venues });
+    }
+    let target = query::fetch_tenant(client, api_url, auth, target_id).await?;
+    let scoped_target = query::scoped_tenant_id(&target, target_id);
+    let existing = query::fetch_resource(client, api_url, auth, &scoped_target, Resource::Venues).await?;
+
+    let (writes, collisions) = resolve(&existing, &sources, field, strategy);
+    tracing::info!("Merging {} venues from {} tenants into {} ({} collisions)", writes.len(), sources.len(), target_id, collisions.len());
+
+    let mut report = MergeReport { created: Vec::new(), updated: Vec::new(), collisions };
+    for write in writes {
+        let payload = migration::venue_create_payload(&write.venue);
+        match write.target_venue_id {
+            Some(id) => {
+                let url = format!("{}/venues/{}", api_url, id);
+                let result = client.send_json(Method::PUT, &url, auth, Some(&scoped_target), Some(&payload)).await;
+                report.updated.push(ItemResult::from_result(&write.venue, result));
+            }
+            None => {
+                let url = format!("{}/venues", api_url);
+                let result = client.send_json(Method::POST, &url, auth, Some(&scoped_target), Some(&payload)).await;
+                report.created.push(ItemResult::from_result(&write.venue, result));
+            }
+        }
+    }
+    Ok(report)
+}
+
+struct VenueWrite {
+    venue: Value,
+    // Set when Overwrite replaces a venue the target already has
+    target_venue_id: Option<String>,
+}
+
+// Where the venue holding a key came from
+enum Holder {
+    Target(Option<String>),
+    // Index into the writes
+    Source(usize, String),
+}
+
+fn dedup_key(venue: &Value, field: &str) -> Option<String> {
+    match venue.get(field)? {
+        Value::String(s) => Some(s.trim().to_lowercase()).filter(|s| !s.is_empty()),
+        Value::Null => None,
+        other => Some(other.to_string()),
+    }
+}
+
+// Venues without the dedup field can't collide and are always sent
+fn resolve(existing: &[Value], sources: &[Source], field: &str, strategy: MergeStrategy) -> (Vec<VenueWrite>, Vec<MergeCollision>) {
+    let mut holders: HashMap<String, Holder> = HashMap::new();
+    for venue in existing {
+        if let Some(key) = dedup_key(venue, field) {
+            holders.insert(key, Holder::Target(venue.get("id").and_then(Value::as_str).map(str::to_string)));
+        }
+    }
+
+    let mut writes: Vec<VenueWrite> = Vec::new();
+    let mut collisions = Vec::new();
+    for source in sources {
+        for venue in &source.venues {
+            let Some(key) = dedup_key(venue, field) else {
+                writes.push(VenueWrite { venue: venue.clone(), target_venue_id: None });
+                continue;
+            };
+            let Some(holder) = holders.get(&key) else {
+                holders.insert(key, Holder::Source(writes.len(), source.id.clone()));
+                writes.push(VenueWrite { venue: venue.clone(), target_venue_id: None });
+                continue;
+            };
+
+            let existing = match holder {
+                Holder::Target(_) => "target".to_string(),
+                Holder::Source(_, id) => id.clone(),
+            };
+            let resolution = match strategy {
+                MergeStrategy::Skip => "skipped".to_string(),
+                MergeStrategy::Rename => {
+                    let base = venue.get("name").and_then(Value::as_str).map(str::trim).filter(|n| !n.is_empty());
+                    let base = base.map(str::to_string).unwrap_or_else(|| migration::record_label(venue));
+                    let mut name = format!("{} ({})", base, source.name);
+                    let mut n = 2;
+                    // Names must also stay unique on the target when they are the key
+                    while field == "name" && holders.contains_key(&name.to_lowercase()) {
+                        name = format!("{} ({} {})", base, source.name, n);
+                        n += 1;
+                    }
+                    let mut renamed = venue.clone();
+                    renamed["name"] = Value::from(name.clone());
+                    if let Some(key) = dedup_key(&renamed, field) {
+                        holders.insert(key, Holder::Source(writes.len(), source.id.clone()));
+                    }
+                    writes.push(VenueWrite { venue: renamed, target_venue_id: None });
+                    format!("renamed to {}", name)
+                }
+                MergeStrategy::Overwrite => {
+                    match holder {
+                        Holder::Target(id) => {
+                            let target_venue_id = id.clone();
+                            holders.insert(key.clone(), Holder::Source(writes.len(), source.id.clone()));
+                            writes.push(VenueWrite { venue: venue.clone(), target_venue_id });
+                        }
+                        Holder::Source(index, _) => {
+                            let index = *index;
+                            writes[index].venue = venue.clone();
+                            holders.insert(key.clone(), Holder::Source(index, source.id.clone()));
+                        }
+                    }
+                    "overwritten".to_string()
+                }
+            };
+            collisions.push(MergeCollision { key, source_id: source.id.clone(), existing, resolution });
+        }
+    }
+    (writes, collisions)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn sources() -> Vec<Source> {
+        vec![
+            Source { id: "a".into(), name: "North".into(), venues: vec![json!({ "id": "a1", "name": "HQ" }), json!({ "id": "a2", "name": "Depot" })] },
+            Source { id: "b".into(), name: "South".into(), venues: vec![json!({ "id": "b1", "name": "hq " }), json!({ "id": "b2", "name": "Store" })] },
+        ]
+    }
+
+    #[test]
+    fn collisions_follow_the_strategy() {
+        let existing = [json!({ "id": "t1", "name": "Store" })];
+        let names = |writes: &[VenueWrite]| writes.iter().map(|w| migration::record_label(&w.venue)).collect::<Vec<_>>();
+
+        let (writes, collisions) = resolve(&existing, &sources(), "name", MergeStrategy::Skip);
+        assert_eq!(names(&writes), ["HQ", "Depot"]);
+        assert_eq!(collisions.iter().map(|c| c.existing.as_str()).collect::<Vec<_>>(), ["a", "target"]);
+
+        let (writes, _) = resolve(&existing, &sources(), "name", MergeStrategy::Rename);
+        assert_eq!(names(&writes), ["HQ", "Depot", "hq (South)", "Store (South)"]);
+
+        let (writes, collisions) = resolve(&existing, &sources(), "name", MergeStrategy::Overwrite);
+        assert_eq!(names(&writes), ["hq ", "Depot", "Store"]);
+        assert_eq!(writes[2].target_venue_id.as_deref(), Some("t1"));
+        assert!(collisions.iter().all(|c| c.resolution == "overwritten"));
+    }
+}
@@ -0,0 +1,1480 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+
+use crate::apiurl;
+use crate::audit::{self, AuditLog};
+use crate::auth::Auth;
+use crate::checkpoint::{self, Checkpoint, CheckpointHeader, PushedEntry};
+use crate::client::AppClient;
+use crate::config::ConfigStore;
+use crate::error::MigrationError;
+use crate::plan::{self, MigrationPlan};
+use crate::preflight;
+use crate::query::Resource;
+use crate::request_id;
+use crate::snapshot::{self, TenantSnapshot};
+use crate::state::AppState;
+use crate::timing::{self, TimingCollector, Timings};
+use crate::transform::{self, TransformRule};
+use crate::validation::{self, ValidationProblem};
+
+// Fields the API fills in itself. They come back from the queries but must not be
+// sent when creating the resource on the target.
+const TENANT_SERVER_FIELDS: &[&str] = &["id", "tenant_id", "createdDate", "updatedDate"];
+const VENUE_SERVER_FIELDS: &[&str] = &[
+    "id", "check-all", "networks", "aggregatedApStatus", "switches", "switchClients",
+    "clients", "apWiredClients", "edges", "iotControllers", "status", "isEnforced",
+];
+const NETWORK_SERVER_FIELDS: &[&str] = &["id", "venueApGroups", "apSerialNumbers", "apCount", "clientCount", "isEnforced"];
+const AP_SERVER_FIELDS: &[&str] = &[
+    "venueId", "networkStatus", "lanPortStatuses", "radioStatuses", "afcStatus", "cellularStatus", "firmwareVersion",
+];
+const SWITCH_SERVER_FIELDS: &[&str] = &["venueId", "deviceStatus", "firmwareVersion", "ipAddress", "clientCount", "uptime"];
+
+// Called with (stage, processed, total) as the fetch and push phases advance
+pub type ProgressFn<'a> = &'a (dyn Fn(&'static str, usize, usize) + Send + Sync);
+
+// ETA is the average of the last ETA_WINDOW per-item durations, and only given once
+// ETA_MIN_ITEMS of the stage are timed - the first creates include connection setup
+// and would make the estimate swing wildly
+const ETA_WINDOW: usize = 20;
+const ETA_MIN_ITEMS: usize = 3;
+
+// How long closing the app waits for a cancelled run to stop: one in-flight request
+// plus writing its audit and checkpoint entries
+pub const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+// Part of AppState: holds the cancellation token of the run in progress. Each run
+// gets a fresh token from begin(); cancel_migration cancels the latest one.
+// Runs are counted while their ActiveRun is alive, so app exit can wait for them.
+#[derive(Default)]
+pub struct MigrationControl {
+    current: Mutex<CancellationToken>,
+    active: Arc<ActiveCount>,
+    shutting_down: AtomicBool,
+}
+
+#[derive(Default)]
+struct ActiveCount {
+    runs: AtomicUsize,
+    idle: Notify,
+}
+
+// A started run; derefs to its cancellation token
+pub struct ActiveRun {
+    token: CancellationToken,
+    active: Arc<ActiveCount>,
+}
+
+impl Deref for ActiveRun {
+    type Target = CancellationToken;
+
+    fn deref(&self) -> &CancellationToken {
+        &self.token
+    }
+}
+
+impl Drop for ActiveRun {
+    fn drop(&mut self) {
+        if self.active.runs.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.active.idle.notify_waiters();
+        }
+    }
+}
+
+impl MigrationControl {
+    pub fn begin(&self) -> ActiveRun {
+        let token = CancellationToken::new();
+        *self.current.lock().unwrap_or_else(PoisonError::into_inner) = token.clone();
+        self.active.runs.fetch_add(1, Ordering::SeqCst);
+        ActiveRun { token, active: self.active.clone() }
+    }
+
+    pub fn cancel(&self) {
+        self.current.lock().unwrap_or_else(PoisonError::into_inner).cancel();
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.active.runs.load(Ordering::SeqCst) > 0
+    }
+
+    // True only for the first call, so the exit that follows the grace period
+    // isn't held up again
+    pub fn start_shutdown(&self) -> bool {
+        !self.shutting_down.swap(true, Ordering::SeqCst)
+    }
+
+    // Waits until no run is active, up to `grace`; false if one is still going
+    pub async fn wait_idle(&self, grace: Duration) -> bool {
+        let wait = async {
+            loop {
+                let idle = self.active.idle.notified();
+                if !self.is_running() {
+                    return;
+                }
+                idle.await;
+            }
+        };
+        tokio::time::timeout(grace, wait).await.is_ok()
+    }
+}
+
+// Closing the window or quitting mid-run: cancel the run and give it SHUTDOWN_GRACE
+// to stop after its current request, so what it created is in the audit log and
+// checkpoint (and "migration-error" lists it) before the process goes away
+pub fn on_exit_requested<R: tauri::Runtime>(app: &AppHandle<R>, api: &tauri::ExitRequestApi) {
+    let control = &app.state::<AppState>().inner().control;
+    if !control.is_running() || !control.start_shutdown() {
+        return;
+    }
+    tracing::info!("Exit requested during a migration, cancelling it");
+    api.prevent_exit();
+    control.cancel();
+
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let control = &app.state::<AppState>().inner().control;
+        if !control.wait_idle(SHUTDOWN_GRACE).await {
+            tracing::warn!("Migration still running after {:?}, exiting anyway", SHUTDOWN_GRACE);
+        }
+        app.exit(0);
+    });
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResourceKind {
+    Tenant,
+    Venue,
+    WifiNetwork,
+    Ap,
+    Switch,
+}
+
+// A resource created on the target, kept so partial runs can be cleaned up
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ResourceRef {
+    pub kind: ResourceKind,
+    pub id: String,
+    pub name: String,
+    // APs and switches only: the target venue they were added to, needed to delete them again
+    #[serde(default)]
+    pub venue_id: Option<String>,
+    // id (or serial, or name) of the source record it was copied from. Missing in
+    // checkpoints written before it was recorded.
+    #[serde(default)]
+    pub source_id: Option<String>,
+}
+
+// Where to read from / write to. Source and target can live in different regions
+// and belong to different MSP accounts, so each carries its own credentials.
+// `token` is still accepted for auth.
+#[derive(Debug, Deserialize)]
+pub struct ApiEndpoint {
+    // Left out, the saved api_url or region is used (see ConfigStore::api_url_or)
+    #[serde(default)]
+    pub api_url: String,
+    #[serde(alias = "token")]
+    pub auth: Auth,
+}
+
+impl ApiEndpoint {
+    // With a missing api_url taken from the saved config, as for every command
+    fn resolve(self, config: &ConfigStore) -> Result<Self, MigrationError> {
+        Ok(Self { api_url: config.api_url_or(Some(&self.api_url))?, ..self })
+    }
+}
+
+// Where migrate_tenant reads the tenant from: the API (`source`), or a snapshot file
+// written by export_tenant (`source_file`) for demos and tests without API access
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceKind {
+    #[default]
+    Api,
+    File,
+}
+
+enum SnapshotSource<'a> {
+    Api(&'a ApiEndpoint),
+    File(&'a Path),
+}
+
+// Which resource types migrate_tenant copies besides the tenant itself. Missing
+// fields (or a missing selection) mean true, so the default is everything.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default)]
+pub struct ResourceSelection {
+    pub venues: bool,
+    pub wifi_networks: bool,
+    pub aps: bool,
+    pub switches: bool,
+}
+
+impl Default for ResourceSelection {
+    fn default() -> Self {
+        Self { venues: true, wifi_networks: true, aps: true, switches: true }
+    }
+}
+
+impl ResourceSelection {
+    // APs and switches are added to venues, so they can't be copied without them
+    pub fn validate(&self) -> Result<(), MigrationError> {
+        let mut problems = Vec::new();
+        if self.aps && !self.venues {
+            problems.push(ValidationProblem::new("aps", "requires venues to be selected"));
+        }
+        if self.switches && !self.venues {
+            problems.push(ValidationProblem::new("switches", "requires venues to be selected"));
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(MigrationError::Validation { problems })
+        }
+    }
+
+    pub fn includes(&self, resource: Resource) -> bool {
+        match resource {
+            Resource::Venues => self.venues,
+            Resource::WifiNetworks => self.wifi_networks,
+            Resource::Aps => self.aps,
+            Resource::Switches => self.switches,
+        }
+    }
+}
+
+// Payload of the "migration-progress" event. `phase` is "fetch" (reading the source),
+// "transform" (building the tenant payload) or "push" (writing to the target);
+// `stage` is the step within it, e.g. "push_aps".
+#[derive(Clone, Serialize)]
+pub struct MigrationProgress {
+    pub phase: &'static str,
+    pub stage: &'static str,
+    pub processed: usize,
+    pub total: usize,
+    // Items the whole phase will process: every record to push, 1 for transform.
+    // None for fetch, whose size is only known once it's done.
+    pub phase_total: Option<usize>,
+    // Estimated seconds left in this stage; None until enough items are timed
+    pub eta_seconds: Option<u64>,
+}
+
+// Per-stage timing behind MigrationProgress::eta_seconds. Interior mutability because
+// progress callbacks are shared Fn references.
+#[derive(Default)]
+struct EtaTracker {
+    state: Mutex<EtaState>,
+}
+
+#[derive(Default)]
+struct EtaState {
+    stage: &'static str,
+    // processed count and time of the previous update in this stage
+    last: Option<(usize, Instant)>,
+    recent: VecDeque<Duration>,
+}
+
+impl EtaTracker {
+    fn update(&self, stage: &'static str, processed: usize, total: usize, now: Instant) -> Option<u64> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if state.stage != stage {
+            *state = EtaState { stage, last: Some((processed, now)), recent: VecDeque::new() };
+            return None;
+        }
+
+        if let Some((previous, at)) = state.last {
+            if processed > previous {
+                let per_item = now.saturating_duration_since(at) / (processed - previous) as u32;
+                for _ in previous..processed {
+                    if state.recent.len() == ETA_WINDOW {
+                        state.recent.pop_front();
+                    }
+                    state.recent.push_back(per_item);
+                }
+            }
+        }
+        state.last = Some((processed, now));
+
+        if state.recent.len() < ETA_MIN_ITEMS {
+            return None;
+        }
+        let average = state.recent.iter().sum::<Duration>() / state.recent.len() as u32;
+        let remaining = total.saturating_sub(processed) as u32;
+        Some((average * remaining).as_secs_f64().round() as u64)
+    }
+}
+
+// Payload of the "migration-started" event
+#[derive(Clone, Serialize)]
+pub struct MigrationStarted {
+    pub tenant_id: String,
+    pub audit_log: String,
+    // x-request-id sent with every call of the run
+    pub request_id: String,
+    // Checkpoint to pass to resume_migration if the run doesn't finish
+    pub checkpoint: String,
+}
+
+// Returned by migrate_tenant and sent with the "migration-complete" event
+#[derive(Clone, Debug, Serialize)]
+pub struct MigrationReport {
+    pub source_tenant_id: String,
+    pub target_tenant_id: String,
+    pub venues: usize,
+    pub wifi_networks: usize,
+    pub aps: usize,
+    pub switches: usize,
+    // JSON Lines file with every API call the run made
+    pub audit_log: Option<String>,
+    pub request_id: Option<String>,
+    // Time spent per endpoint, slowest first, e.g. to tell slow AP queries from slow venue POSTs
+    pub timings: Vec<Timings>,
+    // Dry runs only: what would have been sent to the target. Nothing was created and
+    // target_tenant_id is empty.
+    pub plan: Option<MigrationPlan>,
+    // Everything the run created on the target, tenant first; see export_report_csv
+    pub created: Vec<ResourceRef>,
+}
+
+// What push_snapshot managed to create. When a child resource fails the push stops
+// there and `failed` says which one, so the counts show how far it got.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct PushSummary {
+    pub target_tenant_id: String,
+    pub venues: usize,
+    pub wifi_networks: usize,
+    pub aps: usize,
+    pub switches: usize,
+    pub created: Vec<ResourceRef>,
+    pub failed: Option<PushFailure>,
+    // Source keys of the records created so far, skipped when a run is resumed
+    #[serde(skip)]
+    done: HashSet<(ResourceKind, String)>,
+    // Source venue id -> target venue id, needed to re-home the APs and switches
+    #[serde(skip)]
+    venue_ids: HashMap<String, String>,
+}
+
+// Outcome for one record of a bulk create (put_venues, ...). Bulk commands keep
+// going after a failure, so each record gets its own entry.
+#[derive(Clone, Debug, Serialize)]
+pub struct ItemResult {
+    pub name: String,
+    pub success: bool,
+    pub id: Option<String>,
+    pub error: Option<MigrationError>,
+    // Not sent again: an earlier call with the same run_id already created it
+    pub skipped: bool,
+}
+
+impl ItemResult {
+    pub fn skipped(record: &Value) -> Self {
+        ItemResult { name: record_label(record), success: true, id: None, error: None, skipped: true }
+    }
+
+    pub fn from_result(record: &Value, result: Result<Value, MigrationError>) -> Self {
+        let name = record_label(record);
+        match result {
+            Ok(created) => ItemResult { name, success: true, id: created_id(&created), error: None, skipped: false },
+            Err(error) => {
+                tracing::warn!("Failed to create {}: {}", name, error);
+                ItemResult { name, success: false, id: None, error: Some(error), skipped: false }
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct PushFailure {
+    pub resource: ResourceKind,
+    pub item: String,
+    pub error: MigrationError,
+}
+
+// One resource type fetch_snapshot couldn't read
+#[derive(Clone, Debug, Serialize)]
+pub struct FetchFailure {
+    pub resource: ResourceKind,
+    pub error: MigrationError,
+}
+
+// Copies a tenant with its venues, wifi networks, APs and switches from `source` to `target`,
+// emitting "migration-progress" after every step and "migration-complete" or
+// "migration-error" at the end. `tenant_data` overrides the payload used to create
+// the customer on the target (the frontend collects missing required fields into it);
+// without it the source tenant record is reused minus server-assigned fields.
+// `selection` limits which child resources are fetched and pushed (all by default);
+// one that doesn't hold together, like APs without venues, fails with Validation.
+// `transforms` rewrite each record (tenant included) before it's sent, see transform.rs.
+//
+// The run can be stopped with cancel_migration; it then fails with
+// MigrationError::Cancelled listing what was already created on the target.
+// `max_duration_secs` caps the whole run the same way: once it has passed, the run
+// stops before the next page it reads or resource it creates and fails with
+// DeadlineExceeded instead.
+//
+// With `target_tenant_id` the resources are copied into that existing tenant instead
+// of a new one, and `tenant_data` is unused. The run refuses a target that already
+// has resources (see preflight_target) with TargetNotEmpty unless `force` is set; a
+// dry run lists that in the plan's problems instead.
+//
+// Every API call is appended to an audit log under the app data directory. Its path
+// is in the report and in the "migration-started" event, so it's known even when
+// the run fails. All calls share one x-request-id, also in both places, so a run can
+// be found in R1's server logs.
+//
+// Progress is checkpointed after every created resource (see checkpoint.rs); the
+// checkpoint path is in "migration-started" too and a run that didn't finish can be
+// continued with resume_migration. The checkpoint is removed once the run succeeds.
+//
+// With source_kind "file" the fetch phase reads `source_file` (an export_tenant
+// snapshot) instead of the API and `source` may be left out. With dry_run nothing is
+// written to the target: the report carries the plan_migration operations instead.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn migrate_tenant(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    source: Option<ApiEndpoint>,
+    target: ApiEndpoint,
+    tenant_id: String,
+    tenant_data: Option<Value>,
+    selection: Option<ResourceSelection>,
+    transforms: Option<Vec<TransformRule>>,
+    source_kind: Option<SourceKind>,
+    source_file: Option<String>,
+    dry_run: Option<bool>,
+    max_duration_secs: Option<u64>,
+    target_tenant_id: Option<String>,
+    force: Option<bool>,
+) -> Result<MigrationReport, MigrationError> {
+    let AppState { client, config, control } = state.inner();
+    let fail = |e: MigrationError| {
+        let _ = app.emit("migration-error", &e);
+        e
+    };
+    let source = source.map(|source| source.resolve(config)).transpose().map_err(fail)?;
+    let target = target.resolve(config).map_err(fail)?;
+    let selection = selection.unwrap_or_default();
+    let transforms = transforms.unwrap_or_default();
+    let source_file = source_file.map(PathBuf::from);
+    let prepared = match (source_kind.unwrap_or_default(), &source, &source_file) {
+        (SourceKind::Api, Some(source), _) => Ok(SnapshotSource::Api(source)),
+        (SourceKind::Api, None, _) => Err(MigrationError::invalid_input("source is required when source_kind is \"api\"")),
+        (SourceKind::File, _, Some(path)) => Ok(SnapshotSource::File(path)),
+        (SourceKind::File, _, None) => Err(MigrationError::invalid_input("source_file is required when source_kind is \"file\"")),
+    }
+    .and_then(|snapshot_source| {
+        if max_duration_secs == Some(0) {
+            return Err(MigrationError::invalid_input("max_duration_secs must be at least 1"));
+        }
+        selection.validate()?;
+        transform::validate(&transforms)?;
+        Ok((snapshot_source, checkpoint::new_path(&app, &tenant_id)?))
+    });
+    let (snapshot_source, checkpoint_path) = prepared.map_err(fail)?;
+
+    let cancel = control.begin();
+    // The deadline stops the run through a child token, so cancel_migration can still
+    // be told apart from it
+    let run_cancel = cancel.child_token();
+    let existing_target = target_tenant_id.as_deref().map(str::trim).filter(|id| !id.is_empty()).map(|id| (id, force.unwrap_or(false)));
+    let emit = progress_events(&app);
+    let run = run_migration(
+        &emit,
+        client,
+        &run_cancel,
+        snapshot_source,
+        &target,
+        &tenant_id,
+        tenant_data,
+        selection,
+        &transforms,
+        &checkpoint_path,
+        dry_run.unwrap_or(false),
+        existing_target,
+    );
+    let run = within_deadline(&cancel, &run_cancel, max_duration_secs.map(Duration::from_secs), run);
+    observe(&app, &format!("migration-{}", tenant_id), &tenant_id, &checkpoint_path, run).await
+}
+
+// Runs `run`, which checks `run_cancel` (a child of the run's `cancel`). Once `limit`
+// has passed only `run_cancel` is cancelled, so the run stops at its next check and
+// that stop is reported as DeadlineExceeded. A cancel_migration that came first stays
+// Cancelled, even if the run only stops after the limit.
+async fn within_deadline<T>(
+    cancel: &CancellationToken,
+    run_cancel: &CancellationToken,
+    limit: Option<Duration>,
+    run: impl Future<Output = Result<T, MigrationError>>,
+) -> Result<T, MigrationError> {
+    let Some(limit) = limit else {
+        return run.await;
+    };
+    tokio::pin!(run);
+    tokio::select! {
+        result = &mut run => return result,
+        _ = tokio::time::sleep(limit) => {}
+    }
+    if cancel.is_cancelled() {
+        return run.await;
+    }
+    tracing::warn!("Migration ran past its {:?} limit, stopping before the next resource", limit);
+    run_cancel.cancel();
+    match run.await {
+        Err(MigrationError::Cancelled { created }) => Err(MigrationError::DeadlineExceeded { limit_secs: limit.as_secs(), created }),
+        other => other,
+    }
+}
+
+// Continues a migration that stopped part-way from the checkpoint it left behind,
+// against the same target. Resources the checkpoint lists as created are skipped;
+// the rest are pushed from the checkpoint's snapshot, so the source isn't read again.
+// `token` is for the target, checkpoints don't store tokens. Events, audit log and
+// report are the same as for migrate_tenant; a checkpoint from another format
+// version fails with InvalidInput.
+#[tauri::command]
+pub async fn resume_migration(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    checkpoint_path: String,
+    auth: Auth,
+) -> Result<MigrationReport, MigrationError> {
+    let AppState { client, control, .. } = state.inner();
+    let path = PathBuf::from(&checkpoint_path);
+    let checkpoint = match Checkpoint::open(&path) {
+        Ok(checkpoint) => checkpoint,
+        Err(e) => {
+            let _ = app.emit("migration-error", &e);
+            return Err(e);
+        }
+    };
+    let tenant_id = checkpoint.header().tenant_id.clone();
+    tracing::info!("Resuming migration of tenant {} from {} ({} resources done)", tenant_id, checkpoint_path, checkpoint.pushed().len());
+
+    let cancel = control.begin();
+    let emit = progress_events(&app);
+    let run = resume_run(&emit, client, &cancel, &auth, checkpoint);
+    observe(&app, &format!("resume-{}", tenant_id), &tenant_id, &path, run).await
+}
+
+// Event, audit log, request id and timing plumbing shared by migrate_tenant and
+// resume_migration around the actual run
+async fn observe(
+    app: &AppHandle,
+    label: &str,
+    tenant_id: &str,
+    checkpoint_path: &Path,
+    run: impl Future<Output = Result<MigrationReport, MigrationError>>,
+) -> Result<MigrationReport, MigrationError> {
+    let audit_log = match AuditLog::create(app, label) {
+        Ok(log) => Arc::new(log),
+        Err(e) => {
+            let _ = app.emit("migration-error", &e);
+            return Err(e);
+        }
+    };
+    let audit_path = audit_log.path().display().to_string();
+    let request_id = request_id::new();
+    let _ = app.emit(
+        "migration-started",
+        MigrationStarted {
+            tenant_id: tenant_id.to_string(),
+            audit_log: audit_path.clone(),
+            request_id: request_id.clone(),
+            checkpoint: checkpoint_path.display().to_string(),
+        },
+    );
+
+    let timings = Arc::new(TimingCollector::default());
+    let result = request_id::scope(request_id.clone(), timing::scope(timings.clone(), audit::scope(audit_log, run)))
+        .await
+        .map(|report| MigrationReport { audit_log: Some(audit_path), request_id: Some(request_id), timings: timings.summary(), ..report });
+
+    match &result {
+        Ok(report) => {
+            let _ = app.emit("migration-complete", report);
+        }
+        Err(e) => {
+            let _ = app.emit("migration-error", e);
+        }
+    }
+
+    result
+}
+
+// One tenant's line in a BatchReport
+#[derive(Clone, Debug, Serialize)]
+pub struct TenantOutcome {
+    pub tenant_id: String,
+    pub target_tenant_id: Option<String>,
+    pub error: Option<MigrationError>,
+}
+
+// Returned by migrate_tenants; also sent with "batch-complete"
+#[derive(Clone, Debug, Serialize)]
+pub struct BatchReport {
+    pub succeeded: usize,
+    pub failed: usize,
+    // Same order as the tenants passed in; tenants not reached after a cancel are left out
+    pub tenants: Vec<TenantOutcome>,
+}
+
+// Payload of the "batch-progress" event, sent after each tenant
+#[derive(Clone, Serialize)]
+pub struct BatchProgress {
+    pub index: usize,
+    pub total: usize,
+    pub outcome: TenantOutcome,
+}
+
+// One tenant of a migrate_tenants batch. GET /tenants doesn't always return
+// everything a create needs (admin contact, service dates), so an entry can bring
+// the create payload as `tenant_data`, or a `target_tenant_id` to write into with
+// `force` as for migrate_tenant. Without either the payload is built from the
+// source record, as migrate_tenant does, and the tenant fails on its own if that
+// record is incomplete.
+#[derive(Debug, Deserialize)]
+pub struct BatchTenant {
+    pub tenant_id: String,
+    #[serde(default)]
+    pub tenant_data: Option<Value>,
+    #[serde(default)]
+    pub target_tenant_id: Option<String>,
+    #[serde(default)]
+    pub force: bool,
+}
+
+// A bare tenant id is still accepted for an entry, as migrate_tenants first took
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum BatchEntry {
+    Id(String),
+    Tenant(BatchTenant),
+}
+
+impl From<BatchEntry> for BatchTenant {
+    fn from(entry: BatchEntry) -> Self {
+        match entry {
+            BatchEntry::Id(tenant_id) => BatchTenant { tenant_id, tenant_data: None, target_tenant_id: None, force: false },
+            BatchEntry::Tenant(tenant) => tenant,
+        }
+    }
+}
+
+impl BatchTenant {
+    fn existing_target(&self) -> Option<(&str, bool)> {
+        self.target_tenant_id.as_deref().map(str::trim).filter(|id| !id.is_empty()).map(|id| (id, self.force))
+    }
+}
+
+// Onboards several tenants by running migrate_tenant for each entry in turn, with
+// the same selection and transforms and each tenant's own payload or target. One
+// at a time so the per-tenant migration-* events stay readable. A failing tenant is
+// recorded and the next one started; cancel_migration stops the current tenant and
+// the rest of the batch.
+#[tauri::command]
+pub async fn migrate_tenants(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    source: ApiEndpoint,
+    target: ApiEndpoint,
+    tenants: Vec<BatchEntry>,
+    selection: Option<ResourceSelection>,
+    transforms: Option<Vec<TransformRule>>,
+) -> Result<BatchReport, MigrationError> {
+    let AppState { client, config, control } = state.inner();
+    let (source, target) = (source.resolve(config)?, target.resolve(config)?);
+    let selection = selection.unwrap_or_default();
+    let transforms = transforms.unwrap_or_default();
+    selection.validate()?;
+    transform::validate(&transforms)?;
+    let tenants: Vec<BatchTenant> = tenants.into_iter().map(BatchTenant::from).collect();
+    validate_batch(&tenants)?;
+
+    let cancel = control.begin();
+    let emit = progress_events(&app);
+    let (app, client, cancel, emit, source, target, transforms) = (&app, client, &cancel, &emit, &source, &target, &transforms[..]);
+    let run = move |tenant: BatchTenant| async move {
+        let path = checkpoint::new_path(app, &tenant.tenant_id)?;
+        let run = run_migration(emit, client, cancel, SnapshotSource::Api(source), target, &tenant.tenant_id, tenant.tenant_data.clone(), selection, transforms, &path, false, tenant.existing_target());
+        observe(app, &format!("migration-{}", tenant.tenant_id), &tenant.tenant_id, &path, run).await
+    };
+    let report = run_batch(tenants, cancel, run, |progress| {
+        let _ = app.emit("batch-progress", progress);
+    })
+    .await;
+    let _ = app.emit("batch-complete", &report);
+    Ok(report)
+}
+
+// Every entry needs a tenant_id, checked before the first tenant starts
+fn validate_batch(tenants: &[BatchTenant]) -> Result<(), MigrationError> {
+    let problems: Vec<ValidationProblem> = tenants
+        .iter()
+        .enumerate()
+        .filter_map(|(index, tenant)| {
+            if tenant.tenant_id.trim().is_empty() {
+                Some(ValidationProblem::new(format!("tenants[{}].tenant_id", index), "must not be empty"))
+            } else {
+                None
+            }
+        })
+        .collect();
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(MigrationError::Validation { problems })
+    }
+}
+
+async fn run_batch<F, Fut>(tenants: Vec<BatchTenant>, cancel: &CancellationToken, mut run: F, on_progress: impl Fn(BatchProgress)) -> BatchReport
+where
+    F: FnMut(BatchTenant) -> Fut,
+    Fut: Future<Output = Result<MigrationReport, MigrationError>>,
+{
+    let total = tenants.len();
+    let mut outcomes = Vec::with_capacity(total);
+    for (index, tenant) in tenants.into_iter().enumerate() {
+        if cancel.is_cancelled() {
+            tracing::info!("Batch cancelled after {} of {} tenants", index, total);
+            break;
+        }
+
+        let tenant_id = tenant.tenant_id.clone();
+        let outcome = match run(tenant).await {
+            Ok(report) => TenantOutcome { tenant_id, target_tenant_id: Some(report.target_tenant_id), error: None },
+            Err(error) => {
+                tracing::warn!("Tenant {} failed, continuing with the batch: {}", tenant_id, error);
+                TenantOutcome { tenant_id, target_tenant_id: None, error: Some(error) }
+            }
+        };
+        on_progress(BatchProgress { index, total, outcome: outcome.clone() });
+        outcomes.push(outcome);
+    }
+
+    let failed = outcomes.iter().filter(|t| t.error.is_some()).count();
+    BatchReport { succeeded: outcomes.len() - failed, failed, tenants: outcomes }
+}
+
+#[tauri::command]
+pub fn cancel_migration(state: State<'_, AppState>) {
+    let AppState { control, .. } = state.inner();
+    tracing::info!("Cancellation requested");
+    control.cancel();
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_migration(
+    emit: ProgressSink<'_>,
+    client: &AppClient,
+    cancel: &CancellationToken,
+    source: SnapshotSource<'_>,
+    target: &ApiEndpoint,
+    tenant_id: &str,
+    tenant_data: Option<Value>,
+    selection: ResourceSelection,
+    transforms: &[TransformRule],
+    checkpoint_path: &Path,
+    dry_run: bool,
+    // Existing tenant to write into, and whether it may already have resources
+    existing_target: Option<(&str, bool)>,
+) -> Result<MigrationReport, MigrationError> {
+    if tenant_id.trim().is_empty() {
+        return Err(MigrationError::invalid_input("tenant_id must not be empty"));
+    }
+
+    let target_url = &apiurl::normalize(&target.api_url)?;
+    let eta = EtaTracker::default();
+    let on_fetch_progress = |stage, processed, total| emit_progress(emit, stage, processed, total, None, eta.update(stage, processed, total, Instant::now()));
+
+    // Fetch everything from the source first so nothing is created on the target
+    // if a read fails
+    let snapshot = match source {
+        SnapshotSource::Api(source) => {
+            let source_url = &apiurl::normalize(&source.api_url)?;
+            tracing::info!("Migrating tenant {} from {} to {}", tenant_id, source_url, target_url);
+            snapshot::fetch_snapshot(client, source_url, &source.auth, tenant_id, &selection, cancel, &on_fetch_progress).await?
+        }
+        SnapshotSource::File(path) => {
+            tracing::info!("Migrating tenant {} from snapshot {} to {}", tenant_id, path.display(), target_url);
+            on_fetch_progress("fetch_tenant", 0, 1);
+            let snapshot = snapshot::select(snapshot::read_snapshot(path).await?, &selection);
+            on_fetch_progress("fetch_tenant", 1, 1);
+            snapshot
+        }
+    };
+
+    emit_progress(emit, "transform_tenant", 0, 1, Some(1), None);
+    let tenant_payload = tenant_data.unwrap_or_else(|| tenant_create_payload(&snapshot.tenant));
+    let tenant_payload = transform::apply(transforms, ResourceKind::Tenant, tenant_payload);
+    // Not sent when writing into an existing tenant
+    if existing_target.is_none() {
+        validation::validate_tenant_data(&tenant_payload)?;
+    }
+    emit_progress(emit, "transform_tenant", 1, 1, Some(1), None);
+
+    let mut target_warning = None;
+    if let Some((target_id, force)) = existing_target {
+        let preflight = preflight::check(client, target_url, &target.auth, target_id).await?;
+        if dry_run {
+            target_warning = preflight.warning;
+        } else {
+            preflight::ensure_empty(preflight, force)?;
+        }
+    }
+
+    if dry_run {
+        let mut plan = plan::plan(&snapshot, tenant_payload, transforms, target_url, client.paths(target_url));
+        if existing_target.is_some() {
+            // The tenant already exists, so it isn't created
+            plan.operations.retain(|op| op.resource != ResourceKind::Tenant);
+            plan.problems.extend(target_warning);
+        }
+        tracing::info!("Dry run of tenant {}: {} operations planned", tenant_id, plan.operations.len());
+        return Ok(MigrationReport {
+            source_tenant_id: crate::query::scoped_tenant_id(&snapshot.tenant, tenant_id),
+            target_tenant_id: String::new(),
+            venues: plan.counts.venues,
+            wifi_networks: plan.counts.wifi_networks,
+            aps: plan.counts.aps,
+            switches: plan.counts.switches,
+            audit_log: None,
+            request_id: None,
+            timings: Vec::new(),
+            plan: Some(plan),
+            created: Vec::new(),
+        });
+    }
+
+    let push_total = Some(push_total(&snapshot));
+    let on_progress = |stage, processed, total| emit_progress(emit, stage, processed, total, push_total, eta.update(stage, processed, total, Instant::now()));
+
+    // The snapshot is written once next to the checkpoint so a resume doesn't need the source
+    let snapshot_file = checkpoint::snapshot_path(checkpoint_path);
+    tokio::fs::write(&snapshot_file, snapshot::to_json(&snapshot)?)
+        .await
+        .map_err(|e| MigrationError::io(&snapshot_file, e))?;
+    let header = CheckpointHeader {
+        version: checkpoint::CHECKPOINT_VERSION,
+        tenant_id: tenant_id.to_string(),
+        target_api_url: target_url.to_string(),
+        snapshot_file: snapshot_file.display().to_string(),
+        tenant_payload: tenant_payload.clone(),
+        transforms: transforms.to_vec(),
+        target_tenant_id: existing_target.map(|(id, _)| id.to_string()),
+    };
+    let checkpoint = Checkpoint::create(checkpoint_path, header)?;
+
+    let summary = push_snapshot(client, target_url, &target.auth, &snapshot, &tenant_payload, transforms, cancel, &on_progress, Some(&checkpoint)).await?;
+    finish(&snapshot, tenant_id, summary, checkpoint)
+}
+
+async fn resume_run(
+    emit: ProgressSink<'_>,
+    client: &AppClient,
+    cancel: &CancellationToken,
+    auth: &Auth,
+    checkpoint: Checkpoint,
+) -> Result<MigrationReport, MigrationError> {
+    let header = checkpoint.header().clone();
+    let snapshot = snapshot::read_snapshot(Path::new(&header.snapshot_file)).await?;
+
+    let eta = EtaTracker::default();
+    let push_total = Some(push_total(&snapshot));
+    let on_progress = |stage, processed, total| emit_progress(emit, stage, processed, total, push_total, eta.update(stage, processed, total, Instant::now()));
+
+    let summary = push_snapshot(
+        client,
+        &header.target_api_url,
+        auth,
+        &snapshot,
+        &header.tenant_payload,
+        &header.transforms,
+        cancel,
+        &on_progress,
+        Some(&checkpoint),
+    )
+    .await?;
+    finish(&snapshot, &header.tenant_id, summary, checkpoint)
+}
+
+fn finish(snapshot: &TenantSnapshot, tenant_id: &str, summary: PushSummary, checkpoint: Checkpoint) -> Result<MigrationReport, MigrationError> {
+    if let Some(failure) = summary.failed {
+        // Hand back what was created so the UI can offer rollback_migration.
+        // The checkpoint stays for resume_migration.
+        return Err(MigrationError::PartialMigration { failure: Box::new(failure), created: summary.created });
+    }
+    checkpoint.remove();
+
+    let source_tenant_id = crate::query::scoped_tenant_id(&snapshot.tenant, tenant_id);
+    tracing::info!(
+        "Migrated tenant {} -> {}: {} venues, {} wifi networks, {} APs, {} switches",
+        source_tenant_id, summary.target_tenant_id, summary.venues, summary.wifi_networks, summary.aps, summary.switches
+    );
+
+    Ok(MigrationReport {
+        source_tenant_id,
+        target_tenant_id: summary.target_tenant_id,
+        venues: summary.venues,
+        wifi_networks: summary.wifi_networks,
+        aps: summary.aps,
+        switches: summary.switches,
+        audit_log: None,
+        request_id: None,
+        timings: Vec::new(),
+        plan: None,
+        created: summary.created,
+    })
+}
+
+// Creates the tenant on the target, then its venues, wifi networks, APs and switches
+// in that order; resource types left out of the snapshot are simply empty. Failing to create the tenant is an error; a failing child resource stops
+// the push and is reported in PushSummary::failed. `cancel` is checked before every
+// create, so a cancelled run stops within one request. `tenant_payload` is sent as-is;
+// `transforms` are applied to every child record.
+//
+// With a `checkpoint` every create is appended to it, and whatever it already lists
+// is skipped, counted and reported as created as if this run had made it. A
+// checkpoint naming a target_tenant_id writes into that tenant instead of creating one.
+#[allow(clippy::too_many_arguments)]
+pub async fn push_snapshot(
+    client: &AppClient,
+    target_url: &str,
+    auth: &Auth,
+    snapshot: &TenantSnapshot,
+    tenant_payload: &Value,
+    transforms: &[TransformRule],
+    cancel: &CancellationToken,
+    on_progress: ProgressFn<'_>,
+    checkpoint: Option<&Checkpoint>,
+) -> Result<PushSummary, MigrationError> {
+    let mut summary = PushSummary::resumed(checkpoint.map(Checkpoint::pushed).unwrap_or_default());
+    if let Some(existing) = checkpoint.and_then(|c| c.header().target_tenant_id.clone()) {
+        summary.target_tenant_id = existing;
+    }
+    summary.check_cancelled(cancel)?;
+
+    on_progress("push_tenant", 0, 1);
+    if summary.target_tenant_id.is_empty() {
+        let created = client
+            .send_json(Method::POST, &format!("{}/{}", target_url, client.paths(target_url).tenants), auth, None, Some(tenant_payload))
+            .await?;
+        let target_tenant_id = created_id(&created)
+            .ok_or_else(|| MigrationError::unexpected_response("target did not return an id for the created tenant"))?;
+        summary.record(checkpoint, ResourceKind::Tenant, Some(target_tenant_id), None, tenant_payload);
+    }
+    on_progress("push_tenant", 1, 1);
+    let tenant_id = summary.target_tenant_id.clone();
+
+    for venue in &snapshot.venues {
+        summary.check_cancelled(cancel)?;
+        if !summary.is_done(ResourceKind::Venue, venue) {
+            let result = client
+                .send_json(
+                    Method::POST,
+                    &format!("{}/venues", target_url),
+                    auth,
+                    Some(&tenant_id),
+                    Some(&transform::apply(transforms, ResourceKind::Venue, venue_create_payload(venue))),
+                )
+                .await;
+            match result {
+                Ok(created) => summary.record(checkpoint, ResourceKind::Venue, created_id(&created), None, venue),
+                Err(error) => return Ok(summary.fail(ResourceKind::Venue, record_label(venue), error)),
+            }
+        }
+        on_progress(Resource::Venues.push_stage(), summary.venues, snapshot.venues.len());
+    }
+
+    for network in &snapshot.wifi_networks {
+        summary.check_cancelled(cancel)?;
+        if !summary.is_done(ResourceKind::WifiNetwork, network) {
+            let result = client
+                .send_json(
+                    Method::POST,
+                    &format!("{}/{}", target_url, client.paths(target_url).wifi_networks),
+                    auth,
+                    Some(&tenant_id),
+                    Some(&transform::apply(transforms, ResourceKind::WifiNetwork, network_create_payload(network))),
+                )
+                .await;
+            match result {
+                Ok(created) => summary.record(checkpoint, ResourceKind::WifiNetwork, created_id(&created), None, network),
+                Err(error) => return Ok(summary.fail(ResourceKind::WifiNetwork, record_label(network), error)),
+            }
+        }
+        on_progress(Resource::WifiNetworks.push_stage(), summary.wifi_networks, snapshot.wifi_networks.len());
+    }
+
+    for ap in &snapshot.aps {
+        summary.check_cancelled(cancel)?;
+        if !summary.is_done(ResourceKind::Ap, ap) {
+            let source_venue = ap.get("venueId").and_then(Value::as_str).unwrap_or_default();
+            let Some(venue_id) = summary.venue_ids.get(source_venue).cloned() else {
+                let error = MigrationError::unexpected_response(format!("AP belongs to venue {} which was not migrated", source_venue));
+                return Ok(summary.fail(ResourceKind::Ap, record_label(ap), error));
+            };
+            let result = client
+                .send_json(
+                    Method::POST,
+                    &format!("{}/venues/{}/aps", target_url, venue_id),
+                    auth,
+                    Some(&tenant_id),
+                    Some(&transform::apply(transforms, ResourceKind::Ap, ap_create_payload(ap))),
+                )
+                .await;
+            match result {
+                // AP creation doesn't always echo an id; the serial number identifies it
+                Ok(created) => {
+                    let id = created_id(&created).or_else(|| ap.get("serialNumber").and_then(Value::as_str).map(str::to_string));
+                    summary.record(checkpoint, ResourceKind::Ap, id, Some(venue_id), ap);
+                }
+                Err(error) => return Ok(summary.fail(ResourceKind::Ap, record_label(ap), error)),
+            }
+        }
+        on_progress(Resource::Aps.push_stage(), summary.aps, snapshot.aps.len());
+    }
+
+    for switch in &snapshot.switches {
+        summary.check_cancelled(cancel)?;
+        if !summary.is_done(ResourceKind::Switch, switch) {
+            let source_venue = switch.get("venueId").and_then(Value::as_str).unwrap_or_default();
+            let Some(venue_id) = summary.venue_ids.get(source_venue).cloned() else {
+                let error = MigrationError::unexpected_response(format!("switch belongs to venue {} which was not migrated", source_venue));
+                return Ok(summary.fail(ResourceKind::Switch, record_label(switch), error));
+            };
+            let result = client
+                .send_json(
+                    Method::POST,
+                    &format!("{}/venues/{}/switches", target_url, venue_id),
+                    auth,
+                    Some(&tenant_id),
+                    Some(&transform::apply(transforms, ResourceKind::Switch, switch_create_payload(switch))),
+                )
+                .await;
+            match result {
+                // Switches are identified by their serial number, which is also their id
+                Ok(created) => {
+                    let id = created_id(&created).or_else(|| source_key(switch));
+                    summary.record(checkpoint, ResourceKind::Switch, id, Some(venue_id), switch);
+                }
+                Err(error) => return Ok(summary.fail(ResourceKind::Switch, record_label(switch), error)),
+            }
+        }
+        on_progress(Resource::Switches.push_stage(), summary.switches, snapshot.switches.len());
+    }
+
+    Ok(summary)
+}
+
+impl PushSummary {
+    // State of a push that already created `pushed`, as read from a checkpoint
+    fn resumed(pushed: &[PushedEntry]) -> Self {
+        let mut summary = PushSummary::default();
+        for entry in pushed {
+            summary.mark_done(entry);
+        }
+        summary
+    }
+
+    fn is_done(&self, kind: ResourceKind, source: &Value) -> bool {
+        source_key(source).is_some_and(|key| self.done.contains(&(kind, key)))
+    }
+
+    fn record(&mut self, checkpoint: Option<&Checkpoint>, kind: ResourceKind, id: Option<String>, venue_id: Option<String>, source: &Value) {
+        let created = id.map(|id| ResourceRef { kind, id, name: record_label(source), venue_id, source_id: source_key(source) });
+        if created.is_none() {
+            tracing::warn!("Created {:?} {} but the response had no id", kind, record_label(source));
+        }
+        let entry = PushedEntry { kind, source_key: source_key(source).unwrap_or_default(), created };
+        if let Some(checkpoint) = checkpoint {
+            checkpoint.append(&entry);
+        }
+        self.mark_done(&entry);
+    }
+
+    fn mark_done(&mut self, entry: &PushedEntry) {
+        match entry.kind {
+            ResourceKind::Tenant => self.target_tenant_id = entry.created.as_ref().map(|r| r.id.clone()).unwrap_or_default(),
+            ResourceKind::Venue => self.venues += 1,
+            ResourceKind::WifiNetwork => self.wifi_networks += 1,
+            ResourceKind::Ap => self.aps += 1,
+            ResourceKind::Switch => self.switches += 1,
+        }
+        if let Some(created) = &entry.created {
+            if entry.kind == ResourceKind::Venue {
+                self.venue_ids.insert(entry.source_key.clone(), created.id.clone());
+            }
+            self.created.push(created.clone());
+        }
+        // Records without any key can't be recognised again and are never skipped
+        if !entry.source_key.is_empty() {
+            self.done.insert((entry.kind, entry.source_key.clone()));
+        }
+    }
+
+    fn check_cancelled(&self, cancel: &CancellationToken) -> Result<(), MigrationError> {
+        if cancel.is_cancelled() {
+            tracing::info!("Migration cancelled after creating {} resources", self.created.len());
+            return Err(MigrationError::Cancelled { created: self.created.clone() });
+        }
+        Ok(())
+    }
+
+    fn fail(mut self, resource: ResourceKind, item: String, error: MigrationError) -> Self {
+        tracing::warn!("Failed to create {:?} {}: {}", resource, item, error);
+        self.failed = Some(PushFailure { resource, item, error });
+        self
+    }
+}
+
+// Progress stage names are migration-specific, the rest of Resource lives in query.rs
+impl Resource {
+    pub fn kind(self) -> ResourceKind {
+        match self {
+            Resource::Venues => ResourceKind::Venue,
+            Resource::WifiNetworks => ResourceKind::WifiNetwork,
+            Resource::Aps => ResourceKind::Ap,
+            Resource::Switches => ResourceKind::Switch,
+        }
+    }
+
+    pub fn fetch_stage(self) -> &'static str {
+        match self {
+            Resource::Venues => "fetch_venues",
+            Resource::WifiNetworks => "fetch_wifi_networks",
+            Resource::Aps => "fetch_aps",
+            Resource::Switches => "fetch_switches",
+        }
+    }
+
+    pub fn push_stage(self) -> &'static str {
+        match self {
+            Resource::Venues => "push_venues",
+            Resource::WifiNetworks => "push_wifi_networks",
+            Resource::Aps => "push_aps",
+            Resource::Switches => "push_switches",
+        }
+    }
+}
+
+// Where a run reports its MigrationProgress; commands send it as "migration-progress"
+type ProgressSink<'a> = &'a (dyn Fn(MigrationProgress) + Sync);
+
+fn progress_events(app: &AppHandle) -> impl Fn(MigrationProgress) + Sync + '_ {
+    // Progress is best-effort - a closed window must not abort the migration
+    move |progress| {
+        let _ = app.emit("migration-progress", progress);
+    }
+}
+
+fn emit_progress(emit: ProgressSink<'_>, stage: &'static str, processed: usize, total: usize, phase_total: Option<usize>, eta_seconds: Option<u64>) {
+    emit(MigrationProgress { phase: phase(stage), stage, processed, total, phase_total, eta_seconds });
+}
+
+fn phase(stage: &str) -> &'static str {
+    if stage.starts_with("fetch_") {
+        "fetch"
+    } else if stage.starts_with("transform_") {
+        "transform"
+    } else {
+        "push"
+    }
+}
+
+// The tenant plus every record push_snapshot will create
+fn push_total(snapshot: &TenantSnapshot) -> usize {
+    1 + snapshot.venues.len() + snapshot.wifi_networks.len() + snapshot.aps.len() + snapshot.switches.len()
+}
+
+pub fn tenant_create_payload(tenant: &Value) -> Value {
+    without_fields(tenant, TENANT_SERVER_FIELDS)
+}
+
+pub fn venue_create_payload(venue: &Value) -> Value {
+    without_fields(venue, VENUE_SERVER_FIELDS)
+}
+
+pub fn network_create_payload(network: &Value) -> Value {
+    without_fields(network, NETWORK_SERVER_FIELDS)
+}
+
+pub fn ap_create_payload(ap: &Value) -> Value {
+    without_fields(ap, AP_SERVER_FIELDS)
+}
+
+pub fn switch_create_payload(switch: &Value) -> Value {
+    without_fields(switch, SWITCH_SERVER_FIELDS)
+}
+
+pub fn created_id(response: &Value) -> Option<String> {
+    response
+        .get("id")
+        .or_else(|| response.get("tenant_id"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+}
+
+fn without_fields(value: &Value, fields: &[&str]) -> Value {
+    let mut value = value.clone();
+    if let Some(object) = value.as_object_mut() {
+        for field in fields {
+            object.remove(*field);
+        }
+    }
+    value
+}
+
+// How a source record is recognised in a checkpoint: its id, else its serial number or name
+pub fn source_key(record: &Value) -> Option<String> {
+    ["id", "serialNumber", "name"].iter().find_map(|key| record.get(*key).and_then(Value::as_str)).map(str::to_string)
+}
+
+// Something a human can find the record by in logs and failure reports
+pub fn record_label(record: &Value) -> String {
+    ["name", "serialNumber", "id"]
+        .iter()
+        .find_map(|key| record.get(*key).and_then(Value::as_str))
+        .unwrap_or("<unnamed>")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn a_run_past_its_limit_stops_with_deadline_exceeded() {
+        let run = |cancel: CancellationToken| async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            let summary = PushSummary::default();
+            summary.check_cancelled(&cancel)
+        };
+
+        let cancel = CancellationToken::new();
+        let run_cancel = cancel.child_token();
+        let result = within_deadline(&cancel, &run_cancel, Some(Duration::from_millis(10)), run(run_cancel.clone())).await;
+        assert!(matches!(result, Err(MigrationError::DeadlineExceeded { .. })));
+        assert!(!cancel.is_cancelled());
+
+        let cancel = CancellationToken::new();
+        let run_cancel = cancel.child_token();
+        assert!(within_deadline(&cancel, &run_cancel, Some(Duration::from_secs(5)), run(run_cancel.clone())).await.is_ok());
+
+        // Cancelled by the user before the limit, though the run only noticed after it
+        let cancel = CancellationToken::new();
+        let run_cancel = cancel.child_token();
+        cancel.cancel();
+        let result = within_deadline(&cancel, &run_cancel, Some(Duration::from_millis(10)), run(run_cancel.clone())).await;
+        assert!(matches!(result, Err(MigrationError::Cancelled { .. })));
+    }
+
+    #[tokio::test]
+    async fn a_cancelled_fetch_stops_before_the_next_page() {
+        use std::sync::Arc;
+
+        use crate::backend::mock::MockBackend;
+
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(200, r#"{"id":"t1","name":"One"}"#);
+        let client = AppClient::with_backend(mock.clone()).unwrap();
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        let auth = Auth::Bearer("token".to_string());
+        let result = snapshot::fetch_snapshot(&client, "https://api.example.test", &auth, "t1", &ResourceSelection::default(), &cancel, &|_, _, _| {}).await;
+        assert!(matches!(result, Err(MigrationError::Cancelled { ref created }) if created.is_empty()));
+        // Only the tenant itself was read
+        assert_eq!(mock.requests().len(), 1);
+    }
+
+    #[test]
+    fn stages_belong_to_phases() {
+        assert_eq!(phase(Resource::Aps.fetch_stage()), "fetch");
+        assert_eq!(phase("fetch_tenant"), "fetch");
+        assert_eq!(phase("transform_tenant"), "transform");
+        assert_eq!(phase("push_tenant"), "push");
+        assert_eq!(phase(Resource::Switches.push_stage()), "push");
+    }
+
+    #[test]
+    fn eta_waits_for_enough_items_then_uses_the_average() {
+        let eta = EtaTracker::default();
+        let start = Instant::now();
+        let at = |secs: u64| start + Duration::from_secs(secs);
+
+        assert_eq!(eta.update("push_venues", 0, 10, at(0)), None);
+        assert_eq!(eta.update("push_venues", 1, 10, at(2)), None);
+        assert_eq!(eta.update("push_venues", 2, 10, at(4)), None);
+        // 3 items at 2s each, 7 left
+        assert_eq!(eta.update("push_venues", 3, 10, at(6)), Some(14));
+
+        // A new stage starts over
+        assert_eq!(eta.update("push_aps", 1, 100, at(7)), None);
+    }
+
+    #[test]
+    fn selection_defaults_to_everything() {
+        let selection: ResourceSelection = serde_json::from_value(serde_json::json!({ "aps": false })).unwrap();
+        assert!(selection.venues && selection.wifi_networks && selection.switches);
+        assert!(!selection.aps);
+        assert!(selection.validate().is_ok());
+    }
+
+    #[test]
+    fn selection_rejects_devices_without_venues() {
+        let selection = ResourceSelection { venues: false, ..ResourceSelection::default() };
+        let Err(MigrationError::Validation { problems }) = selection.validate() else {
+            panic!("expected a validation error");
+        };
+        let fields: Vec<&str> = problems.iter().map(|p| p.field.as_str()).collect();
+        assert_eq!(fields, ["aps", "switches"]);
+
+        let networks_only = ResourceSelection { venues: false, aps: false, switches: false, wifi_networks: true };
+        assert!(networks_only.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn push_skips_what_the_checkpoint_lists() {
+        use crate::backend::mock::MockBackend;
+        use serde_json::json;
+
+        let path = std::env::temp_dir().join(format!("checkpoint-{}.jsonl", uuid::Uuid::new_v4()));
+        let header = CheckpointHeader {
+            version: checkpoint::CHECKPOINT_VERSION,
+            tenant_id: "src".to_string(),
+            target_api_url: "https://api.example.test".to_string(),
+            snapshot_file: String::new(),
+            tenant_payload: json!({ "name": "Tenant" }),
+            transforms: Vec::new(),
+            target_tenant_id: None,
+        };
+        let checkpoint = Checkpoint::create(&path, header).unwrap();
+        let created = |kind, id: &str| Some(ResourceRef { kind, id: id.to_string(), name: id.to_string(), venue_id: None, source_id: None });
+        checkpoint.append(&PushedEntry { kind: ResourceKind::Tenant, source_key: "Tenant".to_string(), created: created(ResourceKind::Tenant, "new-t") });
+        checkpoint.append(&PushedEntry { kind: ResourceKind::Venue, source_key: "v1".to_string(), created: created(ResourceKind::Venue, "new-v1") });
+        drop(checkpoint);
+        let checkpoint = Checkpoint::open(&path).unwrap();
+
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(200, r#"{"id":"new-v2"}"#).respond(200, "{}");
+        let client = AppClient::with_backend(mock.clone()).unwrap();
+        let snapshot = TenantSnapshot {
+            snapshot_version: snapshot::SNAPSHOT_VERSION,
+            tenant: json!({ "id": "src", "name": "Tenant" }),
+            venues: vec![json!({ "id": "v1", "name": "One" }), json!({ "id": "v2", "name": "Two" })],
+            wifi_networks: Vec::new(),
+            aps: vec![json!({ "serialNumber": "123456789012", "venueId": "v1" })],
+            switches: Vec::new(),
+        };
+
+        let summary = push_snapshot(&client, "https://api.example.test", &Auth::Bearer("token".to_string()), &snapshot, &json!({}), &[], &CancellationToken::new(), &|_, _, _| {}, Some(&checkpoint))
+            .await
+            .unwrap();
+        let urls: Vec<String> = mock.requests().into_iter().map(|r| r.url).collect();
+        assert_eq!(urls, ["https://api.example.test/venues", "https://api.example.test/venues/new-v1/aps"]);
+        assert_eq!((summary.target_tenant_id.as_str(), summary.venues, summary.aps), ("new-t", 2, 1));
+        assert_eq!(summary.created.len(), 4);
+
+        // The new creates went into the checkpoint after the old ones
+        assert_eq!(Checkpoint::open(&path).unwrap().pushed().len(), 4);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn a_batch_creates_or_fills_each_tenant_with_its_own_payload() {
+        use crate::backend::mock::MockBackend;
+        use serde_json::json;
+
+        let tenant_data = json!({
+            "name": "A",
+            "tenant_type": "MSP_EC",
+            "service_effective_date": "2026-01-01",
+            "service_expiration_date": "2027-01-01",
+            "admin_email": "admin@a.test",
+            "admin_firstname": "Ada",
+            "admin_lastname": "Admin",
+            "admin_role": "PRIME_ADMIN"
+        });
+        let tenants: Vec<BatchTenant> = serde_json::from_value(json!([
+            { "tenant_id": "a", "tenant_data": tenant_data.clone() },
+            { "tenant_id": "b", "target_tenant_id": "existing", "force": true }
+        ]))
+        .unwrap();
+        assert!(validate_batch(&tenants).is_ok());
+        let entries: Vec<BatchEntry> = serde_json::from_value(json!(["c", { "tenant_id": "d", "force": true }, " "])).unwrap();
+        let entries: Vec<BatchTenant> = entries.into_iter().map(BatchTenant::from).collect();
+        assert_eq!((entries[0].tenant_id.as_str(), entries[0].tenant_data.is_none(), entries[1].force), ("c", true, true));
+        match validate_batch(&entries) {
+            Err(MigrationError::Validation { problems }) => assert_eq!(problems.len(), 1),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let mock = Arc::new(MockBackend::default());
+        // Tenant a: read, venues, create. Tenant b: read, venues, then the preflight counts.
+        mock.respond(200, r#"{"id":"a","name":"A","admin_email":null}"#)
+            .respond(200, r#"{"data":[]}"#)
+            .respond(201, r#"{"id":"new-a"}"#)
+            .respond(200, r#"{"id":"b","name":"B"}"#)
+            .respond(200, r#"{"data":[]}"#);
+        let client = AppClient::with_backend(mock.clone()).unwrap();
+        let source = ApiEndpoint { api_url: "https://source.example.test".to_string(), auth: Auth::Bearer("source".to_string()) };
+        let target = ApiEndpoint { api_url: "https://target.example.test".to_string(), auth: Auth::Bearer("target".to_string()) };
+        let selection = ResourceSelection { wifi_networks: false, aps: false, switches: false, ..ResourceSelection::default() };
+        let cancel = CancellationToken::new();
+        let paths: Mutex<Vec<PathBuf>> = Mutex::default();
+
+        let (client, cancel, source, target, paths) = (&client, &cancel, &source, &target, &paths);
+        let run = move |tenant: BatchTenant| async move {
+            let path = std::env::temp_dir().join(format!("checkpoint-{}.jsonl", uuid::Uuid::new_v4()));
+            paths.lock().unwrap().push(path.clone());
+            let emit = |_| {};
+            run_migration(&emit, client, cancel, SnapshotSource::Api(source), target, &tenant.tenant_id, tenant.tenant_data.clone(), selection, &[], &path, false, tenant.existing_target()).await
+        };
+        let report = run_batch(tenants, cancel, run, |_| {}).await;
+
+        assert_eq!((report.succeeded, report.failed), (2, 0), "{:?}", report.tenants);
+        let targets: Vec<Option<&str>> = report.tenants.iter().map(|t| t.target_tenant_id.as_deref()).collect();
+        assert_eq!(targets, [Some("new-a"), Some("existing")]);
+        let requests = mock.requests();
+        let create = requests.iter().find(|r| r.url == "https://target.example.test/mspCustomers").unwrap();
+        assert_eq!(create.body.as_ref(), Some(&tenant_data));
+        // b was written into, not created
+        assert_eq!(requests.iter().filter(|r| r.method == Method::POST && r.url.ends_with("/mspCustomers")).count(), 1);
+        for path in paths.lock().unwrap().iter() {
+            let _ = std::fs::remove_file(checkpoint::snapshot_path(path));
+        }
+    }
+
+    #[tokio::test]
+    async fn wait_idle_returns_once_runs_finish() {
+        let control = MigrationControl::default();
+        assert!(control.wait_idle(Duration::from_millis(10)).await);
+
+        let run = control.begin();
+        assert!(control.is_running());
+        assert!(!control.wait_idle(Duration::from_millis(10)).await);
+
+        let finish = async {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            drop(run);
+        };
+        let (idle, _) = tokio::join!(control.wait_idle(Duration::from_secs(1)), finish);
+        assert!(idle);
+        assert!(control.start_shutdown() && !control.start_shutdown());
+    }
+}
@@ -0,0 +1,89 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use crate::error::MigrationError;
+use crate::query;
+
+// Typed records for the *_typed query commands, with the fields requested by
+// query::Resource::default_query. Every field is optional because a query's `fields`
+// decides what comes back, and fields R1 adds later are ignored.
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Venue {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub address_line: Option<String>,
+    pub city: Option<String>,
+    pub country: Option<String>,
+    // The query endpoint sends coordinates as strings, the venue itself as numbers
+    #[serde(default, deserialize_with = "number_or_string")]
+    pub latitude: Option<f64>,
+    #[serde(default, deserialize_with = "number_or_string")]
+    pub longitude: Option<f64>,
+    #[serde(default)]
+    pub tag_list: Vec<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WifiNetwork {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub ssid: Option<String>,
+    pub nw_sub_type: Option<String>,
+    pub security_protocol: Option<String>,
+    pub captive_type: Option<String>,
+    pub vlan: Option<u32>,
+    // Shape differs between pool types; kept as returned
+    pub vlan_pool: Option<Value>,
+    pub tunnel_wlan_enable: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Ap {
+    pub serial_number: Option<String>,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub model: Option<String>,
+    pub venue_id: Option<String>,
+    pub firmware_version: Option<String>,
+}
+
+// The records of a query response body ("data" or "list"), deserialized as T
+pub fn parse_records<T: DeserializeOwned>(body: &str) -> Result<Vec<T>, MigrationError> {
+    let response: Value = serde_json::from_str(body)?;
+    query::records(&response)
+        .into_iter()
+        .map(|record| serde_json::from_value(record).map_err(MigrationError::from))
+        .collect()
+}
+
+fn number_or_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    match Option::<Value>::deserialize(deserializer)? {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::Number(n)) => Ok(n.as_f64()),
+        Some(Value::String(s)) if s.trim().is_empty() => Ok(None),
+        Some(Value::String(s)) => s.trim().parse().map(Some).map_err(serde::de::Error::custom),
+        Some(other) => Err(serde::de::Error::custom(format!("expected a number, got {}", other))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_records_and_ignores_unknown_fields() {
+        let body = r#"{"totalCount":1,"data":[{"id":"v1","name":"HQ","latitude":"51.5","longitude":-0.12,"addressLine":"1 Main St","newField":true}]}"#;
+        let venues: Vec<Venue> = parse_records(body).unwrap();
+        assert_eq!(venues.len(), 1);
+        assert_eq!(venues[0].address_line.as_deref(), Some("1 Main St"));
+        assert_eq!((venues[0].latitude, venues[0].longitude), (Some(51.5), Some(-0.12)));
+        assert!(venues[0].tag_list.is_empty());
+    }
+}
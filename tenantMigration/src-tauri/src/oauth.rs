@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::apiurl;
+use crate::client::AppClient;
+use crate::error::MigrationError;
+use crate::region::Region;
+use crate::state::AppState;
+
+// What the R1 client-credentials flow needs to mint a new token
+#[derive(Clone, Debug)]
+pub struct OAuthCredentials {
+    pub auth_url: String,
+    pub tenant_id: String,
+    pub client_id: String,
+    pub client_secret: String,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<u64>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RefreshedToken {
+    pub access_token: String,
+    pub expires_in: Option<u64>,
+    // Unix timestamp (seconds), when the server reported expires_in
+    pub expires_at: Option<u64>,
+}
+
+// In-memory only (never persisted): credentials for tokens minted with remember=true,
+// and which token replaced which after a refresh. Lets send_json swap an expired
+// token for its replacement mid-migration.
+#[derive(Default)]
+pub struct TokenStore(Mutex<TokenStoreInner>);
+
+#[derive(Default)]
+struct TokenStoreInner {
+    credentials: HashMap<String, Arc<OAuthCredentials>>,
+    replaced_by: HashMap<String, String>,
+}
+
+impl TokenStore {
+    pub fn remember(&self, token: &str, credentials: Arc<OAuthCredentials>) {
+        self.lock().credentials.insert(token.to_string(), credentials);
+    }
+
+    // Latest replacement for `token`, or `token` itself
+    pub fn current(&self, token: &str) -> String {
+        let inner = self.lock();
+        let mut current = token;
+        // Bounded in case of a cycle, which replace() never creates
+        for _ in 0..inner.replaced_by.len() {
+            match inner.replaced_by.get(current) {
+                Some(next) => current = next,
+                None => break,
+            }
+        }
+        current.to_string()
+    }
+
+    pub fn credentials_for(&self, token: &str) -> Option<Arc<OAuthCredentials>> {
+        self.lock().credentials.get(token).cloned()
+    }
+
+    pub fn replace(&self, old: &str, new: &str, credentials: Arc<OAuthCredentials>) {
+        let mut inner = self.lock();
+        inner.credentials.remove(old);
+        inner.credentials.insert(new.to_string(), credentials);
+        inner.replaced_by.insert(old.to_string(), new.to_string());
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TokenStoreInner> {
+        self.0.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+// Runs the R1 OAuth client-credentials flow. With remember = true the credentials are
+// kept in memory so migrations, imports and rollbacks can refresh the token by
+// themselves when it expires (a 401) instead of failing halfway.
+#[tauri::command]
+pub async fn refresh_token(
+    state: State<'_, AppState>,
+    api_url: Option<String>,
+    tenant_id: String,
+    client_id: String,
+    client_secret: String,
+    remember: Option<bool>,
+) -> Result<RefreshedToken, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    if tenant_id.trim().is_empty() || client_id.trim().is_empty() || client_secret.is_empty() {
+        return Err(MigrationError::invalid_input("tenant_id, client_id and client_secret are required"));
+    }
+
+    let credentials = Arc::new(OAuthCredentials {
+        auth_url: auth_url_for(&config.api_url_or(api_url.as_deref())?)?,
+        tenant_id: tenant_id.trim().to_string(),
+        client_id: client_id.trim().to_string(),
+        client_secret,
+    });
+    let token = request_token(client, &credentials).await?;
+    if remember.unwrap_or(false) {
+        client.tokens().remember(&token.access_token, credentials);
+    }
+    Ok(token)
+}
+
+pub async fn request_token(client: &AppClient, credentials: &OAuthCredentials) -> Result<RefreshedToken, MigrationError> {
+    let url = format!("{}/oauth2/token/{}", credentials.auth_url, credentials.tenant_id);
+    tracing::debug!("Requesting token from {}", url);
+
+    let body = client
+        .execute(|http| {
+            http.post(&url).form(&[
+                ("grant_type", "client_credentials"),
+                ("client_id", credentials.client_id.as_str()),
+                ("client_secret", credentials.client_secret.as_str()),
+            ])
+        })
+        .await?;
+    let response: TokenResponse = serde_json::from_str(&body)
+        .map_err(|e| MigrationError::unexpected_response(format!("token response has no access_token: {}", e)))?;
+
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    Ok(RefreshedToken {
+        access_token: response.access_token,
+        expires_in: response.expires_in,
+        expires_at: response.expires_in.map(|secs| now + secs),
+    })
+}
+
+// The token endpoint lives on the region's web host, not the API host
+// (api.eu.ruckus.cloud -> eu.ruckus.cloud)
+fn auth_url_for(api_url: &str) -> Result<String, MigrationError> {
+    let url = reqwest::Url::parse(&apiurl::normalize(api_url)?).map_err(|e| MigrationError::invalid_input(format!("invalid api_url: {}", e)))?;
+    let host = url.host_str().unwrap_or_default();
+    if let Some(region) = Region::from_api_host(host) {
+        return Ok(region.auth_url().to_string());
+    }
+    let auth_host = host.strip_prefix("api.").unwrap_or(host);
+    Ok(format!("{}://{}", url.scheme(), auth_host))
+}
@@ -0,0 +1,91 @@
+use reqwest::Method;
+use serde::Serialize;
+use serde_json::json;
+use tauri::State;
+
+use crate::auth::Auth;
+use crate::client::AppClient;
+use crate::error::MigrationError;
+use crate::request_id;
+use crate::state::AppState;
+
+// What a token may do, each probed with one request. null means the probe didn't
+// give an answer (network error, 5xx); false means 403/404.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Permissions {
+    pub can_read_own_tenant: Option<bool>,
+    pub can_read_tenants: Option<bool>,
+    pub can_read_venues: Option<bool>,
+    pub can_create_tenants: Option<bool>,
+}
+
+// Preflight before a long migration, so an under-scoped token fails here rather than
+// with a 403 halfway through. Creating tenants is probed by POSTing an empty body to
+// /mspCustomers: the API checks permissions before the payload, so a 400 means the
+// token may create tenants and nothing is created. A token that gets 401 on its own
+// tenant is rejected with the Auth error, as in check_connection.
+#[tauri::command]
+pub async fn check_permissions(state: State<'_, AppState>, api_url: Option<String>, auth: Auth) -> Result<Permissions, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let api_url = config.api_url_or(api_url.as_deref())?;
+    request_id::scope(request_id::new(), probe_permissions(client, &api_url, &auth)).await
+}
+
+async fn probe_permissions(client: &AppClient, api_url: &str, auth: &Auth) -> Result<Permissions, MigrationError> {
+    let own = client.probe(&format!("{}/{}/self", api_url, client.paths(api_url).tenant_records), auth).await;
+    if let Err(e @ MigrationError::Auth { status: 401, .. }) = own {
+        return Err(e);
+    }
+
+    let tenants_path = client.paths(api_url).tenants;
+    let tenants = client.probe(&format!("{}/{}?page=1&pageSize=1", api_url, tenants_path), auth).await;
+    let venues = client.probe(&format!("{}/venues", api_url), auth).await;
+    let create = client.probe_with(Method::POST, &format!("{}/{}", api_url, tenants_path), auth, Some(&json!({}))).await;
+    if create.is_ok() {
+        tracing::warn!("Empty tenant create was accepted by {}", api_url);
+    }
+
+    Ok(Permissions {
+        can_read_own_tenant: allowed(&own, false),
+        can_read_tenants: allowed(&tenants, false),
+        can_read_venues: allowed(&venues, false),
+        can_create_tenants: allowed(&create, true),
+    })
+}
+
+// `rejects_body`: the probe sends an invalid payload, so a 400/422 got past the
+// permission check
+fn allowed<T>(result: &Result<T, MigrationError>, rejects_body: bool) -> Option<bool> {
+    match result {
+        Ok(_) => Some(true),
+        Err(MigrationError::Auth { .. }) | Err(MigrationError::Http { status: 404, .. }) => Some(false),
+        Err(MigrationError::Http { status: 400 | 422, .. }) if rejects_body => Some(true),
+        Err(e) => {
+            tracing::debug!("Permission probe inconclusive: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::backend::mock::MockBackend;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn statuses_map_to_capabilities() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(200, r#"{"id":"t1"}"#).respond(403, "").respond(503, "").respond(400, r#"{"message":"name is required"}"#);
+        let client = AppClient::with_backend(mock.clone()).unwrap();
+
+        let permissions = probe_permissions(&client, "https://api.example.test", &Auth::Bearer("token".to_string())).await.unwrap();
+        assert_eq!(permissions.can_read_own_tenant, Some(true));
+        assert_eq!(permissions.can_read_tenants, Some(false));
+        assert_eq!(permissions.can_read_venues, None);
+        assert_eq!(permissions.can_create_tenants, Some(true));
+        assert_eq!(mock.requests()[3].body, Some(json!({})));
+    }
+}
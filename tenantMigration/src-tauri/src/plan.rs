@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::Value;
+use tauri::State;
+use tokio_util::sync::CancellationToken;
+
+use crate::capabilities::ApiPaths;
+use crate::error::MigrationError;
+use crate::migration::{self, ApiEndpoint, ResourceKind, ResourceSelection};
+use crate::request_id;
+use crate::snapshot::{self, TenantSnapshot};
+use crate::state::AppState;
+use crate::transform::{self, TransformRule};
+use crate::validation;
+
+// One write migrate_tenant would make. Ids of resources created earlier in the run
+// aren't known yet, so their place in `endpoint` names the source record instead,
+// e.g. "venues/{venue HQ}/aps".
+#[derive(Clone, Debug, Serialize)]
+pub struct PlannedOperation {
+    pub method: &'static str,
+    pub endpoint: String,
+    pub resource: ResourceKind,
+    pub name: String,
+    // The body that would be sent, after transforms
+    pub payload: Value,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct PlanCounts {
+    pub venues: usize,
+    pub wifi_networks: usize,
+    pub aps: usize,
+    pub switches: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct MigrationPlan {
+    pub target_api_url: String,
+    pub operations: Vec<PlannedOperation>,
+    pub counts: PlanCounts,
+    // Records migrate_tenant would stop on, e.g. an AP whose venue isn't in the snapshot
+    pub problems: Vec<String>,
+}
+
+// Dry run of migrate_tenant with the same arguments: reads the source exactly as the
+// migration would and returns every create it would send to the target, in order,
+// without writing anything. Meant to be shown for approval before migrate_tenant runs.
+#[tauri::command]
+pub async fn plan_migration(
+    state: State<'_, AppState>,
+    source: ApiEndpoint,
+    target: ApiEndpoint,
+    tenant_id: String,
+    tenant_data: Option<Value>,
+    selection: Option<ResourceSelection>,
+    transforms: Option<Vec<TransformRule>>,
+) -> Result<MigrationPlan, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let selection = selection.unwrap_or_default();
+    let transforms = transforms.unwrap_or_default();
+    selection.validate()?;
+    transform::validate(&transforms)?;
+    if tenant_id.trim().is_empty() {
+        return Err(MigrationError::invalid_input("tenant_id must not be empty"));
+    }
+
+    let source_url = &config.api_url_or(Some(&source.api_url))?;
+    let snapshot = request_id::scope(
+        request_id::new(),
+        snapshot::fetch_snapshot(client, source_url, &source.auth, &tenant_id, &selection, &CancellationToken::new(), &|_, _, _| {}),
+    )
+    .await?;
+
+    let tenant_payload = tenant_data.unwrap_or_else(|| migration::tenant_create_payload(&snapshot.tenant));
+    let tenant_payload = transform::apply(&transforms, ResourceKind::Tenant, tenant_payload);
+    validation::validate_tenant_data(&tenant_payload)?;
+
+    let target_url = &config.api_url_or(Some(&target.api_url))?;
+    let plan = plan(&snapshot, tenant_payload, &transforms, target_url, client.paths(target_url));
+    tracing::info!("Planned {} operations for tenant {} ({} problems)", plan.operations.len(), tenant_id, plan.problems.len());
+    Ok(plan)
+}
+
+// Mirrors push_snapshot: tenant, venues, wifi networks, then APs and switches in
+// their venues
+pub fn plan(snapshot: &TenantSnapshot, tenant_payload: Value, transforms: &[TransformRule], target_url: &str, paths: ApiPaths) -> MigrationPlan {
+    let mut plan = MigrationPlan { target_api_url: target_url.to_string(), operations: Vec::new(), counts: PlanCounts::default(), problems: Vec::new() };
+    let mut add = |endpoint: String, resource: ResourceKind, record: &Value, payload: Value| {
+        plan.operations.push(PlannedOperation {
+            method: "POST",
+            endpoint,
+            resource,
+            name: migration::record_label(record),
+            payload: transform::apply(transforms, resource, payload),
+        });
+    };
+
+    add(paths.tenants.to_string(), ResourceKind::Tenant, &tenant_payload, tenant_payload.clone());
+    let mut venue_names = HashMap::new();
+    for venue in &snapshot.venues {
+        if let Some(id) = venue.get("id").and_then(Value::as_str) {
+            venue_names.insert(id, migration::record_label(venue));
+        }
+        add("venues".to_string(), ResourceKind::Venue, venue, migration::venue_create_payload(venue));
+    }
+    for network in &snapshot.wifi_networks {
+        add(paths.wifi_networks.to_string(), ResourceKind::WifiNetwork, network, migration::network_create_payload(network));
+    }
+
+    let mut problems = Vec::new();
+    let devices = [
+        (ResourceKind::Ap, "aps", &snapshot.aps, migration::ap_create_payload as fn(&Value) -> Value),
+        (ResourceKind::Switch, "switches", &snapshot.switches, migration::switch_create_payload),
+    ];
+    for (kind, path, records, payload) in devices {
+        for record in records {
+            let source_venue = record.get("venueId").and_then(Value::as_str).unwrap_or_default();
+            match venue_names.get(source_venue) {
+                Some(venue) => add(format!("venues/{{venue {}}}/{}", venue, path), kind, record, payload(record)),
+                None => problems.push(format!("{:?} {} belongs to venue {} which is not migrated", kind, migration::record_label(record), source_venue)),
+            }
+        }
+    }
+
+    plan.problems = problems;
+    plan.counts = PlanCounts {
+        venues: snapshot.venues.len(),
+        wifi_networks: snapshot.wifi_networks.len(),
+        aps: snapshot.aps.len(),
+        switches: snapshot.switches.len(),
+    };
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn plan_lists_creates_in_push_order() {
+        let snapshot = TenantSnapshot {
+            snapshot_version: snapshot::SNAPSHOT_VERSION,
+            tenant: json!({ "id": "src", "name": "Tenant" }),
+            venues: vec![json!({ "id": "v1", "name": "HQ", "status": "ok" })],
+            wifi_networks: vec![json!({ "id": "n1", "name": "Guest" })],
+            aps: vec![json!({ "serialNumber": "1", "venueId": "v1" }), json!({ "serialNumber": "2", "venueId": "gone" })],
+            switches: Vec::new(),
+        };
+        let plan = plan(&snapshot, json!({ "name": "Tenant" }), &[], "https://api.example.test", ApiPaths::default());
+
+        let endpoints: Vec<&str> = plan.operations.iter().map(|op| op.endpoint.as_str()).collect();
+        assert_eq!(endpoints, ["mspCustomers", "venues", "wifiNetworks", "venues/{venue HQ}/aps"]);
+        assert_eq!(plan.operations[1].payload, json!({ "name": "HQ" }));
+        assert_eq!(plan.counts.aps, 2);
+        assert_eq!(plan.problems.len(), 1);
+    }
+}
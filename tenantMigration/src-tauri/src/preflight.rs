@@ -0,0 +1,110 @@
+use serde::Serialize;
+use tauri::State;
+
+use crate::auth::Auth;
+use crate::client::AppClient;
+use crate::error::MigrationError;
+use crate::query::{self, ResourceCounts};
+use crate::request_id;
+use crate::state::AppState;
+
+// Result of preflight_target. `warning` is set whenever `empty` is false.
+#[derive(Clone, Debug, Serialize)]
+pub struct TargetPreflight {
+    pub tenant_id: String,
+    pub empty: bool,
+    pub counts: ResourceCounts,
+    pub warning: Option<String>,
+}
+
+// Checks that the tenant a migration is about to write into has no venues, wifi
+// networks, APs or switches yet, since copying into one that does leaves duplicates.
+// A count the API doesn't report (no totalCount) can't show the tenant is empty, so
+// it blocks the migration the same way unless force is set.
+#[tauri::command]
+pub async fn preflight_target(
+    state: State<'_, AppState>,
+    api_url: Option<String>,
+    target_id: String,
+    auth: Auth,
+) -> Result<TargetPreflight, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let api_url = config.api_url_or(api_url.as_deref())?;
+    request_id::scope(request_id::new(), check(client, &api_url, &auth, &target_id)).await
+}
+
+pub async fn check(client: &AppClient, api_url: &str, auth: &Auth, target_id: &str) -> Result<TargetPreflight, MigrationError> {
+    let target_id = target_id.trim();
+    if target_id.is_empty() {
+        return Err(MigrationError::invalid_input("target_id must not be empty"));
+    }
+    let counts = query::count_all(client, api_url, auth, target_id).await?;
+    Ok(assess(target_id, counts))
+}
+
+fn assess(tenant_id: &str, counts: ResourceCounts) -> TargetPreflight {
+    let labelled = [(counts.venues, "venues"), (counts.wifi_networks, "wifi networks"), (counts.aps, "APs"), (counts.switches, "switches")];
+    let found: Vec<String> = labelled.iter().filter_map(|(count, label)| count.filter(|n| *n > 0).map(|n| format!("{} {}", n, label))).collect();
+    let unknown: Vec<&str> = labelled.iter().filter(|(count, _)| count.is_none()).map(|(_, label)| *label).collect();
+    let warning = match (found.is_empty(), unknown.is_empty()) {
+        (true, true) => None,
+        (false, true) => Some(format!("Target tenant {} is not empty: it already has {}", tenant_id, found.join(", "))),
+        (true, false) => Some(format!("Target tenant {} may not be empty: the API did not report how many {} it has", tenant_id, unknown.join(", "))),
+        (false, false) => Some(format!(
+            "Target tenant {} is not empty: it already has {}, and the API did not report how many {} it has",
+            tenant_id,
+            found.join(", "),
+            unknown.join(", ")
+        )),
+    };
+    if let Some(warning) = &warning {
+        tracing::warn!("{}", warning);
+    }
+    TargetPreflight { tenant_id: tenant_id.to_string(), empty: warning.is_none(), counts, warning }
+}
+
+// What a migration into a non-empty target fails with unless it was forced
+pub fn ensure_empty(preflight: TargetPreflight, force: bool) -> Result<(), MigrationError> {
+    match preflight.warning {
+        Some(warning) if force => {
+            tracing::warn!("{}; continuing because force is set", warning);
+            Ok(())
+        }
+        Some(_) => Err(MigrationError::TargetNotEmpty { tenant_id: preflight.tenant_id, counts: preflight.counts }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_empty_targets_are_refused_unless_forced() {
+        let empty = assess("t1", ResourceCounts { venues: Some(0), wifi_networks: Some(0), aps: Some(0), switches: Some(0) });
+        assert!(empty.empty && empty.warning.is_none());
+        assert!(ensure_empty(empty, false).is_ok());
+
+        let busy = assess("t1", ResourceCounts { venues: Some(3), wifi_networks: Some(0), aps: Some(12), switches: Some(0) });
+        assert_eq!(busy.warning.as_deref(), Some("Target tenant t1 is not empty: it already has 3 venues, 12 APs"));
+        assert!(ensure_empty(busy.clone(), true).is_ok());
+        assert!(matches!(ensure_empty(busy, false), Err(MigrationError::TargetNotEmpty { .. })));
+    }
+
+    #[test]
+    fn uncounted_resources_block_like_existing_ones() {
+        let unknown = assess("t1", ResourceCounts { venues: Some(0), wifi_networks: None, aps: Some(0), switches: Some(0) });
+        assert!(!unknown.empty);
+        assert_eq!(unknown.warning.as_deref(), Some("Target tenant t1 may not be empty: the API did not report how many wifi networks it has"));
+        assert!(ensure_empty(unknown.clone(), true).is_ok());
+        let err = ensure_empty(unknown, false).unwrap_err();
+        assert!(err.to_string().starts_with("Could not confirm target tenant t1 is empty"));
+
+        let both = assess("t1", ResourceCounts { venues: Some(2), wifi_networks: Some(0), aps: None, switches: None });
+        assert_eq!(
+            both.warning.as_deref(),
+            Some("Target tenant t1 is not empty: it already has 2 venues, and the API did not report how many APs, switches it has")
+        );
+        assert!(ensure_empty(both, false).unwrap_err().to_string().starts_with("Target tenant t1 already has resources"));
+    }
+}
@@ -0,0 +1,237 @@
+use reqwest::Method;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio_util::sync::CancellationToken;
+
+use crate::auth::Auth;
+use crate::capabilities::ApiPaths;
+use crate::client::AppClient;
+use crate::error::MigrationError;
+
+pub const DEFAULT_PAGE_SIZE: u64 = 500;
+// Hard stop in case the server ignores `page` and keeps returning full pages
+pub const MAX_PAGES: u64 = 1000;
+
+// Runs `query` against a paginated `/query` endpoint page by page and returns every
+// record. Stops once `totalCount` records are collected, or - when the response has
+// no totalCount - on the first short or empty page.
+pub async fn fetch_all_pages(
+    client: &AppClient,
+    url: &str,
+    auth: &Auth,
+    tenant_id: &str,
+    query: &Value,
+    page_size: u64,
+) -> Result<Vec<Value>, MigrationError> {
+    let mut all = Vec::new();
+    fetch_pages(client, url, auth, tenant_id, query, page_size, |_, batch| all.extend(batch)).await?;
+    Ok(all)
+}
+
+// fetch_all_pages without collecting: each page's records go to `on_page` with the
+// page number as they arrive, and only the total count is returned
+pub async fn fetch_pages(
+    client: &AppClient,
+    url: &str,
+    auth: &Auth,
+    tenant_id: &str,
+    query: &Value,
+    page_size: u64,
+    on_page: impl FnMut(u64, Vec<Value>),
+) -> Result<u64, MigrationError> {
+    fetch_pages_cancellable(client, url, auth, tenant_id, query, page_size, &CancellationToken::new(), on_page).await
+}
+
+// fetch_pages that checks `cancel` before every page and fails with Cancelled (nothing
+// created yet) once it's set
+#[allow(clippy::too_many_arguments)]
+pub async fn fetch_pages_cancellable(
+    client: &AppClient,
+    url: &str,
+    auth: &Auth,
+    tenant_id: &str,
+    query: &Value,
+    page_size: u64,
+    cancel: &CancellationToken,
+    mut on_page: impl FnMut(u64, Vec<Value>),
+) -> Result<u64, MigrationError> {
+    if page_size == 0 {
+        return Err(MigrationError::invalid_input("page_size must be at least 1"));
+    }
+
+    let mut query = match query {
+        Value::Object(_) => query.clone(),
+        Value::Null => Value::Object(Default::default()),
+        _ => return Err(MigrationError::invalid_input("query_data must be a JSON object")),
+    };
+    query["pageSize"] = Value::from(page_size);
+
+    let mut fetched = 0;
+    for page in 1..=MAX_PAGES {
+        query["page"] = Value::from(page);
+        if cancel.is_cancelled() {
+            tracing::info!("Fetch of {} cancelled after {} pages", url, page - 1);
+            return Err(MigrationError::Cancelled { created: Vec::new() });
+        }
+
+        let response = client.send_json(Method::POST, url, auth, Some(tenant_id), Some(&query)).await?;
+        let batch = records(&response);
+        let batch_len = batch.len() as u64;
+        fetched += batch_len;
+        on_page(page, batch);
+
+        let done = match response.get("totalCount").and_then(Value::as_u64) {
+            Some(total) => fetched >= total || batch_len == 0,
+            None => batch_len < page_size,
+        };
+        if done {
+            return Ok(fetched);
+        }
+    }
+
+    Err(MigrationError::unexpected_response(format!(
+        "pagination did not finish after {} pages of {} records",
+        MAX_PAGES, page_size
+    )))
+}
+
+// Query responses carry the rows in `data` (or `list` on older API versions)
+pub fn records(response: &Value) -> Vec<Value> {
+    response
+        .get("data")
+        .or_else(|| response.get("list"))
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default()
+}
+
+pub async fn fetch_tenant(client: &AppClient, api_url: &str, auth: &Auth, tenant_id: &str) -> Result<Value, MigrationError> {
+    client
+        .send_json(Method::GET, &format!("{}/{}/{}", api_url, client.paths(api_url).tenant_records, tenant_id), auth, None, None)
+        .await
+}
+
+// Child resources are queried with the tenant's own id in x-rks-tenantid, which the
+// tenant record reports as `tenant_id`
+pub fn scoped_tenant_id(tenant: &Value, fallback: &str) -> String {
+    tenant
+        .get("tenant_id")
+        .and_then(Value::as_str)
+        .unwrap_or(fallback)
+        .to_string()
+}
+
+// Every record of one resource type, using its default query
+pub async fn fetch_resource(client: &AppClient, api_url: &str, auth: &Auth, tenant_id: &str, resource: Resource) -> Result<Vec<Value>, MigrationError> {
+    fetch_resource_cancellable(client, api_url, auth, tenant_id, resource, &CancellationToken::new()).await
+}
+
+// fetch_resource that stops between pages once `cancel` is set
+pub async fn fetch_resource_cancellable(
+    client: &AppClient,
+    api_url: &str,
+    auth: &Auth,
+    tenant_id: &str,
+    resource: Resource,
+    cancel: &CancellationToken,
+) -> Result<Vec<Value>, MigrationError> {
+    let mut all = Vec::new();
+    fetch_pages_cancellable(
+        client,
+        &format!("{}/{}", api_url, resource.query_path(client.paths(api_url))),
+        auth,
+        tenant_id,
+        &resource.default_query(),
+        DEFAULT_PAGE_SIZE,
+        cancel,
+        |_, batch| all.extend(batch),
+    )
+    .await?;
+    Ok(all)
+}
+
+// How many records of `resource` the tenant has, from the `totalCount` of a one-record
+// page. None when the endpoint doesn't report a total.
+pub async fn count_resource(client: &AppClient, api_url: &str, auth: &Auth, tenant_id: &str, resource: Resource) -> Result<Option<u64>, MigrationError> {
+    let mut query = resource.default_query();
+    query["page"] = Value::from(1);
+    query["pageSize"] = Value::from(1);
+    let response = client
+        .send_json(Method::POST, &format!("{}/{}", api_url, resource.query_path(client.paths(api_url))), auth, Some(tenant_id), Some(&query))
+        .await?;
+    Ok(response.get("totalCount").and_then(Value::as_u64))
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct ResourceCounts {
+    pub venues: Option<u64>,
+    pub wifi_networks: Option<u64>,
+    pub aps: Option<u64>,
+    pub switches: Option<u64>,
+}
+
+// count_resource for every resource type at once
+pub async fn count_all(client: &AppClient, api_url: &str, auth: &Auth, tenant_id: &str) -> Result<ResourceCounts, MigrationError> {
+    let count = |resource| count_resource(client, api_url, auth, tenant_id, resource);
+    let (venues, wifi_networks, aps, switches) =
+        futures::future::try_join4(count(Resource::Venues), count(Resource::WifiNetworks), count(Resource::Aps), count(Resource::Switches)).await?;
+    Ok(ResourceCounts { venues, wifi_networks, aps, switches })
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Resource {
+    Venues,
+    WifiNetworks,
+    Aps,
+    Switches,
+}
+
+impl Resource {
+    // `paths` is AppClient::paths of the API queried
+    pub fn query_path(self, paths: ApiPaths) -> String {
+        match self {
+            Resource::Venues => "venues/query".to_string(),
+            Resource::WifiNetworks => format!("{}/query", paths.wifi_networks),
+            Resource::Aps => "venues/aps/query".to_string(),
+            Resource::Switches => "venues/switches/query".to_string(),
+        }
+    }
+
+    // Same defaults as the frontend's getVenues/querywNetworks/queryAPs; page and
+    // pageSize are filled in by fetch_all_pages
+    pub fn default_query(self) -> Value {
+        match self {
+            Resource::Venues => json!({
+                "fields": [
+                    "name", "description", "city", "country", "latitude", "longitude",
+                    "id", "addressLine", "tagList"
+                ],
+                "filters": {},
+                "sortField": "name",
+                "sortOrder": "ASC"
+            }),
+            Resource::WifiNetworks => json!({
+                "fields": [
+                    "name", "description", "nwSubType", "vlan", "ssid", "vlanPool",
+                    "captiveType", "id", "securityProtocol", "tunnelWlanEnable"
+                ],
+                "filters": {},
+                "sortField": "name",
+                "sortOrder": "ASC"
+            }),
+            Resource::Aps => json!({
+                "fields": ["serialNumber", "name", "venueId", "description", "model"]
+            }),
+            Resource::Switches => json!({
+                "fields": ["id", "serialNumber", "name", "venueId", "description", "model"]
+            }),
+        }
+    }
+
+    // default_query narrowed to the records of one venue
+    pub fn venue_query(self, venue_id: &str) -> Value {
+        let mut query = self.default_query();
+        query["filters"] = json!({ "venueId": [venue_id] });
+        query
+    }
+}
@@ -0,0 +1,80 @@
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+// Token bucket shared by every request AppClient sends, to stay under R1's rate
+// limits instead of running into 429s. Off (unlimited) until set_rate_limit is
+// called. Callers that find the bucket empty reserve the next token and sleep until
+// it's due, so waiting doesn't hold the lock or block the runtime, and waiters are
+// served in the order they arrived.
+pub struct RateLimiter {
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    // Tokens per second; None = no limit
+    rate: Option<f64>,
+    burst: f64,
+    // Negative while callers are waiting for tokens not yet refilled
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self { bucket: Mutex::new(Bucket { rate: None, burst: 1.0, tokens: 1.0, updated: Instant::now() }) }
+    }
+}
+
+impl RateLimiter {
+    // `burst` requests can go out back to back after a quiet spell; the bucket starts full
+    pub fn configure(&self, rate: Option<f64>, burst: u32) {
+        let mut bucket = self.bucket.lock().unwrap_or_else(PoisonError::into_inner);
+        let burst = f64::from(burst.max(1));
+        *bucket = Bucket { rate, burst, tokens: burst, updated: Instant::now() };
+    }
+
+    pub async fn acquire(&self) {
+        let wait = self.reserve_at(Instant::now());
+        if !wait.is_zero() {
+            tracing::debug!("Rate limit reached, waiting {:?}", wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    // Takes a token and returns how long to wait before it may be used
+    fn reserve_at(&self, now: Instant) -> Duration {
+        let mut bucket = self.bucket.lock().unwrap_or_else(PoisonError::into_inner);
+        let Some(rate) = bucket.rate else {
+            return Duration::ZERO;
+        };
+        let refilled = now.saturating_duration_since(bucket.updated).as_secs_f64() * rate;
+        bucket.tokens = (bucket.tokens + refilled).min(bucket.burst) - 1.0;
+        bucket.updated = now;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / rate)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn burst_then_one_token_per_interval() {
+        let limiter = RateLimiter::default();
+        assert_eq!(limiter.reserve_at(Instant::now()), Duration::ZERO);
+
+        limiter.configure(Some(2.0), 2);
+        let start = Instant::now();
+        assert_eq!(limiter.reserve_at(start), Duration::ZERO);
+        assert_eq!(limiter.reserve_at(start), Duration::ZERO);
+        // Queued callers each wait one interval longer than the one before
+        assert_eq!(limiter.reserve_at(start), Duration::from_millis(500));
+        assert_eq!(limiter.reserve_at(start), Duration::from_millis(1000));
+        // After the backlog has drained the bucket refills up to the burst
+        assert_eq!(limiter.reserve_at(start + Duration::from_secs(10)), Duration::ZERO);
+    }
+}
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+// R1 regions. Serialized with the names the frontend uses (types.ts `Region`);
+// the short codes are accepted too.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Region {
+    #[serde(rename = "North America", alias = "na", alias = "NA")]
+    NorthAmerica,
+    #[serde(alias = "eu", alias = "EU")]
+    Europe,
+    #[serde(alias = "asia")]
+    Asia,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RegionUrls {
+    pub api_url: &'static str,
+    pub auth_url: &'static str,
+}
+
+const ALL: [Region; 3] = [Region::NorthAmerica, Region::Europe, Region::Asia];
+
+impl Region {
+    pub fn api_url(self) -> &'static str {
+        match self {
+            Region::NorthAmerica => "https://api.ruckus.cloud",
+            Region::Europe => "https://api.eu.ruckus.cloud",
+            Region::Asia => "https://api.asia.ruckus.cloud",
+        }
+    }
+
+    // Token exchange (`/oauth2/token/{tenantId}`) lives on a different host than the API
+    pub fn auth_url(self) -> &'static str {
+        match self {
+            Region::NorthAmerica => "https://ruckus.cloud",
+            Region::Europe => "https://eu.ruckus.cloud",
+            Region::Asia => "https://asia.ruckus.cloud",
+        }
+    }
+
+    pub fn from_api_host(host: &str) -> Option<Region> {
+        ALL.into_iter().find(|region| region.api_url().strip_prefix("https://") == Some(host))
+    }
+}
+
+// Both base URLs for a region, so the frontend doesn't have to keep its own copy
+#[tauri::command]
+pub fn region_urls(region: Region) -> RegionUrls {
+    RegionUrls { api_url: region.api_url(), auth_url: region.auth_url() }
+}
@@ -0,0 +1,126 @@
+use std::io::Write;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde_json::Value;
+use tauri::AppHandle;
+use tauri_plugin_fs::{FsExt, OpenOptions};
+
+use crate::error::MigrationError;
+use crate::migration::{ResourceKind, ResourceRef};
+
+const HEADER: [&str; 6] = ["resource_type", "name", "source_id", "target_id", "status", "error"];
+
+// What export_report_csv reads: a MigrationReport from migrate_tenant, or the
+// PartialMigration error of a run that stopped, which adds the failed record
+#[derive(Debug, Default, Deserialize)]
+pub struct ReportInput {
+    #[serde(default)]
+    pub created: Vec<ResourceRef>,
+    #[serde(default)]
+    pub failure: Option<ReportFailure>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReportFailure {
+    pub resource: ResourceKind,
+    pub item: String,
+    // The serialized MigrationError
+    pub error: Value,
+}
+
+// Writes one row per created resource (plus the failed one, if any) to `file_path`,
+// for change tickets and spreadsheets. Returns the number of rows, header excluded.
+#[tauri::command]
+pub fn export_report_csv(app: AppHandle, report: ReportInput, file_path: String) -> Result<usize, MigrationError> {
+    if file_path.trim().is_empty() {
+        return Err(MigrationError::invalid_input("file_path must not be empty"));
+    }
+    let path = PathBuf::from(file_path);
+    let rows = rows(&report);
+    let csv = to_csv(&rows);
+
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    let mut file = app.fs().open(&path, options).map_err(|e| MigrationError::io(&path, e))?;
+    file.write_all(csv.as_bytes()).map_err(|e| MigrationError::io(&path, e))?;
+
+    tracing::info!("Wrote {} report rows to {}", rows.len(), path.display());
+    Ok(rows.len())
+}
+
+fn rows(report: &ReportInput) -> Vec<[String; 6]> {
+    let mut rows: Vec<[String; 6]> = report
+        .created
+        .iter()
+        .map(|r| [kind_name(r.kind), r.name.clone(), r.source_id.clone().unwrap_or_default(), r.id.clone(), "created".to_string(), String::new()])
+        .collect();
+    if let Some(failure) = &report.failure {
+        rows.push([kind_name(failure.resource), failure.item.clone(), String::new(), String::new(), "failed".to_string(), error_text(&failure.error)]);
+    }
+    rows
+}
+
+fn kind_name(kind: ResourceKind) -> String {
+    serde_json::to_value(kind).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default()
+}
+
+// MigrationError is serialized with a "kind" tag; most variants carry a message,
+// HTTP errors the response body
+fn error_text(error: &Value) -> String {
+    ["message", "body", "kind"]
+        .iter()
+        .find_map(|key| error.get(*key).and_then(Value::as_str).filter(|s| !s.is_empty()))
+        .map(str::to_string)
+        .unwrap_or_else(|| error.to_string())
+}
+
+fn to_csv(rows: &[[String; 6]]) -> String {
+    let mut csv = HEADER.join(",");
+    csv.push_str("\r\n");
+    for row in rows {
+        let fields: Vec<String> = row.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+// RFC 4180 quoting. Fields a spreadsheet would run as a formula get a leading '
+// so a venue named "=HYPERLINK(...)" stays text.
+fn csv_field(field: &str) -> String {
+    let field = if field.starts_with(['=', '+', '-', '@']) { format!("'{}", field) } else { field.to_string() };
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn writes_created_and_failed_rows_with_escaping() {
+        let report: ReportInput = serde_json::from_value(json!({
+            "created": [
+                { "kind": "tenant", "id": "t-new", "name": "Acme", "venue_id": null, "source_id": "t-old" },
+                { "kind": "venue", "id": "v-new", "name": "HQ, \"North\"", "venue_id": null }
+            ],
+            "failure": { "resource": "ap", "item": "AP-1", "error": { "kind": "Http", "status": 400, "body": "bad\nserial" } }
+        }))
+        .unwrap();
+
+        assert_eq!(
+            to_csv(&rows(&report)),
+            "resource_type,name,source_id,target_id,status,error\r\n\
+             tenant,Acme,t-old,t-new,created,\r\n\
+             venue,\"HQ, \"\"North\"\"\",,v-new,created,\r\n\
+             ap,AP-1,,,failed,\"bad\nserial\"\r\n"
+        );
+        assert_eq!(csv_field("=1+1"), "'=1+1");
+    }
+}
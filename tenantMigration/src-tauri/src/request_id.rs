@@ -0,0 +1,24 @@
+use std::future::Future;
+
+// Sent on every outgoing call so a request can be found in R1's server logs
+pub const HEADER: &str = "x-request-id";
+
+tokio::task_local! {
+    // Set for a whole command so every call it makes (retries, polls, each resource
+    // of a migration) shares one id that can be handed to support
+    static CURRENT: String;
+}
+
+pub fn new() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+// Runs `f` with `id` as the request id of every call it makes
+pub async fn scope<F: Future>(id: String, f: F) -> F::Output {
+    CURRENT.scope(id, f).await
+}
+
+// The id of the surrounding scope(), or a fresh one per call outside of one
+pub fn current() -> String {
+    CURRENT.try_with(Clone::clone).unwrap_or_else(|_| new())
+}
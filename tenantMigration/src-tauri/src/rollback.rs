@@ -0,0 +1,96 @@
+use reqwest::Method;
+use serde::Serialize;
+use tauri::State;
+
+use crate::auth::Auth;
+use crate::capabilities::ApiPaths;
+use crate::error::MigrationError;
+use crate::migration::{ResourceKind, ResourceRef};
+use crate::state::AppState;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RollbackReport {
+    pub deleted: Vec<ResourceRef>,
+    pub failed: Vec<RollbackFailure>,
+}
+
+#[derive(Clone, Debug, Serialize)]
+pub struct RollbackFailure {
+    pub resource: ResourceRef,
+    pub error: MigrationError,
+}
+
+// Deletes what a failed or cancelled migration created (the `created` list from
+// MigrationError::PartialMigration / Cancelled). Children go before their parents -
+// switches and APs, wifi networks, venues, then the tenant - newest first within each kind.
+// A failed delete doesn't stop the rest; the report lists both outcomes.
+// `tenant_id` is needed when `created` has no tenant, i.e. after a migrate_tenant run
+// into an existing tenant (its target_tenant_id); that tenant itself is left alone.
+#[tauri::command]
+pub async fn rollback_migration(
+    state: State<'_, AppState>,
+    created: Vec<ResourceRef>,
+    api_url: Option<String>,
+    auth: Auth,
+    tenant_id: Option<String>,
+) -> Result<RollbackReport, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let api_url = &config.api_url_or(api_url.as_deref())?;
+    // Child resources are scoped to the tenant they were created in
+    let tenant_id = created.iter().find(|r| r.kind == ResourceKind::Tenant).map(|r| r.id.clone()).or(tenant_id);
+
+    let mut ordered: Vec<ResourceRef> = created.into_iter().rev().collect();
+    ordered.sort_by_key(|r| delete_order(r.kind));
+
+    let mut report = RollbackReport { deleted: Vec::new(), failed: Vec::new() };
+    for resource in ordered {
+        let result = match delete_url(api_url, client.paths(api_url), &resource) {
+            Ok(url) => {
+                let scope = if resource.kind == ResourceKind::Tenant { None } else { tenant_id.as_deref() };
+                client.send_json(Method::DELETE, &url, &auth, scope, None).await.map(|_| ())
+            }
+            Err(e) => Err(e),
+        };
+
+        match result {
+            Ok(()) => {
+                tracing::info!("Rolled back {:?} {} ({})", resource.kind, resource.name, resource.id);
+                report.deleted.push(resource);
+            }
+            Err(error) => {
+                tracing::warn!("Could not delete {:?} {} ({}): {}", resource.kind, resource.name, resource.id, error);
+                report.failed.push(RollbackFailure { resource, error });
+            }
+        }
+    }
+    Ok(report)
+}
+
+// sort_by_key is stable, so the reversed creation order survives within a kind
+fn delete_order(kind: ResourceKind) -> u8 {
+    match kind {
+        ResourceKind::Ap | ResourceKind::Switch => 0,
+        ResourceKind::WifiNetwork => 1,
+        ResourceKind::Venue => 2,
+        ResourceKind::Tenant => 3,
+    }
+}
+
+fn delete_url(api_url: &str, paths: ApiPaths, resource: &ResourceRef) -> Result<String, MigrationError> {
+    if resource.id.trim().is_empty() {
+        return Err(MigrationError::invalid_input("resource id must not be empty"));
+    }
+    match resource.kind {
+        ResourceKind::Tenant => Ok(format!("{}/{}/{}", api_url, paths.tenants, resource.id)),
+        ResourceKind::Venue => Ok(format!("{}/venues/{}", api_url, resource.id)),
+        ResourceKind::WifiNetwork => Ok(format!("{}/{}/{}", api_url, paths.wifi_networks, resource.id)),
+        ResourceKind::Ap => match &resource.venue_id {
+            Some(venue_id) => Ok(format!("{}/venues/{}/aps/{}", api_url, venue_id, resource.id)),
+            None => Err(MigrationError::invalid_input("AP has no venue_id to delete it from")),
+        },
+        ResourceKind::Switch => match &resource.venue_id {
+            Some(venue_id) => Ok(format!("{}/venues/{}/switches/{}", api_url, venue_id, resource.id)),
+            None => Err(MigrationError::invalid_input("switch has no venue_id to delete it from")),
+        },
+    }
+}
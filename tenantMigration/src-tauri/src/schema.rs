@@ -0,0 +1,119 @@
+use std::collections::BTreeSet;
+
+use serde_json::Value;
+
+use crate::diff::{self, JsonDiff};
+
+// Type skeleton of a JSON document: objects keep their keys, every leaf becomes its
+// type name and an array becomes a one-element array describing all its items, e.g.
+// {"name":"A","tags":[1,2]} -> {"name":"string","tags":["number"]}. Items of mixed
+// types are merged: object keys are unioned, other mismatches become "number|string".
+// Local only, for comparing responses from two API versions.
+#[tauri::command]
+pub fn schema_of(json: Value) -> Value {
+    skeleton(&json)
+}
+
+// Where the skeleton of `target` differs from that of `source`: fields only one side
+// has are added/removed, type changes show up as changed
+#[tauri::command]
+pub fn diff_schemas(source: Value, target: Value) -> JsonDiff {
+    diff::diff_values(&skeleton(&source), &skeleton(&target), &[])
+}
+
+fn skeleton(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(map.iter().map(|(key, value)| (key.clone(), skeleton(value))).collect()),
+        Value::Array(items) => {
+            let merged = items.iter().map(skeleton).reduce(merge);
+            Value::Array(merged.into_iter().collect())
+        }
+        leaf => Value::String(type_name(leaf).to_string()),
+    }
+}
+
+fn merge(a: Value, b: Value) -> Value {
+    match (a, b) {
+        (Value::Object(mut a), Value::Object(b)) => {
+            for (key, b_value) in b {
+                let merged = match a.remove(&key) {
+                    Some(a_value) => merge(a_value, b_value),
+                    None => b_value,
+                };
+                a.insert(key, merged);
+            }
+            Value::Object(a)
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            let merged = a.into_iter().chain(b).reduce(merge);
+            Value::Array(merged.into_iter().collect())
+        }
+        (a, b) if a == b => a,
+        (a, b) => {
+            let names: BTreeSet<String> = [a, b].iter().flat_map(names).collect();
+            Value::String(names.into_iter().collect::<Vec<_>>().join("|"))
+        }
+    }
+}
+
+// Type names a skeleton node stands for; "number|string" counts as both
+fn names(skeleton: &Value) -> Vec<String> {
+    match skeleton {
+        Value::String(names) => names.split('|').map(str::to_string).collect(),
+        other => vec![type_name(other).to_string()],
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn builds_skeleton_and_merges_array_items() {
+        let value = json!({
+            "name": "Main Office",
+            "tags": [],
+            "aps": [
+                { "serial": "123", "model": "R750" },
+                { "serial": 456, "radio": { "channel": 36 } }
+            ],
+            "enabled": true,
+            "parent": null
+        });
+        assert_eq!(
+            schema_of(value),
+            json!({
+                "name": "string",
+                "tags": [],
+                "aps": [{ "serial": "number|string", "model": "string", "radio": { "channel": "number" } }],
+                "enabled": "boolean",
+                "parent": "null"
+            })
+        );
+    }
+
+    #[test]
+    fn diff_shows_added_removed_and_retyped_fields() {
+        let v1 = json!({ "name": "A", "vlan": 10, "legacy": true });
+        let v2 = json!({ "name": "B", "vlan": "10", "tags": ["x"] });
+        let diff = diff_schemas(v1, v2);
+        let paths = |entries: &[diff::DiffEntry]| entries.iter().map(|e| e.path.clone()).collect::<Vec<_>>();
+        assert_eq!(paths(&diff.added), ["tags"]);
+        assert_eq!(paths(&diff.removed), ["legacy"]);
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(diff.changed[0].path, "vlan");
+    }
+}
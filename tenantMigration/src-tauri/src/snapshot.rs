@@ -0,0 +1,573 @@
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use reqwest::Method;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter, Runtime, State};
+use tauri_plugin_fs::{FsExt, OpenOptions};
+use tokio_util::sync::CancellationToken;
+
+use crate::auth::Auth;
+use crate::client::AppClient;
+use crate::concurrency;
+use crate::error::MigrationError;
+use crate::files;
+use crate::migration::{self, FetchFailure, ProgressFn, PushSummary, ResourceKind, ResourceSelection};
+use crate::query::{self, Resource};
+use crate::request_id;
+use crate::state::AppState;
+use crate::tenants;
+use crate::transform::{self, TransformRule};
+use crate::validation::{self, ValidationProblem};
+
+// Bumped whenever the file layout changes; read_snapshot refuses newer versions
+pub const SNAPSHOT_VERSION: u64 = 1;
+
+const SNAPSHOT_KEYS: &[&str] = &["tenant", "venues", "wifi_networks", "aps"];
+const RECORD_KEYS: &[&str] = &["venues", "wifi_networks", "aps", "switches"];
+
+// On-disk format written by export_tenant: the tenant record plus every child
+// resource, exactly as returned by the API
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TenantSnapshot {
+    // Files exported before the field existed are version 1
+    #[serde(default = "first_version")]
+    pub snapshot_version: u64,
+    pub tenant: Value,
+    pub venues: Vec<Value>,
+    pub wifi_networks: Vec<Value>,
+    pub aps: Vec<Value>,
+    // Snapshots exported before switches were migrated don't have this key
+    #[serde(default)]
+    pub switches: Vec<Value>,
+}
+
+// Reads the tenant and its selected child resources. `cancel` is checked before every
+// page, so a cancelled run stops fetching within one request and fails with Cancelled.
+pub async fn fetch_snapshot(
+    client: &AppClient,
+    api_url: &str,
+    auth: &Auth,
+    tenant_id: &str,
+    selection: &ResourceSelection,
+    cancel: &CancellationToken,
+    on_progress: ProgressFn<'_>,
+) -> Result<TenantSnapshot, MigrationError> {
+    on_progress("fetch_tenant", 0, 1);
+    let tenant = query::fetch_tenant(client, api_url, auth, tenant_id).await?;
+    on_progress("fetch_tenant", 1, 1);
+
+    let scoped_id = query::scoped_tenant_id(&tenant, tenant_id);
+    // Unselected resource types are left empty without a request
+    let fetch = |resource: Resource| {
+        let scoped_id = scoped_id.clone();
+        async move {
+            if !selection.includes(resource) {
+                return Ok(Vec::new());
+            }
+            let _slot = client.fetch_slot().await;
+            on_progress(resource.fetch_stage(), 0, 0);
+            let records = query::fetch_resource_cancellable(client, api_url, auth, &scoped_id, resource, cancel).await?;
+            on_progress(resource.fetch_stage(), records.len(), records.len());
+            Ok::<_, MigrationError>(records)
+        }
+    };
+
+    // The four queries don't depend on each other (APs and switches are queried for
+    // the whole tenant, not per venue), so they run at once. Each pages one request at
+    // a time while holding one of the client's fetch slots (see AppClient::fetch_slot),
+    // and set_rate_limit applies to all of them.
+    let (venues, wifi_networks, aps, switches) = tokio::join!(
+        fetch(Resource::Venues),
+        fetch(Resource::WifiNetworks),
+        fetch(Resource::Aps),
+        fetch(Resource::Switches),
+    );
+    // Every branch stopped the same way; not a fetch failure
+    if cancel.is_cancelled() {
+        return Err(MigrationError::Cancelled { created: Vec::new() });
+    }
+    let mut failures = Vec::new();
+    let mut records = |resource: Resource, result: Result<Vec<Value>, MigrationError>| {
+        result.unwrap_or_else(|error| {
+            failures.push(FetchFailure { resource: resource.kind(), error });
+            Vec::new()
+        })
+    };
+    let snapshot = TenantSnapshot {
+        snapshot_version: SNAPSHOT_VERSION,
+        venues: records(Resource::Venues, venues),
+        wifi_networks: records(Resource::WifiNetworks, wifi_networks),
+        aps: records(Resource::Aps, aps),
+        switches: records(Resource::Switches, switches),
+        tenant,
+    };
+    match fetch_error(failures) {
+        Some(error) => Err(error),
+        None => Ok(snapshot),
+    }
+}
+
+// A single failure, or a token rejected by every query, is returned as is so callers
+// can still match on e.g. Auth; several different failures are listed together
+fn fetch_error(failures: Vec<FetchFailure>) -> Option<MigrationError> {
+    if failures.len() <= 1 || failures.iter().all(|f| matches!(f.error, MigrationError::Auth { .. })) {
+        return failures.into_iter().next().map(|f| f.error);
+    }
+    Some(MigrationError::FetchFailed { failures })
+}
+
+// Fetches the tenant with its venues, wifi networks, APs and switches and writes them to
+// `file_path` as pretty-printed JSON. Returns the number of bytes written.
+#[tauri::command]
+pub async fn export_tenant(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    api_url: Option<String>,
+    tenant_id: String,
+    auth: Auth,
+    file_path: String,
+) -> Result<u64, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let save = |path: &Path, contents: &[u8]| save_file(&app, path, contents);
+    write_snapshot(client, &config.api_url_or(api_url.as_deref())?, &auth, &tenant_id, Path::new(&file_path), &save).await
+}
+
+// Writes files through tauri_plugin_fs, as export_report_csv does
+type SaveFn<'a> = dyn Fn(&Path, &[u8]) -> Result<(), MigrationError> + Sync + 'a;
+
+fn save_file<R: Runtime>(app: &AppHandle<R>, path: &Path, contents: &[u8]) -> Result<(), MigrationError> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    let mut file = app.fs().open(path, options).map_err(|e| open_error(path, e))?;
+    file.write_all(contents).map_err(|e| MigrationError::io(path, e))
+}
+
+// Creating a file only fails with NotFound when a directory on the way is missing
+fn open_error(path: &Path, e: std::io::Error) -> MigrationError {
+    match path.parent() {
+        Some(parent) if e.kind() == std::io::ErrorKind::NotFound && !parent.as_os_str().is_empty() => {
+            MigrationError::Io { path: path.display().to_string(), message: format!("directory {} does not exist", parent.display()) }
+        }
+        _ => MigrationError::io(path, e),
+    }
+}
+
+async fn write_snapshot(client: &AppClient, api_url: &str, auth: &Auth, tenant_id: &str, path: &Path, save: &SaveFn<'_>) -> Result<u64, MigrationError> {
+    let snapshot = fetch_snapshot(client, api_url, auth, tenant_id, &ResourceSelection::default(), &CancellationToken::new(), &|_, _, _| {}).await?;
+    let json = to_json(&snapshot)?;
+
+    save(path, &json)?;
+
+    tracing::info!("Exported tenant {} to {} ({} bytes)", tenant_id, path.display(), json.len());
+    Ok(json.len() as u64)
+}
+
+// One tenant of export_all_tenants: where it went, or why it didn't
+#[derive(Clone, Debug, Serialize)]
+pub struct ExportedTenant {
+    pub tenant_id: String,
+    pub name: String,
+    pub file: String,
+    // None when the export failed
+    pub bytes: Option<u64>,
+    pub error: Option<MigrationError>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportManifest {
+    pub dir: String,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub tenants: Vec<ExportedTenant>,
+}
+
+// Payload of the "export-progress" event, sent as each tenant finishes
+#[derive(Clone, Serialize)]
+pub struct ExportProgress {
+    pub done: usize,
+    pub total: usize,
+    pub tenant: ExportedTenant,
+}
+
+// Backup of every tenant the token can see (see tenants::list_tenants): each one is
+// exported as by export_tenant to {dir_path}/{tenant_id}.json, at most `max_in_flight`
+// (default concurrency::DEFAULT_MAX_IN_FLIGHT) at a time. A tenant that fails is
+// recorded in the manifest and the rest carry on. The directory is created if needed.
+#[tauri::command]
+pub async fn export_all_tenants(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    api_url: Option<String>,
+    auth: Auth,
+    dir_path: String,
+    max_in_flight: Option<usize>,
+) -> Result<ExportManifest, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let emit = |progress: ExportProgress| {
+        let _ = app.emit("export-progress", progress);
+    };
+    let save = |path: &Path, contents: &[u8]| save_file(&app, path, contents);
+    let max_in_flight = max_in_flight.unwrap_or(concurrency::DEFAULT_MAX_IN_FLIGHT);
+    request_id::scope(request_id::new(), export_all(client, &config.api_url_or(api_url.as_deref())?, &auth, Path::new(&dir_path), max_in_flight, &save, &emit)).await
+}
+
+async fn export_all(
+    client: &AppClient,
+    api_url: &str,
+    auth: &Auth,
+    dir: &Path,
+    max_in_flight: usize,
+    save: &SaveFn<'_>,
+    emit: &(dyn Fn(ExportProgress) + Sync),
+) -> Result<ExportManifest, MigrationError> {
+    tokio::fs::create_dir_all(dir).await.map_err(|e| MigrationError::io(dir, e))?;
+    let listed = tenants::fetch_tenants(client, api_url, auth).await?;
+    let total = listed.len();
+    let done = AtomicUsize::new(0);
+
+    let exported = concurrency::try_map_bounded(listed, max_in_flight, |tenant| {
+        let done = &done;
+        async move {
+            let file = dir.join(format!("{}.json", files::label(&tenant.id)));
+            let result = write_snapshot(client, api_url, auth, &tenant.id, &file, save).await;
+            if let Err(e) = &result {
+                tracing::warn!("Could not export tenant {}: {}", tenant.id, e);
+            }
+            let (bytes, error) = match result {
+                Ok(bytes) => (Some(bytes), None),
+                Err(e) => (None, Some(e)),
+            };
+            let exported = ExportedTenant { tenant_id: tenant.id, name: tenant.name, file: file.display().to_string(), bytes, error };
+            emit(ExportProgress { done: done.fetch_add(1, Ordering::Relaxed) + 1, total, tenant: exported.clone() });
+            Ok(exported)
+        }
+    })
+    .await?;
+
+    let failed = exported.iter().filter(|t| t.error.is_some()).count();
+    tracing::info!("Exported {} of {} tenants to {}", total - failed, total, dir.display());
+    Ok(ExportManifest { dir: dir.display().to_string(), succeeded: total - failed, failed, tenants: exported })
+}
+
+// Payload of the "download-progress" event
+#[derive(Clone, Serialize)]
+pub struct DownloadProgress {
+    pub file_path: String,
+    pub bytes_written: u64,
+}
+
+// Saves the raw response of {api_url}/{path} to `file_path` without holding it in
+// memory (see AppClient::download), for exports of tenants with tens of thousands of
+// APs that are hundreds of MB as JSON. A `query` is POSTed, e.g. to venues/aps/query
+// with a large pageSize; without one the path is fetched with GET. Sends
+// "download-progress" events along the way and returns the bytes written.
+#[allow(clippy::too_many_arguments)]
+#[tauri::command]
+pub async fn download_export(
+    app: AppHandle,
+    state: State<'_, AppState>,
+    api_url: Option<String>,
+    tenant_id: Option<String>,
+    auth: Auth,
+    path: String,
+    query: Option<Value>,
+    file_path: String,
+) -> Result<u64, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let path = path.trim().trim_start_matches('/');
+    if path.is_empty() {
+        return Err(MigrationError::invalid_input("path must not be empty"));
+    }
+    if let Some(query) = &query {
+        validation::validate_query(query.clone())?;
+    }
+    let dest = Path::new(&file_path);
+    ensure_parent_dir(dest)?;
+
+    let url = format!("{}/{}", config.api_url_or(api_url.as_deref())?, path);
+    let method = if query.is_some() { Method::POST } else { Method::GET };
+    let on_progress = |bytes_written| {
+        let _ = app.emit("download-progress", DownloadProgress { file_path: file_path.clone(), bytes_written });
+    };
+    let written = request_id::scope(request_id::new(), client.download(method, &url, &auth, tenant_id.as_deref(), query.as_ref(), dest, &on_progress)).await?;
+    tracing::info!("Downloaded {} to {} ({} bytes)", url, dest.display(), written);
+    Ok(written)
+}
+
+// Replays a snapshot written by export_tenant against the target API: tenant first,
+// then venues, wifi networks, APs and switches. The summary counts what was created and, if a
+// resource failed, which one. `transforms` rewrite records on the way, as in migrate_tenant.
+#[tauri::command]
+pub async fn import_tenant(
+    state: State<'_, AppState>,
+    api_url: Option<String>,
+    auth: Auth,
+    file_path: String,
+    transforms: Option<Vec<TransformRule>>,
+) -> Result<PushSummary, MigrationError> {
+    let AppState { client, config, control } = state.inner();
+    let transforms = transforms.unwrap_or_default();
+    transform::validate(&transforms)?;
+    let snapshot = read_snapshot(Path::new(&file_path)).await?;
+
+    let tenant_payload = transform::apply(&transforms, ResourceKind::Tenant, migration::tenant_create_payload(&snapshot.tenant));
+    validation::validate_tenant_data(&tenant_payload)?;
+
+    // Imports can be stopped with cancel_migration like a migration run
+    let cancel = control.begin();
+    let api_url = &config.api_url_or(api_url.as_deref())?;
+    let summary = migration::push_snapshot(client, api_url, &auth, &snapshot, &tenant_payload, &transforms, &cancel, &|_, _, _| {}, None).await?;
+
+    tracing::info!(
+        "Imported {} into tenant {}: {} venues, {} wifi networks, {} APs, {} switches{}",
+        file_path,
+        summary.target_tenant_id,
+        summary.venues,
+        summary.wifi_networks,
+        summary.aps,
+        summary.switches,
+        if summary.failed.is_some() { " (stopped on failure)" } else { "" }
+    );
+    Ok(summary)
+}
+
+pub async fn read_snapshot(path: &Path) -> Result<TenantSnapshot, MigrationError> {
+    let raw = tokio::fs::read_to_string(path).await.map_err(|e| MigrationError::io(path, e))?;
+    let value: Value = serde_json::from_str(&raw)?;
+    check(&value)?;
+    Ok(serde_json::from_value(value)?)
+}
+
+// `snapshot` with the resource types `selection` leaves out emptied, as if
+// fetch_snapshot had been called with it
+pub fn select(snapshot: TenantSnapshot, selection: &ResourceSelection) -> TenantSnapshot {
+    let keep = |resource, records: Vec<Value>| if selection.includes(resource) { records } else { Vec::new() };
+    TenantSnapshot {
+        venues: keep(Resource::Venues, snapshot.venues),
+        wifi_networks: keep(Resource::WifiNetworks, snapshot.wifi_networks),
+        aps: keep(Resource::Aps, snapshot.aps),
+        switches: keep(Resource::Switches, snapshot.switches),
+        ..snapshot
+    }
+}
+
+// Pretty-printed file contents, checked the same way read_snapshot checks them so a
+// bad record is caught at export time rather than when the file is imported
+pub fn to_json(snapshot: &TenantSnapshot) -> Result<Vec<u8>, MigrationError> {
+    check(&serde_json::to_value(snapshot)?)?;
+    Ok(serde_json::to_vec_pretty(snapshot)?)
+}
+
+fn first_version() -> u64 {
+    1
+}
+
+fn check(value: &Value) -> Result<(), MigrationError> {
+    let problems = problems(value);
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(MigrationError::Validation { problems })
+    }
+}
+
+// Every way `value` departs from the snapshot format, with the path of the offending
+// field (e.g. "aps[3].serialNumber"). Records are otherwise kept as the API returned them.
+fn problems(value: &Value) -> Vec<ValidationProblem> {
+    let Some(map) = value.as_object() else {
+        return vec![ValidationProblem::new("snapshot", "must be a JSON object")];
+    };
+
+    let mut problems: Vec<ValidationProblem> = SNAPSHOT_KEYS
+        .iter()
+        .filter(|key| !map.contains_key(**key))
+        .map(|key| ValidationProblem::new(*key, "missing from snapshot"))
+        .collect();
+
+    for (key, value) in map {
+        match key.as_str() {
+            "snapshot_version" => match value.as_u64() {
+                Some(version) if (1..=SNAPSHOT_VERSION).contains(&version) => {}
+                Some(version) => problems.push(ValidationProblem::new(
+                    key,
+                    format!("version {} is not supported by this build (up to {})", version, SNAPSHOT_VERSION),
+                )),
+                None => problems.push(ValidationProblem::new(key, "must be a positive integer")),
+            },
+            "tenant" => {
+                if !value.is_object() {
+                    problems.push(ValidationProblem::new(key, "must be an object"));
+                } else if !value.get("name").is_some_and(Value::is_string) {
+                    problems.push(ValidationProblem::new("tenant.name", "must be a string"));
+                }
+            }
+            key if RECORD_KEYS.contains(&key) => record_problems(key, value, &mut problems),
+            _ => problems.push(ValidationProblem::new(key, "unknown snapshot key")),
+        }
+    }
+    problems
+}
+
+fn record_problems(key: &str, value: &Value, problems: &mut Vec<ValidationProblem>) {
+    let Some(records) = value.as_array() else {
+        return problems.push(ValidationProblem::new(key, "must be an array"));
+    };
+    for (i, record) in records.iter().enumerate() {
+        if !record.is_object() {
+            problems.push(ValidationProblem::new(format!("{}[{}]", key, i), "must be an object"));
+        } else if matches!(key, "aps" | "switches") && !record.get("serialNumber").is_some_and(Value::is_string) {
+            // Devices are onboarded by serial number
+            problems.push(ValidationProblem::new(format!("{}[{}].serialNumber", key, i), "must be a string"));
+        }
+    }
+}
+
+fn ensure_parent_dir(path: &Path) -> Result<(), MigrationError> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() && !parent.is_dir() => Err(MigrationError::Io {
+            path: path.display().to_string(),
+            message: format!("directory {} does not exist", parent.display()),
+        }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use serde_json::json;
+
+    use crate::backend::mock::MockBackend;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn export_all_carries_on_past_a_failed_tenant() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(200, r#"{"data":[{"id":"t1","name":"One"},{"id":"t2","name":"Two"}],"totalCount":2}"#)
+            .respond(404, "")
+            .respond(200, r#"{"id":"t2","name":"Two"}"#);
+        let client = AppClient::with_backend(mock.clone()).unwrap();
+        let dir = std::env::temp_dir().join(format!("export-{}", uuid::Uuid::new_v4()));
+
+        let seen = Mutex::new(Vec::new());
+        let emit = |progress: ExportProgress| seen.lock().unwrap().push(progress.done);
+        let save = |path: &Path, contents: &[u8]| std::fs::write(path, contents).map_err(|e| open_error(path, e));
+        let manifest = export_all(&client, "https://api.example.test", &Auth::Bearer("token".to_string()), &dir, 1, &save, &emit).await.unwrap();
+
+        assert_eq!((manifest.succeeded, manifest.failed), (1, 1));
+        assert!(manifest.tenants[0].error.is_some());
+        assert!(manifest.tenants[1].bytes.is_some_and(|bytes| bytes > 0));
+        assert!(dir.join("t2.json").is_file());
+        assert_eq!(seen.into_inner().unwrap(), [1, 2]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn resource_fetches_wait_for_a_fetch_slot() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(200, r#"{"id":"t1","name":"One"}"#);
+        let client = AppClient::with_backend(mock.clone()).unwrap();
+        let held = futures::future::join_all((0..concurrency::DEFAULT_MAX_IN_FLIGHT).map(|_| client.fetch_slot())).await;
+
+        let auth = Auth::Bearer("token".to_string());
+        let (selection, cancel) = (ResourceSelection::default(), CancellationToken::new());
+        let fetch = fetch_snapshot(&client, "https://api.example.test", &auth, "t1", &selection, &cancel, &|_, _, _| {});
+        tokio::pin!(fetch);
+        assert!(tokio::time::timeout(std::time::Duration::from_millis(50), &mut fetch).await.is_err());
+        // The tenant was read, none of its resources
+        assert_eq!(mock.requests().len(), 1);
+
+        drop(held);
+        assert!(fetch.await.is_ok());
+        assert_eq!(mock.requests().len(), 5);
+    }
+
+    #[test]
+    fn saving_into_a_missing_directory_names_it() {
+        let dir = std::env::temp_dir().join(format!("missing-{}", uuid::Uuid::new_v4()));
+        let path = dir.join("t1.json");
+        let e = std::fs::File::create(&path).unwrap_err();
+        match open_error(&path, e) {
+            MigrationError::Io { message, .. } => assert_eq!(message, format!("directory {} does not exist", dir.display())),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn download_streams_the_body_to_the_file() {
+        let mock = Arc::new(MockBackend::default());
+        let body = format!("[{}]", vec![r#"{"serialNumber":"AP-1"}"#; 1000].join(","));
+        mock.respond(200, &body).respond(500, "oops");
+        let client = AppClient::with_backend(mock.clone()).unwrap();
+        let dest = std::env::temp_dir().join(format!("download-{}.json", uuid::Uuid::new_v4()));
+
+        let seen = Mutex::new(Vec::new());
+        let written = client
+            .download(Method::POST, "https://api.example.test/venues/aps/query", &Auth::Bearer("token".to_string()), Some("t1"), Some(&json!({})), &dest, &|bytes| seen.lock().unwrap().push(bytes))
+            .await
+            .unwrap();
+        assert_eq!(written, body.len() as u64);
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), body);
+        assert_eq!(seen.into_inner().unwrap().last(), Some(&written));
+        assert_eq!(mock.requests()[0].headers["x-rks-tenantid"], "t1");
+
+        // A failed download leaves the earlier file alone and no .part behind
+        let failed = client.download(Method::GET, "https://api.example.test/venues", &Auth::Bearer("token".to_string()), None, None, &dest, &|_| {}).await;
+        assert!(matches!(failed, Err(MigrationError::Http { status: 500, .. })));
+        assert_eq!(std::fs::read_to_string(&dest).unwrap(), body);
+        std::fs::remove_file(&dest).unwrap();
+    }
+
+    #[test]
+    fn problems_name_the_offending_path() {
+        let value = json!({
+            "snapshot_version": 2,
+            "tenant": { "name": "Acme" },
+            "venues": [{ "name": "HQ" }, "HQ"],
+            "aps": [{ "name": "Lobby" }],
+            "extra": true
+        });
+        let mut found: Vec<String> = problems(&value).into_iter().map(|p| p.field).collect();
+        found.sort();
+        assert_eq!(found, ["aps[0].serialNumber", "extra", "snapshot_version", "venues[1]", "wifi_networks"]);
+    }
+
+    #[test]
+    fn unversioned_snapshots_read_as_version_1() {
+        let value = json!({ "tenant": { "name": "Acme" }, "venues": [], "wifi_networks": [], "aps": [] });
+        assert!(problems(&value).is_empty());
+        let snapshot: TenantSnapshot = serde_json::from_value(value).unwrap();
+        assert_eq!(snapshot.snapshot_version, 1);
+    }
+
+    #[test]
+    fn select_empties_unselected_resources() {
+        let value = json!({ "tenant": { "name": "Acme" }, "venues": [{ "id": "v1" }], "wifi_networks": [{ "id": "n1" }], "aps": [], "switches": [{ "serialNumber": "s1" }] });
+        let snapshot: TenantSnapshot = serde_json::from_value(value).unwrap();
+        let selection = ResourceSelection { wifi_networks: false, switches: false, ..ResourceSelection::default() };
+
+        let selected = select(snapshot, &selection);
+        assert_eq!((selected.venues.len(), selected.wifi_networks.len(), selected.switches.len()), (1, 0, 0));
+    }
+
+    #[test]
+    fn distinct_fetch_failures_are_all_reported() {
+        let failure = |resource, status| FetchFailure {
+            resource,
+            error: MigrationError::Http { status, body: String::new(), attempts: 1, api_error: None, request_id: None },
+        };
+        assert!(fetch_error(Vec::new()).is_none());
+        assert!(matches!(fetch_error(vec![failure(ResourceKind::Ap, 500)]), Some(MigrationError::Http { status: 500, .. })));
+
+        let Some(MigrationError::FetchFailed { failures }) = fetch_error(vec![failure(ResourceKind::Venue, 500), failure(ResourceKind::Ap, 404)]) else {
+            panic!("expected FetchFailed");
+        };
+        assert_eq!(failures.iter().map(|f| f.resource).collect::<Vec<_>>(), [ResourceKind::Venue, ResourceKind::Ap]);
+    }
+}
@@ -0,0 +1,20 @@
+use crate::client::AppClient;
+use crate::config::ConfigStore;
+use crate::migration::MigrationControl;
+
+// The app's shared state, registered once with .manage() and taken by commands as
+// State<'_, AppState>. Each part does its own locking: the HTTP client keeps its
+// settings (timeout, proxy, language, ...) behind RwLocks, the config sits in
+// ConfigStore's RwLock and the token of the run in progress in MigrationControl's
+// Mutex. None of those locks is held across an await.
+pub struct AppState {
+    pub client: AppClient,
+    pub config: ConfigStore,
+    pub control: MigrationControl,
+}
+
+impl AppState {
+    pub fn new() -> Result<Self, reqwest::Error> {
+        Ok(Self { client: AppClient::new()?, config: ConfigStore::default(), control: MigrationControl::default() })
+    }
+}
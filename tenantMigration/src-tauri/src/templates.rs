@@ -0,0 +1,73 @@
+use serde_json::Value;
+
+use crate::error::MigrationError;
+
+// Known-good /query bodies for the UI's query editor to start from. Field lists are
+// those of query::Resource::default_query, with the first page added. A template
+// edited here must still pass validate_query; the test below checks that.
+
+pub const VENUES: &str = r#"{
+    "fields": ["name", "description", "city", "country", "latitude", "longitude", "id", "addressLine", "tagList"],
+    "searchString": "",
+    "filters": {},
+    "sortField": "name",
+    "sortOrder": "ASC",
+    "page": 1,
+    "pageSize": 100
+}"#;
+
+pub const WIFI_NETWORKS: &str = r#"{
+    "fields": ["name", "description", "nwSubType", "vlan", "ssid", "vlanPool", "captiveType", "id", "securityProtocol", "tunnelWlanEnable"],
+    "searchString": "",
+    "filters": {},
+    "sortField": "name",
+    "sortOrder": "ASC",
+    "page": 1,
+    "pageSize": 100
+}"#;
+
+pub const APS: &str = r#"{
+    "fields": ["serialNumber", "name", "venueId", "description", "model"],
+    "searchString": "",
+    "filters": {},
+    "sortField": "name",
+    "sortOrder": "ASC",
+    "page": 1,
+    "pageSize": 100
+}"#;
+
+// Names query_template accepts for each template
+const TEMPLATES: &[(&[&str], &str)] = &[
+    (&["venues", "venue"], VENUES),
+    (&["networks", "network", "wifi_networks", "wifiNetworks"], WIFI_NETWORKS),
+    (&["aps", "ap"], APS),
+];
+
+// The canonical query body for `resource` ("venues", "networks" or "aps")
+#[tauri::command]
+pub fn query_template(resource: String) -> Result<Value, MigrationError> {
+    let resource = resource.trim();
+    let template = TEMPLATES
+        .iter()
+        .find(|(names, _)| names.iter().any(|name| name.eq_ignore_ascii_case(resource)))
+        .map(|(_, template)| *template)
+        .ok_or_else(|| MigrationError::invalid_input(format!("no query template for {:?}; expected venues, networks or aps", resource)))?;
+    Ok(serde_json::from_str(template)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::Resource;
+    use crate::validation::validate_query;
+
+    #[test]
+    fn templates_are_valid_and_match_the_default_fields() {
+        for (name, resource) in [("venues", Resource::Venues), ("Networks", Resource::WifiNetworks), ("aps", Resource::Aps)] {
+            let template = query_template(name.to_string()).unwrap();
+            validate_query(template.clone()).unwrap();
+            assert_eq!(template["fields"], resource.default_query()["fields"], "{}", name);
+        }
+        assert!(query_template("switches".to_string()).is_err());
+    }
+}
@@ -0,0 +1,108 @@
+use reqwest::Method;
+use serde::Serialize;
+use serde_json::Value;
+use tauri::State;
+
+use crate::auth::Auth;
+use crate::client::AppClient;
+use crate::error::MigrationError;
+use crate::query;
+use crate::state::AppState;
+
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct TenantSummary {
+    pub id: String,
+    pub name: String,
+}
+
+// Every tenant the token can see, for the tenant picker. MSP tokens list their
+// customers from /mspCustomers page by page; a token scoped to one tenant gets a
+// 403/404 there and is answered with just its own tenant from /tenants/self
+// (or wherever detect_capabilities found tenant records).
+// A missing or empty api_url falls back to the saved config.
+#[tauri::command]
+pub async fn list_tenants(state: State<'_, AppState>, api_url: Option<String>, auth: Auth) -> Result<Vec<TenantSummary>, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let api_url = config.api_url_or(api_url.as_deref())?;
+    fetch_tenants(client, &api_url, &auth).await
+}
+
+pub async fn fetch_tenants(client: &AppClient, api_url: &str, auth: &Auth) -> Result<Vec<TenantSummary>, MigrationError> {
+    match fetch_msp_customers(client, api_url, auth).await {
+        Err(MigrationError::Auth { status: 403, .. }) | Err(MigrationError::Http { status: 404, .. }) => {
+            tracing::debug!("Token can't list MSP customers, returning its own tenant");
+            let tenant = client.send_json(Method::GET, &format!("{}/{}/self", api_url, client.paths(api_url).tenant_records), auth, None, None).await?;
+            Ok(summary(&tenant).into_iter().collect())
+        }
+        result => result,
+    }
+}
+
+async fn fetch_msp_customers(client: &AppClient, api_url: &str, auth: &Auth) -> Result<Vec<TenantSummary>, MigrationError> {
+    let page_size = query::DEFAULT_PAGE_SIZE;
+    let mut tenants = Vec::new();
+    for page in 1..=query::MAX_PAGES {
+        let url = format!("{}/{}?page={}&pageSize={}", api_url, client.paths(api_url).tenants, page, page_size);
+        let response = client.send_json(Method::GET, &url, auth, None, None).await?;
+
+        // Older API versions return a bare array instead of a page object
+        let batch = match &response {
+            Value::Array(records) => records.clone(),
+            _ => query::records(&response),
+        };
+        let batch_len = batch.len() as u64;
+        tenants.extend(batch.iter().filter_map(summary));
+
+        let done = match response.get("totalCount").and_then(Value::as_u64) {
+            Some(total) => page * page_size >= total || batch_len == 0,
+            None => batch_len < page_size,
+        };
+        if done {
+            return Ok(tenants);
+        }
+    }
+
+    Err(MigrationError::unexpected_response(format!("tenant list did not finish after {} pages", query::MAX_PAGES)))
+}
+
+// Records without an id can't be picked, so they're left out
+fn summary(record: &Value) -> Option<TenantSummary> {
+    let field = |names: &[&str]| names.iter().find_map(|name| record.get(*name).and_then(Value::as_str)).map(str::to_string);
+    let id = field(&["id", "tenant_id", "tenantId"])?;
+    Some(TenantSummary { name: field(&["name"]).unwrap_or_else(|| id.clone()), id })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::backend::mock::MockBackend;
+
+    const API: &str = "https://api.example.test";
+
+    #[tokio::test]
+    async fn lists_msp_customers_across_pages() {
+        let mock = Arc::new(MockBackend::default());
+        let first_page: Vec<Value> = (0..query::DEFAULT_PAGE_SIZE).map(|i| serde_json::json!({ "id": format!("t{}", i), "name": "Customer" })).collect();
+        mock.respond(200, &serde_json::json!({ "data": first_page }).to_string())
+            .respond(200, r#"{"data":[{"id":"last","name":"Last Customer"}]}"#);
+        let client = AppClient::with_backend(mock.clone()).unwrap();
+
+        let tenants = fetch_tenants(&client, API, &Auth::Bearer("token".to_string())).await.unwrap();
+        assert_eq!(tenants.len() as u64, query::DEFAULT_PAGE_SIZE + 1);
+        assert_eq!(tenants.last(), Some(&TenantSummary { id: "last".to_string(), name: "Last Customer".to_string() }));
+        assert!(mock.requests()[1].url.ends_with("/mspCustomers?page=2&pageSize=500"));
+    }
+
+    #[tokio::test]
+    async fn single_tenant_token_gets_its_own_tenant() {
+        let mock = Arc::new(MockBackend::default());
+        mock.respond(403, "forbidden").respond(200, r#"{"id":"own","name":"Own Tenant"}"#);
+        let client = AppClient::with_backend(mock.clone()).unwrap();
+
+        let tenants = fetch_tenants(&client, API, &Auth::Bearer("token".to_string())).await.unwrap();
+        assert_eq!(tenants, [TenantSummary { id: "own".to_string(), name: "Own Tenant".to_string() }]);
+        assert_eq!(mock.requests()[1].url, format!("{}/tenants/self", API));
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use reqwest::{Method, Url};
+use serde::Serialize;
+
+tokio::task_local! {
+    // Set for a migration so every call's timing is added to its summary
+    static CURRENT: Arc<TimingCollector>;
+}
+
+// Timing of one call made through AppClient. reqwest has no hooks for DNS or connect,
+// so those are inside `ttfb_ms` (request sent to response headers), together with
+// TLS and server time. `total_ms` also covers retries and their backoff.
+#[derive(Clone, Copy, Debug, Default, Serialize)]
+pub struct CallTiming {
+    pub ttfb_ms: u64,
+    pub body_ms: u64,
+    pub total_ms: u64,
+}
+
+impl CallTiming {
+    pub fn new(ttfb: Duration, body: Duration, total: Duration) -> Self {
+        Self { ttfb_ms: millis(ttfb), body_ms: millis(body), total_ms: millis(total) }
+    }
+}
+
+// Calls to one endpoint added up, e.g. all "POST /venues/{id}/aps" of a migration
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct Timings {
+    pub endpoint: String,
+    pub calls: u32,
+    pub ttfb_ms: u64,
+    pub body_ms: u64,
+    pub total_ms: u64,
+    pub slowest_ms: u64,
+}
+
+#[derive(Default)]
+pub struct TimingCollector {
+    endpoints: Mutex<HashMap<String, Timings>>,
+}
+
+impl TimingCollector {
+    // Slowest endpoint first, which is usually the answer to "where did the time go"
+    pub fn summary(&self) -> Vec<Timings> {
+        let mut timings: Vec<Timings> = self.endpoints.lock().unwrap_or_else(PoisonError::into_inner).values().cloned().collect();
+        timings.sort_by_key(|t| std::cmp::Reverse(t.total_ms));
+        timings
+    }
+
+    fn add(&self, endpoint: String, timing: CallTiming) {
+        let mut endpoints = self.endpoints.lock().unwrap_or_else(PoisonError::into_inner);
+        let entry = endpoints.entry(endpoint.clone()).or_insert_with(|| Timings { endpoint, ..Timings::default() });
+        entry.calls += 1;
+        entry.ttfb_ms += timing.ttfb_ms;
+        entry.body_ms += timing.body_ms;
+        entry.total_ms += timing.total_ms;
+        entry.slowest_ms = entry.slowest_ms.max(timing.total_ms);
+    }
+}
+
+// Runs `f` with every call it makes added to `collector`
+pub async fn scope<F: Future>(collector: Arc<TimingCollector>, f: F) -> F::Output {
+    CURRENT.scope(collector, f).await
+}
+
+// Logs the call and adds it to the surrounding scope(), if any
+pub fn record(method: &Method, url: &Url, status: Option<u16>, timing: CallTiming) {
+    tracing::debug!(
+        method = %method,
+        url = %url,
+        status,
+        ttfb_ms = timing.ttfb_ms,
+        body_ms = timing.body_ms,
+        total_ms = timing.total_ms,
+        "HTTP call finished"
+    );
+    let _ = CURRENT.try_with(|collector| collector.add(endpoint(method, url), timing));
+}
+
+// Method plus path with ids replaced, so calls for different venues group together
+fn endpoint(method: &Method, url: &Url) -> String {
+    let path: Vec<&str> = url
+        .path_segments()
+        .map(|segments| segments.map(|s| if looks_like_id(s) { "{id}" } else { s }).collect())
+        .unwrap_or_default();
+    format!("{} /{}", method, path.join("/"))
+}
+
+// R1 ids are 32-char hex, UUIDs or serial numbers; endpoint names have no digits
+fn looks_like_id(segment: &str) -> bool {
+    segment.len() >= 6 && segment.chars().any(|c| c.is_ascii_digit())
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_calls_by_endpoint_without_ids() {
+        let collector = TimingCollector::default();
+        let url = |path: &str| Url::parse(&format!("https://api.example.test{}", path)).unwrap();
+        let timing = |total| CallTiming { ttfb_ms: 10, body_ms: 5, total_ms: total };
+
+        collector.add(endpoint(&Method::POST, &url("/venues/9f8e7d6c5b4a39281706f5e4d3c2b1a0/aps")), timing(40));
+        collector.add(endpoint(&Method::POST, &url("/venues/0a1b2c3d4e5f60718293a4b5c6d7e8f9/aps")), timing(60));
+        collector.add(endpoint(&Method::POST, &url("/venues/aps/query")), timing(30));
+
+        let summary = collector.summary();
+        assert_eq!(summary.len(), 2);
+        assert_eq!(summary[0].endpoint, "POST /venues/{id}/aps");
+        assert_eq!((summary[0].calls, summary[0].ttfb_ms, summary[0].total_ms, summary[0].slowest_ms), (2, 20, 100, 60));
+        assert_eq!(summary[1].endpoint, "POST /venues/aps/query");
+    }
+}
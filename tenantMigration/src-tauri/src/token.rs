@@ -0,0 +1,51 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::MigrationError;
+
+#[derive(Clone, Debug, Serialize)]
+pub struct TokenInfo {
+    // Unix timestamp (seconds) from the `exp` claim
+    pub expires_at: u64,
+    pub expired: bool,
+    // Negative once the token has expired
+    pub seconds_remaining: i64,
+}
+
+// Reads the expiry out of a JWT so the UI can warn before a long job. The signature
+// is not checked - the API does that - this only decodes the payload.
+#[tauri::command]
+pub fn token_info(token: String) -> Result<TokenInfo, MigrationError> {
+    let expires_at = expiry(&token)?;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let seconds_remaining = expires_at as i64 - now as i64;
+
+    Ok(TokenInfo { expires_at, expired: seconds_remaining <= 0, seconds_remaining })
+}
+
+fn expiry(token: &str) -> Result<u64, MigrationError> {
+    let token = token.trim();
+    let token = token.strip_prefix("Bearer ").unwrap_or(token).trim();
+
+    let parts: Vec<&str> = token.split('.').collect();
+    if parts.len() != 3 {
+        return Err(MigrationError::invalid_input("token is not a JWT (expected three dot-separated parts)"));
+    }
+
+    // Some issuers pad their base64url; the spec says not to
+    let payload = URL_SAFE_NO_PAD
+        .decode(parts[1].trim_end_matches('='))
+        .map_err(|e| MigrationError::invalid_input(format!("JWT payload is not valid base64url: {}", e)))?;
+    let claims: Value = serde_json::from_slice(&payload)
+        .map_err(|e| MigrationError::invalid_input(format!("JWT payload is not JSON: {}", e)))?;
+
+    match claims.get("exp").and_then(Value::as_f64) {
+        Some(exp) if exp >= 0.0 => Ok(exp as u64),
+        Some(_) => Err(MigrationError::invalid_input("JWT `exp` claim is negative")),
+        None => Err(MigrationError::invalid_input("JWT has no numeric `exp` claim")),
+    }
+}
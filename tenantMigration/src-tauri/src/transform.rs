@@ -0,0 +1,214 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::error::MigrationError;
+use crate::migration::ResourceKind;
+use crate::validation::ValidationProblem;
+
+// One rewrite applied to resources before they're POSTed to the target, passed from
+// the frontend as e.g. { "resource": "venue", "path": "address.timezone", "op": "replace",
+// "value": "Europe/London" }. Without `resource` the rule applies to every kind.
+//
+// `path` is dot-separated keys from the record root, optionally starting with "$.";
+// a number picks an array element and "*" matches every key or element, so
+// "tags.*" is every tag and "radios.*.channel" every radio's channel. Paths that don't
+// exist in a record are skipped.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TransformRule {
+    #[serde(default)]
+    pub resource: Option<ResourceKind>,
+    pub path: String,
+    #[serde(flatten)]
+    pub action: TransformAction,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum TransformAction {
+    Remove,
+    // Moves the value to key `to` in the same object
+    Rename { to: String },
+    // Overwrites the existing value
+    Replace { value: Value },
+    // Replaces every occurrence of `from` in a string value, e.g. a domain suffix
+    ReplaceText { from: String, to: String },
+}
+
+// Checked before a run starts so a typo doesn't surface halfway through the push
+pub fn validate(rules: &[TransformRule]) -> Result<(), MigrationError> {
+    let mut problems = Vec::new();
+    for (i, rule) in rules.iter().enumerate() {
+        let field = format!("transforms[{}]", i);
+        let segments = segments(&rule.path);
+        if segments.is_empty() || segments.iter().any(|s| s.is_empty()) {
+            problems.push(ValidationProblem::new(&field, "path must be dot-separated keys, e.g. address.timezone"));
+            continue;
+        }
+        match &rule.action {
+            TransformAction::Rename { to } if to.is_empty() || to.contains('.') => {
+                problems.push(ValidationProblem::new(&field, "rename target must be a single key"))
+            }
+            TransformAction::Rename { .. } if segments.last() == Some(&"*") => {
+                problems.push(ValidationProblem::new(&field, "cannot rename a wildcard"))
+            }
+            TransformAction::ReplaceText { from, .. } if from.is_empty() => {
+                problems.push(ValidationProblem::new(&field, "replace_text needs a non-empty `from`"))
+            }
+            _ => {}
+        }
+    }
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(MigrationError::Validation { problems })
+    }
+}
+
+// Applies the rules for `kind` to `record` in order
+pub fn apply(rules: &[TransformRule], kind: ResourceKind, mut record: Value) -> Value {
+    for rule in rules.iter().filter(|rule| rule.resource.is_none_or(|resource| resource == kind)) {
+        apply_path(&mut record, &segments(&rule.path), &rule.action);
+    }
+    record
+}
+
+fn segments(path: &str) -> Vec<&str> {
+    let path = path.trim();
+    let path = path.strip_prefix("$.").or_else(|| path.strip_prefix('$')).unwrap_or(path);
+    if path.is_empty() {
+        Vec::new()
+    } else {
+        path.split('.').collect()
+    }
+}
+
+fn apply_path(value: &mut Value, path: &[&str], action: &TransformAction) {
+    match path {
+        [] => {}
+        [key] => apply_leaf(value, key, action),
+        [key, rest @ ..] => {
+            for child in children_mut(value, key) {
+                apply_path(child, rest, action);
+            }
+        }
+    }
+}
+
+fn apply_leaf(parent: &mut Value, key: &str, action: &TransformAction) {
+    match action {
+        TransformAction::Remove => match parent {
+            Value::Object(map) if key == "*" => map.clear(),
+            Value::Object(map) => {
+                map.remove(key);
+            }
+            Value::Array(items) if key == "*" => items.clear(),
+            Value::Array(items) => {
+                if let Some(i) = key.parse::<usize>().ok().filter(|i| *i < items.len()) {
+                    items.remove(i);
+                }
+            }
+            _ => {}
+        },
+        TransformAction::Rename { to } => {
+            if let Value::Object(map) = parent {
+                if let Some(value) = map.remove(key) {
+                    map.insert(to.clone(), value);
+                }
+            }
+        }
+        TransformAction::Replace { value } => {
+            for child in children_mut(parent, key) {
+                *child = value.clone();
+            }
+        }
+        TransformAction::ReplaceText { from, to } => {
+            for child in children_mut(parent, key) {
+                if let Value::String(text) = child {
+                    *text = text.replace(from.as_str(), to);
+                }
+            }
+        }
+    }
+}
+
+fn children_mut<'a>(value: &'a mut Value, key: &str) -> Vec<&'a mut Value> {
+    match value {
+        Value::Object(map) => {
+            if key == "*" {
+                map.values_mut().collect()
+            } else {
+                map.get_mut(key).into_iter().collect()
+            }
+        }
+        Value::Array(items) => {
+            if key == "*" {
+                items.iter_mut().collect()
+            } else {
+                key.parse::<usize>().ok().and_then(|i| items.get_mut(i)).into_iter().collect()
+            }
+        }
+        _ => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn rules(value: Value) -> Vec<TransformRule> {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn applies_rules_in_order_to_matching_kind() {
+        let rules = rules(json!([
+            { "path": "$.externalId", "op": "remove" },
+            { "resource": "venue", "path": "address.timezone", "op": "replace", "value": "Europe/London" },
+            { "resource": "venue", "path": "contact", "op": "rename", "to": "primaryContact" },
+            { "path": "admins.*.email", "op": "replace_text", "from": "@old.test", "to": "@new.test" }
+        ]));
+        validate(&rules).unwrap();
+
+        let venue = json!({
+            "externalId": "src-1",
+            "address": { "timezone": "America/New_York" },
+            "contact": "Ada",
+            "admins": [{ "email": "a@old.test" }, { "email": "b@other.test" }]
+        });
+        assert_eq!(
+            apply(&rules, ResourceKind::Venue, venue.clone()),
+            json!({
+                "address": { "timezone": "Europe/London" },
+                "primaryContact": "Ada",
+                "admins": [{ "email": "a@new.test" }, { "email": "b@other.test" }]
+            })
+        );
+
+        // Venue-only rules leave other kinds alone
+        let network = apply(&rules, ResourceKind::WifiNetwork, venue);
+        assert_eq!(network["address"]["timezone"], "America/New_York");
+        assert_eq!(network["contact"], "Ada");
+        assert!(network.get("externalId").is_none());
+    }
+
+    #[test]
+    fn missing_paths_are_skipped() {
+        let rules = rules(json!([{ "path": "a.b.c", "op": "replace", "value": 1 }]));
+        assert_eq!(apply(&rules, ResourceKind::Ap, json!({ "a": 5 })), json!({ "a": 5 }));
+    }
+
+    #[test]
+    fn rejects_unusable_rules() {
+        let rules = rules(json!([
+            { "path": "", "op": "remove" },
+            { "path": "name", "op": "rename", "to": "a.b" },
+            { "path": "tags.*", "op": "rename", "to": "x" }
+        ]));
+        let Err(MigrationError::Validation { problems }) = validate(&rules) else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(problems.len(), 3);
+    }
+}
@@ -0,0 +1,163 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::error::MigrationError;
+
+// Fields /mspCustomers rejects the request without (mirrors the required entries of
+// TENANT_FIELD_CONFIGS in the frontend). Update both when the API changes.
+pub const REQUIRED_TENANT_FIELDS: &[&str] = &[
+    "name",
+    "tenant_type",
+    "service_effective_date",
+    "service_expiration_date",
+    "admin_email",
+    "admin_firstname",
+    "admin_lastname",
+    "admin_role",
+];
+
+#[derive(Clone, Debug, Serialize)]
+pub struct ValidationProblem {
+    pub field: String,
+    pub message: String,
+}
+
+impl ValidationProblem {
+    pub fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { field: field.into(), message: message.into() }
+    }
+}
+
+// Checks a tenant create payload locally so a missing field fails fast with the
+// full list of problems instead of an opaque 400 after the round trip
+pub fn validate_tenant_data(tenant_data: &Value) -> Result<(), MigrationError> {
+    let Some(object) = tenant_data.as_object() else {
+        return Err(MigrationError::Validation {
+            problems: vec![ValidationProblem::new("", "tenant_data must be a JSON object")],
+        });
+    };
+
+    let problems: Vec<ValidationProblem> = REQUIRED_TENANT_FIELDS
+        .iter()
+        .filter_map(|field| match object.get(*field) {
+            None | Some(Value::Null) => Some(ValidationProblem::new(*field, "missing required field")),
+            Some(Value::String(s)) if s.trim().is_empty() => Some(ValidationProblem::new(*field, "required field is empty")),
+            _ => None,
+        })
+        .collect();
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(MigrationError::Validation { problems })
+    }
+}
+
+// Assigned by R1 and never changed through an update
+const IMMUTABLE_TENANT_FIELDS: &[&str] = &["id", "tenant_id", "tenantId", "createdDate", "updatedDate"];
+
+// A tenant PATCH body: a non-empty object that leaves the server-assigned fields alone
+pub fn validate_tenant_patch(partial: &Value) -> Result<(), MigrationError> {
+    let problems = match partial.as_object() {
+        None => vec![ValidationProblem::new("", "partial must be a JSON object")],
+        Some(object) if object.is_empty() => vec![ValidationProblem::new("", "partial has no fields to update")],
+        Some(object) => object
+            .keys()
+            .filter(|key| IMMUTABLE_TENANT_FIELDS.contains(&key.as_str()))
+            .map(|key| ValidationProblem::new(key, "field cannot be changed"))
+            .collect(),
+    };
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(MigrationError::Validation { problems })
+    }
+}
+
+// Top-level keys the R1 /query endpoints take (see the *QueryParams interfaces in api.ts)
+const QUERY_KEYS: &[&str] = &[
+    "fields", "searchString", "searchTargetFields", "filters", "groupFilters",
+    "sortField", "sortOrder", "page", "pageSize", "defaultPageSize", "total",
+];
+// Larger pages are refused or time out; fetch_all_pages uses 500
+pub const MAX_QUERY_PAGE_SIZE: u64 = 10_000;
+
+// Checks the common shape of a /query body so a typo fails locally with every problem
+// listed instead of a bare 400. A null query is fine, the endpoints accept an empty body.
+#[tauri::command]
+pub fn validate_query(query_data: Value) -> Result<(), MigrationError> {
+    let object = match &query_data {
+        Value::Null => return Ok(()),
+        Value::Object(object) => object,
+        _ => {
+            return Err(MigrationError::Validation {
+                problems: vec![ValidationProblem::new("", "query_data must be a JSON object")],
+            })
+        }
+    };
+
+    let mut problems = Vec::new();
+    for (key, value) in object {
+        if !QUERY_KEYS.contains(&key.as_str()) {
+            problems.push(ValidationProblem::new(key, "unknown query key"));
+            continue;
+        }
+        let problem = match key.as_str() {
+            "fields" | "searchTargetFields" if !is_string_list(value) => Some("must be an array of field names".to_string()),
+            "filters" => match value.as_object() {
+                // R1 filters map a field to the list of values it may have
+                Some(filters) => filters.values().any(|v| !v.is_array()).then(|| "every filter must be an array of values".to_string()),
+                None => Some("must be an object of field -> values".to_string()),
+            },
+            "groupFilters" if !value.is_array() => Some("must be an array".to_string()),
+            "searchString" | "sortField" if !value.is_string() => Some("must be a string".to_string()),
+            "sortOrder" if !value.as_str().is_some_and(|o| o.eq_ignore_ascii_case("ASC") || o.eq_ignore_ascii_case("DESC")) => {
+                Some("must be ASC or DESC".to_string())
+            }
+            "page" if value.as_u64().is_none_or(|page| page == 0) => Some("must be a whole number of at least 1".to_string()),
+            "pageSize" | "defaultPageSize" if !value.as_u64().is_some_and(|size| (1..=MAX_QUERY_PAGE_SIZE).contains(&size)) => {
+                Some(format!("must be a whole number from 1 to {}", MAX_QUERY_PAGE_SIZE))
+            }
+            _ => None,
+        };
+        if let Some(message) = problem {
+            problems.push(ValidationProblem::new(key, message));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(MigrationError::Validation { problems })
+    }
+}
+
+fn is_string_list(value: &Value) -> bool {
+    value.as_array().is_some_and(|items| items.iter().all(Value::is_string))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn query_validation_reports_every_problem() {
+        assert!(validate_query(json!({ "fields": ["name"], "filters": { "venueId": ["v1"] }, "pageSize": 500, "sortOrder": "asc" })).is_ok());
+        assert!(validate_query(Value::Null).is_ok());
+
+        let Err(MigrationError::Validation { problems }) =
+            validate_query(json!({ "pageSize": -1, "filter": {}, "fields": "name", "filters": { "venueId": "v1" }, "page": 0 }))
+        else {
+            panic!("expected a validation error");
+        };
+        let mut fields: Vec<&str> = problems.iter().map(|p| p.field.as_str()).collect();
+        fields.sort();
+        assert_eq!(fields, ["fields", "filter", "filters", "page", "pageSize"]);
+
+        let too_big = validate_query(json!({ "pageSize": MAX_QUERY_PAGE_SIZE + 1 }));
+        assert!(matches!(too_big, Err(MigrationError::Validation { .. })));
+    }
+}
@@ -0,0 +1,135 @@
+use std::collections::HashSet;
+
+use reqwest::Method;
+use serde::Serialize;
+use serde_json::Value;
+use tauri::State;
+
+use crate::auth::Auth;
+use crate::client::AppClient;
+use crate::error::MigrationError;
+use crate::query::{self, Resource};
+use crate::state::AppState;
+
+const RESOURCES: [Resource; 4] = [Resource::Venues, Resource::WifiNetworks, Resource::Aps, Resource::Switches];
+
+#[derive(Debug, Serialize)]
+pub struct VerifyReport {
+    // Every count matched and no sampled record was missing
+    pub passed: bool,
+    pub resources: Vec<ResourceCheck>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ResourceCheck {
+    pub resource: &'static str,
+    // None when the endpoint didn't report a totalCount
+    pub source_count: Option<u64>,
+    pub target_count: Option<u64>,
+    // Only true when both counts are known and equal
+    pub counts_match: bool,
+    // Sampled source records (by name, or serial number for devices) not found on the target
+    pub missing: Vec<String>,
+}
+
+// Did everything come over? Compares the number of venues, wifi networks, APs and
+// switches of the source and target tenants. With `sample`, the first `sample`
+// records of each type on the source are also looked up on the target by name
+// (serial number for APs and switches), which catches a renamed or re-created
+// resource that the counts alone would miss.
+#[tauri::command]
+pub async fn verify_migration(
+    state: State<'_, AppState>,
+    api_url: Option<String>,
+    source_id: String,
+    target_id: String,
+    auth: Auth,
+    sample: Option<u64>,
+) -> Result<VerifyReport, MigrationError> {
+    let AppState { client, config, .. } = state.inner();
+    let api_url = &config.api_url_or(api_url.as_deref())?;
+    let source = tenant_scope(client, api_url, &auth, &source_id).await?;
+    let target = tenant_scope(client, api_url, &auth, &target_id).await?;
+
+    let mut resources = Vec::with_capacity(RESOURCES.len());
+    for resource in RESOURCES {
+        let source_count = query::count_resource(client, api_url, &auth, &source, resource).await?;
+        let target_count = query::count_resource(client, api_url, &auth, &target, resource).await?;
+
+        let missing = match sample.filter(|n| *n > 0) {
+            Some(n) => {
+                let sampled = fetch_page(client, api_url, &auth, &source, resource, n).await?;
+                let on_target = query::fetch_resource(client, api_url, &auth, &target, resource).await?;
+                missing_keys(resource, &sampled, &on_target)
+            }
+            None => Vec::new(),
+        };
+
+        resources.push(ResourceCheck {
+            resource: label(resource),
+            source_count,
+            target_count,
+            counts_match: source_count.is_some() && source_count == target_count,
+            missing,
+        });
+    }
+
+    let passed = resources.iter().all(|r| r.counts_match && r.missing.is_empty());
+    tracing::info!("Verified {} -> {}: {}", source_id, target_id, if passed { "passed" } else { "differences found" });
+    Ok(VerifyReport { passed, resources })
+}
+
+// Child resources are queried with the tenant's own id, as in migrate_tenant
+async fn tenant_scope(client: &AppClient, api_url: &str, auth: &Auth, tenant_id: &str) -> Result<String, MigrationError> {
+    let tenant = query::fetch_tenant(client, api_url, auth, tenant_id).await?;
+    Ok(query::scoped_tenant_id(&tenant, tenant_id))
+}
+
+async fn fetch_page(client: &AppClient, api_url: &str, auth: &Auth, tenant_id: &str, resource: Resource, size: u64) -> Result<Vec<Value>, MigrationError> {
+    let mut query = resource.default_query();
+    query["page"] = Value::from(1);
+    query["pageSize"] = Value::from(size);
+    let response = client
+        .send_json(Method::POST, &format!("{}/{}", api_url, resource.query_path(client.paths(api_url))), auth, Some(tenant_id), Some(&query))
+        .await?;
+    Ok(query::records(&response))
+}
+
+fn missing_keys(resource: Resource, sampled: &[Value], on_target: &[Value]) -> Vec<String> {
+    let key = |record: &Value| record.get(key_field(resource)).and_then(Value::as_str).map(str::to_string);
+    let present: HashSet<String> = on_target.iter().filter_map(key).collect();
+    sampled.iter().filter_map(key).filter(|k| !present.contains(k)).collect()
+}
+
+// Ids differ between tenants, so records are matched on what migrate_tenant copies over
+fn key_field(resource: Resource) -> &'static str {
+    match resource {
+        Resource::Venues | Resource::WifiNetworks => "name",
+        Resource::Aps | Resource::Switches => "serialNumber",
+    }
+}
+
+fn label(resource: Resource) -> &'static str {
+    match resource {
+        Resource::Venues => "venues",
+        Resource::WifiNetworks => "wifi_networks",
+        Resource::Aps => "aps",
+        Resource::Switches => "switches",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn sampled_records_are_matched_by_name_or_serial() {
+        let venues = [json!({ "id": "s1", "name": "HQ" }), json!({ "id": "s2", "name": "Depot" })];
+        assert_eq!(missing_keys(Resource::Venues, &venues, &[json!({ "id": "t1", "name": "HQ" })]), ["Depot"]);
+
+        let aps = [json!({ "serialNumber": "123456789012", "name": "Lobby" })];
+        assert!(missing_keys(Resource::Aps, &aps, &[json!({ "serialNumber": "123456789012", "name": "Renamed" })]).is_empty());
+    }
+}
@@ -1,4 +1,4 @@
-import { MSPAccount, MigrationResult, Region, TenantCreationData, FieldConfig } from "../types";
+import { MSPAccount, MigrationResult, Region, TenantCreationData, FieldConfig, MigrationError } from "../types";
 import { GET, POST, PUT, DELETE, POSTFormEncoded } from "./httpReqs";
 import { fetch } from "@tauri-apps/plugin-http";
 import { writeTextFile, readTextFile, BaseDirectory } from "@tauri-apps/plugin-fs";
@@ -162,6 +162,47 @@ const saveMSPsToFile = async (): Promise<void> => {
 }
 
 
+/* Turn a rejected invoke() value into a readable message */
+const describeInvokeError = (error: unknown): string => {
+  if (error && typeof error === 'object' && 'kind' in error) {
+    const e = error as MigrationError;
+    switch (e.kind) {
+      case 'Http':
+      case 'Auth': {
+        // x-request-id we sent, for support; R1's own requestId is shown when it has one
+        const sent = e.request_id ? ` [x-request-id ${e.request_id}]` : '';
+        if (e.api_error?.message) {
+          const ref = e.api_error.request_id ? ` (requestId ${e.api_error.request_id})` : '';
+          return `HTTP ${e.status}: ${e.api_error.message}${ref}${sent}`;
+        }
+        return `HTTP ${e.status}: ${e.body}${sent}`;
+      }
+      case 'Timeout':
+        return `${e.connect ? `Could not connect within ${e.secs}s` : `Request timed out after ${e.secs}s`}${e.request_id ? ` [x-request-id ${e.request_id}]` : ''}`;
+      case 'CircuitOpen':
+        return `${e.host} keeps failing; requests are paused for ${e.retry_in_secs}s`;
+      case 'ResponseTooLarge':
+        return `Response exceeded the ${e.limit} byte limit`;
+      case 'Decode':
+        return `HTTP ${e.status} response is not valid text (${e.length} bytes, Content-Type ${e.content_type ?? 'missing'}): ${e.preview_hex}${e.request_id ? ` [x-request-id ${e.request_id}]` : ''}`;
+      case 'RequestTooLarge':
+        return `Request body is ${e.size} bytes, over the ${e.limit} byte limit`;
+      case 'Conflict':
+        return 'The tenant was changed by someone else. Re-fetch it before saving.';
+      case 'PartialMigration':
+        return `Migration stopped at ${e.failure.resource} ${e.failure.item}: ${describeInvokeError(e.failure.error)}`;
+      case 'Cancelled':
+        return `Cancelled after creating ${e.created.length} resource(s)`;
+      case 'Validation':
+        return `Validation failed: ${e.problems.map((p) => p.field ? `${p.field}: ${p.message}` : p.message).join(', ')}`;
+      default:
+        return `${e.kind} error: ${e.message}`;
+    }
+  }
+  return error instanceof Error ? error.message : String(error);
+};
+
+
 /* Fetch region-based URL */
 function getRegionUrl(region: Region): string {
   switch (region) {
@@ -310,7 +351,7 @@ export const performTenantMigration = async (
       const resp = await invoke<string>('get_tenant', {
         apiUrl: getAPIUrlByRegion(sourceMSP.region),
         tenantId: tenantId,
-        token: sessionToken.trim()
+        auth: sessionToken.trim()
       });
 
       const data = JSON.parse(resp);
@@ -433,10 +474,10 @@ export const queryAllAPs = async (
     console.log(`Querying wifi networks for tenant ${tenantId} in region ${region}...`);
     console.log('Query parameters:', JSON.stringify(queryParams, null, 2));
 
-    const response = await invoke<string>('querywNetworks', {
+    const response = await invoke<string>('query_wifi_networks', {
       apiUrl: getAPIUrlByRegion(region),
       tenantId: tenantId,
-      token: token.trim(),
+      auth: token.trim(),
       queryData: queryParams
     });
 
@@ -447,7 +488,7 @@ export const queryAllAPs = async (
     return data;
   } catch (error) {
     console.error('Error querying wifi networks:', error);
-    const errorMessage = error instanceof Error ? error.message : String(error);
+    const errorMessage = describeInvokeError(error);
     
     if (errorMessage.includes('HTTP 401')) {
       throw new Error('Unauthorized: Invalid or expired token');
@@ -499,10 +540,10 @@ export const querywNetworks = async (
     console.log(`Querying wifi networks for tenant ${tenantId} in region ${region}...`);
     console.log('Query parameters:', JSON.stringify(queryParams, null, 2));
 
-    const response = await invoke<string>('querywNetworks', {
+    const response = await invoke<string>('query_wifi_networks', {
       apiUrl: getAPIUrlByRegion(region),
       tenantId: tenantId,
-      token: token.trim(),
+      auth: token.trim(),
       queryData: queryParams
     });
 
@@ -513,7 +554,7 @@ export const querywNetworks = async (
     return data;
   } catch (error) {
     console.error('Error querying wifi networks:', error);
-    const errorMessage = error instanceof Error ? error.message : String(error);
+    const errorMessage = describeInvokeError(error);
     
     if (errorMessage.includes('HTTP 401')) {
       throw new Error('Unauthorized: Invalid or expired token');
@@ -529,6 +570,27 @@ export const querywNetworks = async (
   }
 };
 
+// Wifi networks of a single venue; the filter payload is built by the backend
+export const queryNetworksByVenue = async (
+  tenantId: string,
+  venueId: string,
+  token: string,
+  region: Region
+): Promise<WifiNetworksQueryParams> => {
+  try {
+    const response = await invoke<string>('query_networks_by_venue', {
+      apiUrl: getAPIUrlByRegion(region),
+      tenantId: tenantId,
+      venueId: venueId,
+      auth: token.trim()
+    });
+    return JSON.parse(response);
+  } catch (error) {
+    console.error(`Error querying wifi networks for venue ${venueId}:`, error);
+    throw new Error(`Failed to query wifi networks for venue ${venueId}: ${describeInvokeError(error)}`);
+  }
+};
+
 
 /**
  * Query APs for a tenant
@@ -558,7 +620,7 @@ export const queryAPs = async (
     const response = await invoke<string>('query_aps', {
       apiUrl: getAPIUrlByRegion(region),
       tenantId: tenantId,
-      token: token.trim(),
+      auth: token.trim(),
       queryData: queryParams
     });
 
@@ -569,7 +631,7 @@ export const queryAPs = async (
     return data;
   } catch (error) {
     console.error('Error querying APs:', error);
-    const errorMessage = error instanceof Error ? error.message : String(error);
+    const errorMessage = describeInvokeError(error);
     
     if (errorMessage.includes('HTTP 401')) {
       throw new Error('Unauthorized: Invalid or expired token');
@@ -625,7 +687,7 @@ export const getVenues = async (
     const response = await invoke<string>('query_venues', {
       apiUrl: getAPIUrlByRegion(region),
       tenantId: tenantId,
-      token: token.trim(),
+      auth: token.trim(),
       queryData: queryParams
     });
 
@@ -636,7 +698,7 @@ export const getVenues = async (
     return data;
   } catch (error) {
     console.error('Error querying venues:', error);
-    const errorMessage = error instanceof Error ? error.message : String(error);
+    const errorMessage = describeInvokeError(error);
     
     // Parse error message for HTTP status codes
     if (errorMessage.includes('HTTP 401')) {
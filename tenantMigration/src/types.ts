@@ -75,4 +75,151 @@ export interface FieldConfig {
   minLength: number;
   required: boolean;
   type: 'string' | 'object' | 'array';
-}
\ No newline at end of file
+}
+// Resource created on the migration target (see src-tauri/src/migration.rs)
+export interface ResourceRef {
+  kind: 'tenant' | 'venue' | 'wifi_network' | 'ap' | 'switch';
+  id: string;
+  name: string;
+  venue_id: string | null;
+  // Source record it was copied from; missing in reports resumed from old checkpoints
+  source_id?: string | null;
+}
+
+// Optional `source_kind` argument of migrate_tenant; 'file' reads `source_file` instead of the API
+export type SourceKind = 'api' | 'file';
+
+// Optional `selection` argument of migrate_tenant; omitted fields default to true
+export interface ResourceSelection {
+  venues?: boolean;
+  wifi_networks?: boolean;
+  aps?: boolean;
+  switches?: boolean;
+}
+
+// Rewrite applied to records before they're created on the target (src-tauri/src/transform.rs).
+// `path` is dot-separated keys, "*" matching every key/element, e.g. "address.timezone".
+export type TransformRule = { resource?: ResourceRef['kind']; path: string } & (
+  | { op: 'remove' }
+  | { op: 'rename'; to: string }
+  | { op: 'replace'; value: unknown }
+  | { op: 'replace_text'; from: string; to: string }
+);
+
+// Parsed R1 error response attached to Http/Auth errors
+export interface ApiErrorBody {
+  code: string | null;
+  message: string | null;
+  request_id: string | null;
+}
+
+// Error object returned by rejected Rust commands (see src-tauri/src/error.rs)
+export type MigrationError =
+  | { kind: 'Network'; message: string; attempts: number; failure: 'dns' | 'tls' | 'refused' | 'other'; host: string | null; request_id: string | null }
+  | { kind: 'Http'; status: number; body: string; attempts: number; api_error: ApiErrorBody | null; request_id: string | null }
+  | { kind: 'Timeout'; secs: number; connect: boolean; request_id: string | null }
+  | { kind: 'Auth'; status: number; body: string; api_error: ApiErrorBody | null; request_id: string | null }
+  | { kind: 'CircuitOpen'; host: string; retry_in_secs: number }
+  | { kind: 'ResponseTooLarge'; limit: number }
+  | { kind: 'Decode'; status: number; content_type: string | null; length: number; preview_hex: string; request_id: string | null }
+  | { kind: 'RequestTooLarge'; size: number; limit: number }
+  | { kind: 'PayloadTooLarge'; body: string; request_id: string | null }
+  | { kind: 'Conflict'; body: string }
+  | { kind: 'Serde'; message: string }
+  | { kind: 'InvalidInput'; message: string }
+  | { kind: 'Validation'; problems: { field: string; message: string }[] }
+  | { kind: 'Cancelled'; created: ResourceRef[] }
+  | { kind: 'DeadlineExceeded'; limit_secs: number; created: ResourceRef[] }
+  | { kind: 'TargetNotEmpty'; tenant_id: string; counts: ResourceCounts }
+  | { kind: 'FetchFailed'; failures: { resource: string; error: MigrationError }[] }
+  | { kind: 'PartialMigration'; failure: { resource: string; item: string; error: MigrationError }; created: ResourceRef[] }
+  | { kind: 'Io'; path: string; message: string }
+  | { kind: 'Keychain'; message: string }
+  | { kind: 'UnexpectedResponse'; message: string };
+
+// query_resource_verbose / get_resource_verbose; header names are lowercase
+export interface VerboseResponse {
+  status: number;
+  headers: Record<string, string>;
+  body: string;
+}
+
+// check_permissions; null when the probe couldn't tell
+export interface Permissions {
+  can_read_own_tenant: boolean | null;
+  can_read_tenants: boolean | null;
+  can_read_venues: boolean | null;
+  can_create_tenants: boolean | null;
+}
+
+// `auth` argument of API commands; a bare string is a bearer token
+export type Auth =
+  | string
+  | { scheme: 'bearer'; value: string }
+  | { scheme: 'api_key'; value: string };
+
+// discover_endpoint; 'unsupported' when the server doesn't answer OPTIONS
+export type EndpointMethods =
+  | { status: 'allowed'; methods: string[] }
+  | { status: 'unsupported' };
+
+// merge_tenants: what happens to a venue whose name (or dedup_field) is already taken
+export type MergeStrategy = 'skip' | 'rename' | 'overwrite';
+
+// set_http_version; 'auto' lets TLS negotiate
+export type HttpVersion = 'auto' | 'http1' | 'http2';
+
+// plan_migration: what migrate_tenant would create, for approval before running it
+export interface PlannedOperation {
+  method: string;
+  endpoint: string;
+  resource: ResourceRef['kind'];
+  name: string;
+  payload: unknown;
+}
+
+export interface MigrationPlan {
+  target_api_url: string;
+  operations: PlannedOperation[];
+  counts: { venues: number; wifi_networks: number; aps: number; switches: number };
+  problems: string[];
+}
+
+// Payload of the "migration-progress" event
+export interface MigrationProgress {
+  phase: 'fetch' | 'transform' | 'push';
+  stage: string;
+  processed: number;
+  total: number;
+  phase_total: number | null;
+  eta_seconds: number | null;
+}
+
+// Payload of the "download-progress" event sent by download_export
+export interface DownloadProgress {
+  file_path: string;
+  bytes_written: number;
+}
+
+// Returned by detect_capabilities: collection paths the deployment uses
+export interface ApiPaths {
+  tenants: 'mspCustomers' | 'tenants';
+  tenant_records: 'tenants' | 'mspCustomers';
+  wifi_networks: 'wifiNetworks' | 'wlans';
+}
+
+// Returned by resource_counts; null where the API didn't report a totalCount
+export interface ResourceCounts {
+  venues: number | null;
+  wifi_networks: number | null;
+  aps: number | null;
+  switches: number | null;
+}
+
+// Returned by preflight_target; `warning` is set whenever `empty` is false
+export interface TargetPreflight {
+  tenant_id: string;
+  empty: boolean;
+  counts: ResourceCounts;
+  warning: string | null;
+}